@@ -0,0 +1,8 @@
+DROP INDEX IF EXISTS label_deleted_idx;
+ALTER TABLE labels DROP COLUMN IF EXISTS deleted_at;
+
+DROP INDEX IF EXISTS task_deleted_idx;
+ALTER TABLE tasks DROP COLUMN IF EXISTS deleted_at;
+
+DROP INDEX IF EXISTS list_deleted_idx;
+ALTER TABLE lists DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,8 @@
+ALTER TABLE lists ADD COLUMN deleted_at TIMESTAMP;
+CREATE INDEX list_deleted_idx ON lists(deleted_at);
+
+ALTER TABLE tasks ADD COLUMN deleted_at TIMESTAMP;
+CREATE INDEX task_deleted_idx ON tasks(deleted_at);
+
+ALTER TABLE labels ADD COLUMN deleted_at TIMESTAMP;
+CREATE INDEX label_deleted_idx ON labels(deleted_at);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS actions_user_created_idx;
+ALTER TABLE actions DROP COLUMN IF EXISTS undone_at;
@@ -0,0 +1,2 @@
+ALTER TABLE actions ADD COLUMN undone_at TIMESTAMP;
+CREATE INDEX actions_user_created_idx ON actions(user_id, created_at);
@@ -0,0 +1,4 @@
+ALTER TABLE tasks
+  DROP COLUMN recurrence,
+  DROP COLUMN recurrence_from_completion,
+  DROP COLUMN recurrence_keep_history;
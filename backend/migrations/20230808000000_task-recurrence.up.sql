@@ -0,0 +1,4 @@
+ALTER TABLE tasks
+  ADD COLUMN recurrence TEXT,
+  ADD COLUMN recurrence_from_completion BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN recurrence_keep_history BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+ALTER TABLE tasks ADD COLUMN priority SMALLINT NOT NULL DEFAULT 4
+  CONSTRAINT task_priority_constraint CHECK (priority BETWEEN 1 AND 4);
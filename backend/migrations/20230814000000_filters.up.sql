@@ -0,0 +1,7 @@
+CREATE TABLE filters (
+  id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  user_id UUID NOT NULL REFERENCES users,
+  title TEXT NOT NULL,
+  query TEXT NOT NULL
+);
+CREATE INDEX filter_user_idx ON filters(user_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS label_search_idx;
+DROP INDEX IF EXISTS list_search_idx;
+DROP INDEX IF EXISTS task_search_idx;
@@ -0,0 +1,6 @@
+CREATE INDEX task_search_idx ON tasks USING GIN ((
+  setweight(to_tsvector('english', title), 'A') ||
+  setweight(to_tsvector('english', COALESCE(description, '')), 'B')
+));
+CREATE INDEX list_search_idx ON lists USING GIN (to_tsvector('english', title));
+CREATE INDEX label_search_idx ON labels USING GIN (to_tsvector('english', title));
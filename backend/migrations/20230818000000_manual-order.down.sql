@@ -0,0 +1,10 @@
+DROP TRIGGER IF EXISTS set_label_rank ON labels;
+DROP TRIGGER IF EXISTS set_task_rank ON tasks;
+DROP TRIGGER IF EXISTS set_list_rank ON lists;
+ALTER TABLE labels DROP COLUMN rank;
+ALTER TABLE tasks DROP COLUMN rank;
+ALTER TABLE lists DROP COLUMN rank;
+DROP FUNCTION IF EXISTS set_label_rank();
+DROP FUNCTION IF EXISTS set_task_rank();
+DROP FUNCTION IF EXISTS set_list_rank();
+DROP FUNCTION IF EXISTS rank_after(_rank TEXT);
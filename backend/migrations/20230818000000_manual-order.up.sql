@@ -0,0 +1,86 @@
+-- Ranks are base 62 fractions compared byte by byte, see `rank.rs`.
+CREATE OR REPLACE FUNCTION rank_after(_rank TEXT) RETURNS TEXT AS $$
+DECLARE
+    digits CONSTANT TEXT := '0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz';
+BEGIN
+    IF _rank IS NULL OR _rank = '' THEN
+        RETURN 'V';
+    END IF;
+    FOR i IN REVERSE length(_rank)..1 LOOP
+        IF substr(_rank, i, 1) <> 'z' THEN
+            RETURN substr(_rank, 1, i - 1) ||
+                substr(digits, strpos(digits, substr(_rank, i, 1)) + 1, 1);
+        END IF;
+    END LOOP;
+    RETURN _rank || 'V';
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+-- New items without a rank go after their last sibling.
+CREATE OR REPLACE FUNCTION set_list_rank() RETURNS trigger AS $$
+BEGIN
+    IF NEW.rank IS NULL THEN
+        NEW.rank := rank_after((
+            SELECT MAX(rank) FROM lists
+            WHERE user_id = NEW.user_id AND parent_id IS NOT DISTINCT FROM NEW.parent_id
+        ));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION set_task_rank() RETURNS trigger AS $$
+BEGIN
+    IF NEW.rank IS NULL THEN
+        NEW.rank := rank_after((
+            SELECT MAX(rank) FROM tasks
+            WHERE parent_id IS NOT DISTINCT FROM NEW.parent_id
+                AND (NEW.parent_id IS NOT NULL OR list_id = NEW.list_id)
+        ));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION set_label_rank() RETURNS trigger AS $$
+BEGIN
+    IF NEW.rank IS NULL THEN
+        NEW.rank := rank_after((SELECT MAX(rank) FROM labels WHERE user_id = NEW.user_id));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Existing items keep the order they were created in.
+ALTER TABLE lists ADD COLUMN rank TEXT COLLATE "C";
+UPDATE lists SET rank = ranked.rank FROM (
+    SELECT id, lpad(ROW_NUMBER() OVER (PARTITION BY user_id, parent_id ORDER BY id)::TEXT, 10, '0') || 'V' AS rank
+    FROM lists
+) ranked WHERE lists.id = ranked.id;
+ALTER TABLE lists ALTER COLUMN rank SET NOT NULL;
+CREATE INDEX list_rank_idx ON lists(user_id, parent_id, rank);
+CREATE TRIGGER set_list_rank BEFORE INSERT ON lists
+    FOR EACH ROW EXECUTE PROCEDURE set_list_rank();
+
+ALTER TABLE tasks ADD COLUMN rank TEXT COLLATE "C";
+UPDATE tasks SET rank = ranked.rank FROM (
+    SELECT id, lpad(ROW_NUMBER() OVER (
+        PARTITION BY parent_id, CASE WHEN parent_id IS NULL THEN list_id END
+        ORDER BY created_at, id
+    )::TEXT, 10, '0') || 'V' AS rank
+    FROM tasks
+) ranked WHERE tasks.id = ranked.id;
+ALTER TABLE tasks ALTER COLUMN rank SET NOT NULL;
+CREATE INDEX task_rank_idx ON tasks(list_id, parent_id, rank);
+CREATE TRIGGER set_task_rank BEFORE INSERT ON tasks
+    FOR EACH ROW EXECUTE PROCEDURE set_task_rank();
+
+ALTER TABLE labels ADD COLUMN rank TEXT COLLATE "C";
+UPDATE labels SET rank = ranked.rank FROM (
+    SELECT id, lpad(ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY id)::TEXT, 10, '0') || 'V' AS rank
+    FROM labels
+) ranked WHERE labels.id = ranked.id;
+ALTER TABLE labels ALTER COLUMN rank SET NOT NULL;
+CREATE INDEX label_rank_idx ON labels(user_id, rank);
+CREATE TRIGGER set_label_rank BEFORE INSERT ON labels
+    FOR EACH ROW EXECUTE PROCEDURE set_label_rank();
@@ -0,0 +1,21 @@
+CREATE TABLE reminders (
+  id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  task_id UUID NOT NULL REFERENCES tasks ON DELETE CASCADE,
+  -- Either a fixed time in UTC or minutes relative to the task's due date
+  remind_at TIMESTAMP,
+  offset_minutes INTEGER,
+  channel TEXT NOT NULL,
+  target TEXT,
+  -- The time the reminder was last delivered (or given up) for,
+  -- so each time it fires is only handled once
+  delivered_for TIMESTAMP,
+  delivered_at TIMESTAMP,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TIMESTAMP,
+  last_error TEXT,
+  CONSTRAINT reminder_time_constraint
+    CHECK ((remind_at IS NULL) <> (offset_minutes IS NULL)),
+  CONSTRAINT reminder_channel_constraint
+    CHECK (channel IN ('log', 'email', 'webhook'))
+);
+CREATE INDEX reminder_task_idx ON reminders(task_id);
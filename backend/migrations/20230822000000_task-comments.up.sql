@@ -0,0 +1,10 @@
+CREATE TABLE task_comments (
+  id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  task_id UUID NOT NULL REFERENCES tasks ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES users,
+  body TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+CREATE INDEX task_comment_task_idx ON task_comments(task_id, created_at);
+SELECT manage_updated_at('task_comments');
@@ -0,0 +1,12 @@
+CREATE TABLE attachments (
+  id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  task_id UUID NOT NULL REFERENCES tasks ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES users,
+  file_name TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  size BIGINT NOT NULL,
+  storage_key TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+CREATE INDEX attachment_task_idx ON attachments(task_id);
+CREATE INDEX attachment_user_idx ON attachments(user_id);
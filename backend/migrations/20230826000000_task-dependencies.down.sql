@@ -0,0 +1,2 @@
+DROP FUNCTION IF EXISTS task_blocked(UUID);
+DROP TABLE IF EXISTS task_dependencies;
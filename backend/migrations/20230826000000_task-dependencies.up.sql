@@ -0,0 +1,18 @@
+CREATE TABLE task_dependencies (
+  task_id UUID NOT NULL REFERENCES tasks ON DELETE CASCADE,
+  blocked_by_id UUID NOT NULL REFERENCES tasks ON DELETE CASCADE,
+  PRIMARY KEY (task_id, blocked_by_id),
+  CONSTRAINT task_dependency_self_constraint CHECK (task_id <> blocked_by_id)
+);
+CREATE INDEX task_dependency_blocked_by_idx ON task_dependencies(blocked_by_id);
+
+-- A task is blocked while any task it depends on is still open.
+CREATE OR REPLACE FUNCTION task_blocked(_task_id UUID) RETURNS BOOLEAN AS $$
+    SELECT EXISTS (
+        SELECT 1 FROM task_dependencies
+            INNER JOIN tasks blockers ON blockers.id = task_dependencies.blocked_by_id
+        WHERE task_dependencies.task_id = _task_id
+            AND NOT blockers.completed
+            AND blockers.deleted_at IS NULL
+    );
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS task_completed_at_idx;
+DROP TRIGGER IF EXISTS set_completed_at ON tasks;
+DROP FUNCTION IF EXISTS set_completed_at();
+ALTER TABLE tasks DROP COLUMN IF EXISTS completed_at;
@@ -0,0 +1,28 @@
+ALTER TABLE tasks ADD COLUMN completed_at TIMESTAMP;
+
+-- The best guess for tasks completed so far is their last update.
+ALTER TABLE tasks DISABLE TRIGGER set_updated_at;
+UPDATE tasks SET completed_at = updated_at WHERE completed;
+ALTER TABLE tasks ENABLE TRIGGER set_updated_at;
+
+-- Keeps `completed_at` in step with `completed`, unless it is set along
+-- with it, like when a change is undone.
+CREATE OR REPLACE FUNCTION set_completed_at() RETURNS trigger AS $$
+BEGIN
+    IF NOT NEW.completed THEN
+        NEW.completed_at := NULL;
+    ELSIF NEW.completed_at IS NULL OR (
+        TG_OP = 'UPDATE' AND
+        NOT OLD.completed AND
+        NEW.completed_at IS NOT DISTINCT FROM OLD.completed_at
+    ) THEN
+        NEW.completed_at := current_timestamp;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_completed_at BEFORE INSERT OR UPDATE ON tasks
+    FOR EACH ROW EXECUTE PROCEDURE set_completed_at();
+
+CREATE INDEX task_completed_at_idx ON tasks(list_id, completed_at DESC) WHERE completed;
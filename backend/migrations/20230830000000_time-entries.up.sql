@@ -0,0 +1,17 @@
+CREATE TABLE time_entries (
+  id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  user_id UUID NOT NULL REFERENCES users ON DELETE CASCADE,
+  task_id UUID NOT NULL REFERENCES tasks ON DELETE CASCADE,
+  started_at TIMESTAMP NOT NULL,
+  -- NULL while the timer is running
+  ended_at TIMESTAMP,
+  note TEXT,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  CONSTRAINT time_entry_range_constraint CHECK (ended_at >= started_at)
+);
+CREATE INDEX time_entry_task_idx ON time_entries(task_id, started_at);
+CREATE INDEX time_entry_user_idx ON time_entries(user_id, started_at);
+-- A user can only time one task at a time.
+CREATE UNIQUE INDEX time_entry_running_idx ON time_entries(user_id) WHERE ended_at IS NULL;
+SELECT manage_updated_at('time_entries');
@@ -0,0 +1,12 @@
+CREATE TABLE templates (
+  id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  user_id UUID NOT NULL REFERENCES users ON DELETE CASCADE,
+  title TEXT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('task', 'list')),
+  -- A snapshot of the saved tree, see `api::templates::TemplateContent`
+  content JSONB NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+CREATE INDEX template_user_idx ON templates(user_id);
+SELECT manage_updated_at('templates');
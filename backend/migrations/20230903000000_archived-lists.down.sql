@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS list_archived_idx;
+ALTER TABLE lists DROP COLUMN IF EXISTS archived_at;
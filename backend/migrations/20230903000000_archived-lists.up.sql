@@ -0,0 +1,4 @@
+-- Archived lists and their tasks are hidden unless asked for by id.
+-- Sublists are archived with their parent and share its timestamp.
+ALTER TABLE lists ADD COLUMN archived_at TIMESTAMP;
+CREATE INDEX list_archived_idx ON lists(archived_at);
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS queue_storage_deletion ON attachments;
+DROP FUNCTION IF EXISTS queue_storage_deletion();
+DROP TABLE IF EXISTS storage_deletions;
@@ -0,0 +1,17 @@
+-- Keys of stored files whose attachment is gone, like when its task is
+-- purged from the trash. The trash purge worker deletes the files.
+CREATE TABLE storage_deletions (
+  storage_key TEXT PRIMARY KEY NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+
+CREATE OR REPLACE FUNCTION queue_storage_deletion() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO storage_deletions (storage_key) VALUES (OLD.storage_key)
+        ON CONFLICT DO NOTHING;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER queue_storage_deletion AFTER DELETE ON attachments
+    FOR EACH ROW EXECUTE PROCEDURE queue_storage_deletion();
@@ -0,0 +1,404 @@
+use rocket::{http::Status, Build, Rocket};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Connection as _, PgConnection};
+use time::PrimitiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    api::utils::serde::primitive_date_iso_serialize,
+    database::BackendDb,
+    guards::auth::Auth,
+    models::{action::ActionModel, user::UserModel},
+    responses::{
+        conflict, internal_server_error, result_not_found, APIResponse, APIResult, MapAPIResponse,
+    },
+};
+
+#[post("/undo")]
+async fn undo(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>) -> APIResult {
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to start transaction.")?;
+
+    let action = sqlx::query_as!(
+        ActionModel,
+        "SELECT * FROM actions
+            WHERE user_id = $1 AND undone_at IS NULL
+            ORDER BY created_at DESC LIMIT 1 FOR UPDATE",
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch action.")?;
+    let Some(action) = action else {
+        return result_not_found("Nothing to undo.");
+    };
+
+    let data: ActionData = serde_json::from_value(action.data.clone())
+        .map_internal_server_error("Failed to parse action.")?;
+    for change in data.changes.iter().rev() {
+        apply_change(&mut tx, &change.table, &change.after, &change.before).await?;
+    }
+    check_hierarchy(&mut tx, auth_user.id, &data.changes).await?;
+    check_dependencies(&mut tx, auth_user.id, &data.changes).await?;
+
+    sqlx::query!(
+        "UPDATE actions SET undone_at = clock_timestamp() WHERE id = $1",
+        action.id
+    )
+    .execute(&mut tx)
+    .await
+    .map_internal_server_error("Failed to update action.")?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to commit transaction.")?;
+
+    action_response(action)
+}
+
+#[post("/redo")]
+async fn redo(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>) -> APIResult {
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to start transaction.")?;
+
+    let action = sqlx::query_as!(
+        ActionModel,
+        "SELECT * FROM actions
+            WHERE user_id = $1 AND undone_at IS NOT NULL
+            ORDER BY undone_at DESC LIMIT 1 FOR UPDATE",
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch action.")?;
+    let Some(action) = action else {
+        return result_not_found("Nothing to redo.");
+    };
+
+    let data: ActionData = serde_json::from_value(action.data.clone())
+        .map_internal_server_error("Failed to parse action.")?;
+    for change in data.changes.iter() {
+        apply_change(&mut tx, &change.table, &change.before, &change.after).await?;
+    }
+    check_hierarchy(&mut tx, auth_user.id, &data.changes).await?;
+    check_dependencies(&mut tx, auth_user.id, &data.changes).await?;
+
+    sqlx::query!(
+        "UPDATE actions SET undone_at = NULL WHERE id = $1",
+        action.id
+    )
+    .execute(&mut tx)
+    .await
+    .map_internal_server_error("Failed to update action.")?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to commit transaction.")?;
+
+    action_response(action)
+}
+
+fn action_response(action: ActionModel) -> APIResult {
+    let resp = GetModel {
+        id: action.id,
+        action_type: action.action_type,
+        created_at: action.created_at,
+    };
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// Records a mutation made by the user so it can be undone later.
+///
+/// Recording a new action clears the user's redo history.
+pub async fn record_action(
+    db: &mut PgConnection,
+    user_id: Uuid,
+    action_type: &str,
+    changes: Vec<ActionChange>,
+) -> Result<(), APIResponse> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "DELETE FROM actions WHERE user_id = $1 AND undone_at IS NOT NULL",
+        user_id
+    )
+    .execute(&mut *db)
+    .await
+    .map_internal_server_error("Failed to record action.")?;
+
+    let data = serde_json::to_value(ActionData { changes })
+        .map_internal_server_error("Failed to record action.")?;
+    sqlx::query!(
+        "INSERT INTO actions (user_id, created_at, action_type, data)
+            VALUES ($1, clock_timestamp(), $2, $3)",
+        user_id,
+        action_type,
+        data
+    )
+    .execute(&mut *db)
+    .await
+    .map_internal_server_error("Failed to record action.")?;
+    Ok(())
+}
+
+/// Moves a row from the `from` state to the `to` state, where `None`
+/// means the row does not exist.
+///
+/// Fails with a conflict if the row is no longer in the `from` state,
+/// since something else has changed it in the meantime.
+async fn apply_change(
+    db: &mut PgConnection,
+    table: &str,
+    from: &Option<Value>,
+    to: &Option<Value>,
+) -> Result<(), APIResponse> {
+    let key_columns = match table {
+        "lists" | "tasks" | "labels" | "filters" | "reminders" | "task_comments"
+        | "time_entries" | "templates" => "id",
+        "task_labels" => "task_id, label_id",
+        "task_dependencies" => "task_id, blocked_by_id",
+        _ => return Err(internal_server_error("Action touches an unknown table.")),
+    };
+    let Some(row) = from.as_ref().or(to.as_ref()) else {
+        return Ok(());
+    };
+    let key_where = format!(
+        "({keys}) = (SELECT {keys} FROM jsonb_populate_record(NULL::{table}, $1))",
+        keys = key_columns,
+        table = table
+    );
+
+    let current: Option<Value> = sqlx::query_scalar(&format!(
+        "SELECT to_jsonb({table}.*) FROM {table} WHERE {key_where} FOR UPDATE",
+        table = table,
+        key_where = key_where
+    ))
+    .bind(row)
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch item.")?;
+
+    let unchanged = match (from, &current) {
+        (None, None) => true,
+        (Some(from), Some(current)) => rows_match(from, current),
+        _ => false,
+    };
+    if !unchanged {
+        return Err(conflict(
+            "The item has changed since this action, so it can't be safely reversed.",
+        ));
+    }
+
+    let query = match (&current, to) {
+        (None, Some(_)) => format!(
+            "INSERT INTO {table} SELECT * FROM jsonb_populate_record(NULL::{table}, $1)",
+            table = table
+        ),
+        (Some(_), None) => format!(
+            "DELETE FROM {table} WHERE {key_where}",
+            table = table,
+            key_where = key_where
+        ),
+        (Some(_), Some(to)) => {
+            let columns = changed_columns(to, key_columns)?;
+            if columns.is_empty() {
+                return Ok(());
+            }
+            format!(
+                "UPDATE {table} SET ({columns}) = (SELECT {columns} FROM jsonb_populate_record(NULL::{table}, $1)) WHERE {key_where}",
+                table = table,
+                columns = columns.join(", "),
+                key_where = key_where
+            )
+        }
+        (None, None) => return Ok(()),
+    };
+    sqlx::query(&query)
+        .bind(to.as_ref().unwrap_or(row))
+        .execute(&mut *db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(_) => conflict(
+                "The item's surroundings have changed since this action, so it can't be safely reversed.",
+            ),
+            _ => internal_server_error("Failed to apply action."),
+        })?;
+    Ok(())
+}
+
+/// Fails with a conflict if the restored rows put a task or list under
+/// itself, or a subtask in another list than its parent.
+///
+/// Checked once all changes are applied, since moving a subtree back
+/// passes through states where only part of it has changed lists.
+async fn check_hierarchy(
+    db: &mut PgConnection,
+    user_id: Uuid,
+    changes: &[ActionChange],
+) -> Result<(), APIResponse> {
+    let ids = |table: &str| -> Vec<Uuid> {
+        changes
+            .iter()
+            .filter(|change| change.table == table)
+            .filter_map(|change| change.before.as_ref().or(change.after.as_ref()))
+            .filter_map(|row| serde_json::from_value(row["id"].clone()).ok())
+            .collect()
+    };
+    let (task_ids, list_ids) = (ids("tasks"), ids("lists"));
+    if task_ids.is_empty() && list_ids.is_empty() {
+        return Ok(());
+    }
+
+    // Two moves at once could close a cycle neither sees alone
+    sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", user_id)
+        .fetch_one(&mut *db)
+        .await
+        .map_internal_server_error("Failed to apply action.")?;
+    let invalid = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE task_ancestors AS (
+            SELECT id AS start_id, parent_id FROM tasks WHERE id = ANY($1)
+            UNION
+            SELECT task_ancestors.start_id, tasks.parent_id FROM tasks
+                INNER JOIN task_ancestors ON tasks.id = task_ancestors.parent_id
+        ), list_ancestors AS (
+            SELECT id AS start_id, parent_id FROM lists WHERE id = ANY($2)
+            UNION
+            SELECT list_ancestors.start_id, lists.parent_id FROM lists
+                INNER JOIN list_ancestors ON lists.id = list_ancestors.parent_id
+        )
+        SELECT
+            EXISTS (SELECT 1 FROM task_ancestors WHERE parent_id = start_id)
+            OR EXISTS (SELECT 1 FROM list_ancestors WHERE parent_id = start_id)
+            OR EXISTS (
+                SELECT 1 FROM tasks
+                    INNER JOIN tasks parent_tasks ON parent_tasks.id = tasks.parent_id
+                WHERE (tasks.id = ANY($1) OR parent_tasks.id = ANY($1))
+                    AND tasks.list_id <> parent_tasks.list_id
+            ) AS "invalid!""#,
+        &task_ids,
+        &list_ids
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to apply action.")?;
+    if invalid {
+        return Err(conflict(
+            "The item's surroundings have changed since this action, so it can't be safely reversed.",
+        ));
+    }
+    Ok(())
+}
+
+/// Fails with a conflict if a restored blocker closes a cycle of tasks
+/// blocking each other.
+async fn check_dependencies(
+    db: &mut PgConnection,
+    user_id: Uuid,
+    changes: &[ActionChange],
+) -> Result<(), APIResponse> {
+    let task_ids: Vec<Uuid> = changes
+        .iter()
+        .filter(|change| change.table == "task_dependencies")
+        .filter_map(|change| change.before.as_ref().or(change.after.as_ref()))
+        .filter_map(|row| serde_json::from_value(row["task_id"].clone()).ok())
+        .collect();
+    if task_ids.is_empty() {
+        return Ok(());
+    }
+
+    // Two edges added at once could close a cycle neither sees alone
+    sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", user_id)
+        .fetch_one(&mut *db)
+        .await
+        .map_internal_server_error("Failed to apply action.")?;
+    let cycle = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE blockers AS (
+            SELECT task_id AS start_id, blocked_by_id AS id FROM task_dependencies
+                WHERE task_id = ANY($1)
+            UNION
+            SELECT blockers.start_id, task_dependencies.blocked_by_id FROM task_dependencies
+                INNER JOIN blockers ON blockers.id = task_dependencies.task_id
+        )
+        SELECT EXISTS (SELECT 1 FROM blockers WHERE id = start_id) AS "cycle!""#,
+        &task_ids
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to apply action.")?;
+    if cycle {
+        return Err(conflict(
+            "The item's surroundings have changed since this action, so it can't be safely reversed.",
+        ));
+    }
+    Ok(())
+}
+
+/// `updated_at` is maintained by the database, so it
+/// is neither compared nor written back.
+const IGNORED_COLUMN: &str = "updated_at";
+
+fn rows_match(expected: &Value, current: &Value) -> bool {
+    match (expected.as_object(), current.as_object()) {
+        (Some(expected), Some(current)) => expected
+            .iter()
+            .filter(|(column, _)| *column != IGNORED_COLUMN)
+            .all(|(column, value)| current.get(column) == Some(value)),
+        _ => false,
+    }
+}
+
+fn changed_columns<'a>(row: &'a Value, key_columns: &str) -> Result<Vec<&'a str>, APIResponse> {
+    let row = row
+        .as_object()
+        .ok_or_else(|| internal_server_error("Failed to parse action."))?;
+    let mut columns = Vec::new();
+    for column in row.keys() {
+        if !column.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+            return Err(internal_server_error("Failed to parse action."));
+        }
+        if column == IGNORED_COLUMN || key_columns.split(", ").any(|key| key == column) {
+            continue;
+        }
+        columns.push(column.as_str());
+    }
+    Ok(columns)
+}
+
+/// A single row's state before and after an action.
+///
+/// `None` means the row did not exist.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionChange {
+    pub table: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionData {
+    pub changes: Vec<ActionChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetModel {
+    pub id: Uuid,
+    pub action_type: String,
+    #[serde(serialize_with = "primitive_date_iso_serialize")]
+    pub created_at: PrimitiveDateTime,
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/actions", routes![undo, redo])
+}
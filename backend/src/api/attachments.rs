@@ -0,0 +1,355 @@
+use rocket::{
+    data::{Limits, ToByteUnit},
+    form::{self, error::ErrorKind, DataField, Form, FromFormField},
+    http::{ContentType, Status},
+    response::{self, stream::ByteStream, Responder},
+    Build, Request, Rocket, State,
+};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use sqlx::Connection as _;
+use time::PrimitiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        tasks::check_task_owner,
+        utils::{serde::primitive_date_iso_serialize, PostResponse},
+    },
+    config::AppConfig,
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{
+        bad_gateway, bad_request, internal_server_error, ok, result_not_found, APIResponse,
+        APIResult, MapAPIResponse,
+    },
+    storage::{ByteChunks, Storage},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetModel {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    #[serde(serialize_with = "primitive_date_iso_serialize")]
+    pub created_at: PrimitiveDateTime,
+}
+
+/// The file field of a multipart form, read into memory up to the
+/// `file` limit.
+pub struct UploadedFile {
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromFormField<'r> for UploadedFile {
+    async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
+        let limit = field.request.limits().get("file").unwrap_or(Limits::FILE);
+        let bytes = field.data.open(limit).into_bytes().await?;
+        if !bytes.is_complete() {
+            Err((None, Some(limit)))?;
+        }
+        let file_name = field
+            .file_name
+            .map(|name| sanitize_file_name(name.dangerous_unsafe_unsanitized_raw().as_str()))
+            .unwrap_or_default();
+        Ok(UploadedFile {
+            file_name: if file_name.is_empty() {
+                String::from("file")
+            } else {
+                file_name
+            },
+            bytes: bytes.into_inner(),
+        })
+    }
+}
+
+/// Keeps the last path segment of a client supplied name, without
+/// control characters and at most 255 characters long.
+fn sanitize_file_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    name.chars()
+        .filter(|c| !c.is_control())
+        .take(255)
+        .collect::<String>()
+        .trim()
+        .to_owned()
+}
+
+/// Picks the content type from the file's leading bytes, since the
+/// type sent by the client can't be trusted.
+fn sniff_content_type(bytes: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+    ];
+    if let Some((_, content_type)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+    {
+        return content_type;
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.contains('\0') => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Streams a stored file to the client as a download.
+pub struct Download {
+    file_name: String,
+    content_type: String,
+    chunks: ByteChunks,
+}
+
+impl<'r> Responder<'r, 'r> for Download {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'r> {
+        let mut response = ByteStream(self.chunks).respond_to(req)?;
+        response.set_header(
+            ContentType::parse_flexible(&self.content_type).unwrap_or(ContentType::Binary),
+        );
+        response.set_raw_header("Content-Disposition", content_disposition(&self.file_name));
+        // Uploaded html must never render as a page on our origin
+        response.set_raw_header("X-Content-Type-Options", "nosniff");
+        Ok(response)
+    }
+}
+
+fn content_disposition(file_name: &str) -> String {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+    let encoded: String = file_name
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback, encoded
+    )
+}
+
+#[get("/<task_id>/attachments")]
+async fn get_all(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+) -> APIResult {
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let attachments = sqlx::query_as!(
+        GetModel,
+        "SELECT id, task_id, file_name, content_type, size, created_at FROM attachments
+            WHERE task_id = $1
+            ORDER BY created_at, id",
+        task_id
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch attachments from database.")?;
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(attachments)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[post(
+    "/<task_id>/attachments",
+    data = "<input>",
+    format = "multipart/form-data"
+)]
+async fn post(
+    auth_user: Auth<UserModel>,
+    config: &State<AppConfig>,
+    storage: &State<Box<dyn Storage>>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    input: Result<Form<UploadedFile>, form::Errors<'_>>,
+) -> APIResult {
+    let file = match input {
+        Ok(input) => input.into_inner(),
+        Err(errors) => {
+            if errors
+                .iter()
+                .any(|error| matches!(error.kind, ErrorKind::InvalidLength { .. }))
+            {
+                return Err(APIResponse::new_message(
+                    Status::PayloadTooLarge,
+                    &format!(
+                        "Files can't be larger than {}.",
+                        config.attachment_max_bytes.bytes()
+                    ),
+                ));
+            }
+            return Err(bad_request("Upload a file as multipart form data."));
+        }
+    };
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let size = file.bytes.len() as i64;
+    let content_type = sniff_content_type(&file.bytes);
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to create attachment in database.")?;
+    // Uploads of the same user wait for each other so both can't fit the quota
+    sqlx::query!(
+        "SELECT id FROM users WHERE id = $1 FOR UPDATE",
+        auth_user.id
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to create attachment in database.")?;
+    let used = sqlx::query_scalar!(
+        r#"SELECT COALESCE(SUM(size), 0)::BIGINT AS "used!" FROM attachments WHERE user_id = $1"#,
+        auth_user.id
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to create attachment in database.")?;
+    if used + size > config.attachment_quota_bytes {
+        return Err(APIResponse::new_message(
+            Status::PayloadTooLarge,
+            &format!(
+                "Attachments can't take up more than {} in total.",
+                (config.attachment_quota_bytes as u64).bytes()
+            ),
+        ));
+    }
+
+    let id = Uuid::new_v4();
+    let storage_key = format!("{}/{}", auth_user.id, id);
+    storage
+        .put(&storage_key, file.bytes, content_type)
+        .await
+        .map_err(|e| {
+            error!("Failed to store attachment: {}", e);
+            bad_gateway("Failed to store attachment.")
+        })?;
+
+    let created = async {
+        sqlx::query!(
+            "INSERT INTO attachments
+                (id, task_id, user_id, file_name, content_type, size, storage_key)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            id,
+            task_id,
+            auth_user.id,
+            file.file_name,
+            content_type,
+            size,
+            storage_key
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await
+    }
+    .await;
+    if created.is_err() {
+        if let Err(e) = storage.delete(&storage_key).await {
+            warn!("Failed to delete unused attachment {}: {}", storage_key, e);
+        }
+        return Err(internal_server_error(
+            "Failed to create attachment in database.",
+        ));
+    }
+
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::to_value(PostResponse { id })
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[get("/<task_id>/attachments/<id>/content")]
+async fn download(
+    auth_user: Auth<UserModel>,
+    storage: &State<Box<dyn Storage>>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    id: Uuid,
+) -> Result<Download, APIResponse> {
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let attachment = sqlx::query!(
+        "SELECT file_name, content_type, storage_key FROM attachments
+            WHERE id = $1 AND task_id = $2",
+        id,
+        task_id
+    )
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch attachment from database.")?;
+    let Some(attachment) = attachment else {
+        return Err(APIResponse::new_message(
+            Status::NotFound,
+            "Attachment not found.",
+        ));
+    };
+
+    let chunks = storage.get(&attachment.storage_key).await.map_err(|e| {
+        error!("Failed to read attachment: {}", e);
+        bad_gateway("Failed to read attachment.")
+    })?;
+    Ok(Download {
+        file_name: attachment.file_name,
+        content_type: attachment.content_type,
+        chunks,
+    })
+}
+
+#[delete("/<task_id>/attachments/<id>")]
+async fn delete(
+    auth_user: Auth<UserModel>,
+    storage: &State<Box<dyn Storage>>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    id: Uuid,
+) -> APIResult {
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let storage_key = sqlx::query_scalar!(
+        "DELETE FROM attachments WHERE id = $1 AND task_id = $2 RETURNING storage_key",
+        id,
+        task_id
+    )
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to delete attachment from database.")?;
+    let Some(storage_key) = storage_key else {
+        return result_not_found("Attachment not found.");
+    };
+    // The attachment is gone either way, a leftover file is only wasted space
+    if let Err(e) = storage.delete(&storage_key).await {
+        warn!("Failed to delete attachment {}: {}", storage_key, e);
+    }
+
+    Ok(ok("Attachment deleted successfully."))
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/tasks", routes![get_all, post, download, delete])
+}
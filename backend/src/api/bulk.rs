@@ -0,0 +1,240 @@
+use rocket::{http::Status, serde::json::Json, Build, Rocket, State};
+use rocket_db_pools::Connection;
+use rocket_validation::Validated;
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection as _, PgConnection};
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        tasks::{check_task_owner, patch_task, PatchInput},
+        trash,
+        utils::{ownership::check_label, Patch},
+    },
+    config::AppConfig,
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{not_found, APIResponse, APIResult, MapAPIResponse},
+};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BulkInput {
+    #[validate(length(
+        min = 1,
+        max = 500,
+        message = "Between 1 and 500 tasks can be changed at once."
+    ))]
+    pub ids: Vec<Uuid>,
+    pub operation: Operation,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Operation {
+    SetCompleted {
+        completed: bool,
+        /// Completes blocked tasks too.
+        #[serde(default)]
+        force: bool,
+    },
+    Move {
+        #[serde(default)]
+        list_id: Option<Uuid>,
+        #[serde(default)]
+        parent_id: Patch<Uuid>,
+    },
+    AddLabels {
+        label_ids: Vec<Uuid>,
+    },
+    RemoveLabels {
+        label_ids: Vec<Uuid>,
+    },
+    SetDueDate {
+        due_at: Option<String>,
+        due_text: String,
+    },
+    Delete,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemResult {
+    pub id: Uuid,
+    pub status: u16,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkResponse {
+    pub results: Vec<ItemResult>,
+}
+
+/// Applies one operation to many tasks in a single transaction.
+///
+/// Every task gets its own savepoint, so a task that fails, e.g. because
+/// it doesn't exist or is blocked, is reported without undoing the others.
+/// All the changes are recorded as one action, undone at once.
+#[post("/bulk", data = "<input>", format = "application/json")]
+async fn post(
+    auth_user: Auth<UserModel>,
+    config: &State<AppConfig>,
+    mut db: Connection<BackendDb>,
+    input: Validated<Json<BulkInput>>,
+) -> APIResult {
+    let input = input.into_deep_inner();
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to start transaction.")?;
+    match &input.operation {
+        Operation::AddLabels { label_ids } => {
+            for label_id in label_ids {
+                check_label(&mut tx, auth_user.id, *label_id).await?;
+            }
+        }
+        Operation::Delete => trash::purge_expired(&mut tx, config, Some(auth_user.id)).await?,
+        _ => (),
+    }
+
+    let mut ids = input.ids.clone();
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+
+    let mut results = vec![];
+    let mut changes = vec![];
+    for id in ids {
+        let mut savepoint = tx
+            .begin()
+            .await
+            .map_internal_server_error("Failed to start transaction.")?;
+        match apply(&mut savepoint, &auth_user, id, &input.operation).await {
+            Ok(task_changes) => {
+                savepoint
+                    .commit()
+                    .await
+                    .map_internal_server_error("Failed to commit transaction.")?;
+                changes.extend(task_changes);
+                results.push(ItemResult {
+                    id,
+                    status: Status::Ok.code,
+                    message: None,
+                });
+            }
+            Err(e) => {
+                savepoint
+                    .rollback()
+                    .await
+                    .map_internal_server_error("Failed to roll back transaction.")?;
+                let (status, data) = e.as_guard_error();
+                results.push(ItemResult {
+                    id,
+                    status: status.code,
+                    message: data["message"].as_str().map(String::from),
+                });
+            }
+        }
+    }
+
+    record_action(&mut tx, auth_user.id, "bulk", changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to commit transaction.")?;
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(BulkResponse { results })
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+async fn apply(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    operation: &Operation,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    check_task_owner(db, user.id, id).await?;
+    match operation {
+        Operation::SetCompleted { completed, force } => {
+            let input = PatchInput {
+                completed: Patch::Value(*completed),
+                force: *force,
+                ..Default::default()
+            };
+            patch_task(db, user, id, input).await
+        }
+        Operation::Move { list_id, parent_id } => {
+            let input = PatchInput {
+                list_id: list_id.map(Patch::Value).unwrap_or_default(),
+                parent_id: parent_id.clone(),
+                ..Default::default()
+            };
+            patch_task(db, user, id, input).await
+        }
+        Operation::SetDueDate { due_at, due_text } => {
+            let input = PatchInput {
+                due_at: due_at.clone().map(Patch::Value).unwrap_or_default(),
+                due_text: Patch::Value(due_text.clone()),
+                ..Default::default()
+            };
+            patch_task(db, user, id, input).await
+        }
+        Operation::AddLabels { label_ids } => {
+            let rows = sqlx::query_scalar!(
+                r#"INSERT INTO task_labels (task_id, label_id)
+                    SELECT $1, label_id FROM UNNEST($2::UUID[]) AS label_id
+                    ON CONFLICT DO NOTHING
+                    RETURNING to_jsonb(task_labels.*) AS "row!""#,
+                id,
+                label_ids
+            )
+            .fetch_all(&mut *db)
+            .await
+            .map_internal_server_error("Failed to attach labels in database.")?;
+            Ok(label_changes(rows, false))
+        }
+        Operation::RemoveLabels { label_ids } => {
+            let rows = sqlx::query_scalar!(
+                r#"DELETE FROM task_labels WHERE task_id = $1 AND label_id = ANY($2)
+                    RETURNING to_jsonb(task_labels.*) AS "row!""#,
+                id,
+                label_ids
+            )
+            .fetch_all(&mut *db)
+            .await
+            .map_internal_server_error("Failed to detach labels in database.")?;
+            Ok(label_changes(rows, true))
+        }
+        Operation::Delete => {
+            let changes = trash::soft_delete(db, "tasks", id, user.id).await?;
+            if changes.is_empty() {
+                return Err(not_found("Task not found."));
+            }
+            Ok(changes)
+        }
+    }
+}
+
+fn label_changes(rows: Vec<serde_json::Value>, removed: bool) -> Vec<ActionChange> {
+    rows.into_iter()
+        .map(|row| {
+            let (before, after) = if removed {
+                (Some(row), None)
+            } else {
+                (None, Some(row))
+            };
+            ActionChange {
+                table: String::from("task_labels"),
+                before,
+                after,
+            }
+        })
+        .collect()
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/tasks", routes![post])
+}
@@ -0,0 +1,244 @@
+use rocket::{http::Status, serde::json::Json, Build, Rocket};
+use rocket_db_pools::Connection;
+use rocket_validation::Validated;
+use serde::{Deserialize, Serialize};
+use sqlx::Connection as _;
+use time::PrimitiveDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        tasks::check_task_owner,
+        utils::{serde::primitive_date_iso_serialize, GetAllResponse, PostResponse, GET_LIMIT},
+    },
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{ok, result_not_found, APIResponse, APIResult, MapAPIResponse},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetModel {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub author: String,
+    /// Markdown, rendered by the client.
+    pub body: String,
+    #[serde(serialize_with = "primitive_date_iso_serialize")]
+    pub created_at: PrimitiveDateTime,
+    #[serde(serialize_with = "primitive_date_iso_serialize")]
+    pub updated_at: PrimitiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CommentInput {
+    #[validate(length(
+        min = 1,
+        max = 10000,
+        message = "Comment must have between 1 and 10000 characters."
+    ))]
+    pub body: String,
+}
+
+/// Lists the comments of a task, oldest first.
+#[get("/<task_id>/comments?<limit>&<page>")]
+async fn get_all(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    limit: Option<u32>,
+    page: Option<u32>,
+) -> APIResult {
+    let limit = limit.unwrap_or(GET_LIMIT);
+    let page = page.unwrap_or(0);
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let items = sqlx::query_as!(
+        GetModel,
+        r#"
+        SELECT
+            task_comments.id,
+            task_comments.task_id,
+            task_comments.user_id,
+            users.username AS author,
+            task_comments.body,
+            task_comments.created_at,
+            task_comments.updated_at
+            FROM task_comments
+            INNER JOIN users ON users.id = task_comments.user_id
+        WHERE task_comments.task_id = $1
+        ORDER BY task_comments.created_at, task_comments.id
+        LIMIT $2 OFFSET $3
+        "#,
+        task_id,
+        limit as i64,
+        (page * limit) as i64
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch comments from database.")?;
+    let resp = GetAllResponse::<GetModel> { items, limit, page };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[post("/<task_id>/comments", data = "<input>", format = "application/json")]
+async fn post(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    input: Validated<Json<CommentInput>>,
+) -> APIResult {
+    let input = input.into_deep_inner();
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to create comment in database.")?;
+    let created = sqlx::query!(
+        r#"INSERT INTO task_comments (task_id, user_id, body) VALUES ($1, $2, $3)
+            RETURNING id, to_jsonb(task_comments.*) AS "row!""#,
+        task_id,
+        auth_user.id,
+        input.body
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to create comment in database.")?;
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "create",
+        vec![ActionChange {
+            table: String::from("task_comments"),
+            before: None,
+            after: Some(created.row),
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to create comment in database.")?;
+
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::to_value(PostResponse { id: created.id })
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[patch(
+    "/<task_id>/comments/<id>",
+    data = "<input>",
+    format = "application/json"
+)]
+async fn patch(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    id: Uuid,
+    input: Validated<Json<CommentInput>>,
+) -> APIResult {
+    let input = input.into_deep_inner();
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to update comment in database.")?;
+    let before = sqlx::query_scalar!(
+        r#"SELECT to_jsonb(task_comments.*) AS "row!" FROM task_comments
+            WHERE id = $1 AND task_id = $2 AND user_id = $3
+            FOR UPDATE"#,
+        id,
+        task_id,
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to update comment in database.")?;
+    let Some(before) = before else {
+        return result_not_found("Comment not found.");
+    };
+    let after = sqlx::query_scalar!(
+        r#"UPDATE task_comments SET body = $2 WHERE id = $1
+            RETURNING to_jsonb(task_comments.*) AS "row!""#,
+        id,
+        input.body
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to update comment in database.")?;
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "update",
+        vec![ActionChange {
+            table: String::from("task_comments"),
+            before: Some(before),
+            after: Some(after),
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to update comment in database.")?;
+
+    Ok(ok("Comment updated successfully."))
+}
+
+#[delete("/<task_id>/comments/<id>")]
+async fn delete(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    id: Uuid,
+) -> APIResult {
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to delete comment from database.")?;
+    let deleted = sqlx::query_scalar!(
+        r#"DELETE FROM task_comments WHERE id = $1 AND task_id = $2 AND user_id = $3
+            RETURNING to_jsonb(task_comments.*) AS "row!""#,
+        id,
+        task_id,
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to delete comment from database.")?;
+    let Some(deleted) = deleted else {
+        return result_not_found("Comment not found.");
+    };
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "delete",
+        vec![ActionChange {
+            table: String::from("task_comments"),
+            before: Some(deleted),
+            after: None,
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to delete comment from database.")?;
+
+    Ok(ok("Comment deleted successfully."))
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/tasks", routes![get_all, post, patch, delete])
+}
@@ -0,0 +1,181 @@
+use rocket::{http::Status, serde::json::Json, Build, Rocket};
+use rocket_db_pools::Connection;
+use rocket_validation::Validated;
+use serde::{Deserialize, Serialize};
+use sqlx::Connection as _;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        tasks::check_task_owner,
+    },
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{
+        bad_request, conflict, ok, result_not_found, APIResponse, APIResult, MapAPIResponse,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetModel {
+    pub id: Uuid,
+    pub title: String,
+    pub completed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PostInput {
+    /// The task that has to be completed first.
+    pub id: Uuid,
+}
+
+/// Lists the tasks the task is blocked by.
+#[get("/<id>/blockers")]
+async fn get_all(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>, id: Uuid) -> APIResult {
+    check_task_owner(&mut db, auth_user.id, id).await?;
+
+    let blockers = sqlx::query_as!(
+        GetModel,
+        "SELECT tasks.id, tasks.title, tasks.completed FROM task_dependencies
+            INNER JOIN tasks ON tasks.id = task_dependencies.blocked_by_id
+            WHERE task_dependencies.task_id = $1 AND tasks.deleted_at IS NULL
+            ORDER BY tasks.completed, tasks.due_at, tasks.id",
+        id
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch blockers from database.")?;
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(blockers)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[post("/<id>/blockers", data = "<input>", format = "application/json")]
+async fn post(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+    input: Validated<Json<PostInput>>,
+) -> APIResult {
+    let input = input.into_deep_inner();
+    if input.id == id {
+        return Err(bad_request("A task can't be blocked by itself."));
+    }
+    check_task_owner(&mut db, auth_user.id, id).await?;
+    check_task_owner(&mut db, auth_user.id, input.id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to add blocker in database.")?;
+    // Two edges added at once could close a cycle neither sees alone
+    sqlx::query!(
+        "SELECT id FROM users WHERE id = $1 FOR UPDATE",
+        auth_user.id
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to add blocker in database.")?;
+    let cycle = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE blockers AS (
+            SELECT $1::UUID AS id
+            UNION
+            SELECT task_dependencies.blocked_by_id FROM task_dependencies
+                INNER JOIN blockers ON blockers.id = task_dependencies.task_id
+        )
+        SELECT EXISTS (SELECT 1 FROM blockers WHERE id = $2) AS "cycle!""#,
+        input.id,
+        id
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to add blocker in database.")?;
+    if cycle {
+        return Err(conflict(
+            "The blocker already depends on this task, which would be a cycle.",
+        ));
+    }
+
+    let created = sqlx::query_scalar!(
+        r#"INSERT INTO task_dependencies (task_id, blocked_by_id) VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            RETURNING to_jsonb(task_dependencies.*) AS "row!""#,
+        id,
+        input.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to add blocker in database.")?;
+    if let Some(created) = created {
+        record_action(
+            &mut tx,
+            auth_user.id,
+            "add_blocker",
+            vec![ActionChange {
+                table: String::from("task_dependencies"),
+                before: None,
+                after: Some(created),
+            }],
+        )
+        .await?;
+    }
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to add blocker in database.")?;
+
+    Ok(ok("Blocker added successfully."))
+}
+
+#[delete("/<id>/blockers/<blocker_id>")]
+async fn delete(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+    blocker_id: Uuid,
+) -> APIResult {
+    check_task_owner(&mut db, auth_user.id, id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to remove blocker in database.")?;
+    let deleted = sqlx::query_scalar!(
+        r#"DELETE FROM task_dependencies WHERE task_id = $1 AND blocked_by_id = $2
+            RETURNING to_jsonb(task_dependencies.*) AS "row!""#,
+        id,
+        blocker_id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to remove blocker in database.")?;
+    let Some(deleted) = deleted else {
+        return result_not_found("Blocker not found.");
+    };
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "remove_blocker",
+        vec![ActionChange {
+            table: String::from("task_dependencies"),
+            before: Some(deleted),
+            after: None,
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to remove blocker in database.")?;
+
+    Ok(ok("Blocker removed successfully."))
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/tasks", routes![get_all, post, delete])
+}
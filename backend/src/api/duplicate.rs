@@ -0,0 +1,362 @@
+use rocket::{http::Status, serde::json::Json, Build, Rocket};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection as _, PgConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        tasks::{check_task_owner, SUBTASK_LIST_MESSAGE},
+        templates::insert_changes,
+        utils::{
+            ownership::{check_list, check_task},
+            Patch,
+        },
+    },
+    database::BackendDb,
+    guards::auth::Auth,
+    models::{list::ListModel, task::TaskModel, user::UserModel},
+    responses::{
+        bad_request, internal_server_error, not_found, APIResponse, APIResult, MapAPIResponse,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct TaskInput {
+    /// Copies completed subtasks too, the task itself always is.
+    #[serde(default = "default_include_completed")]
+    pub include_completed: bool,
+    /// The list of the copy, the same as the original if missing.
+    pub list_id: Option<Uuid>,
+    /// The parent of the copy. If missing, the copy is a sibling of the
+    /// original when it stays in the same list, a top level task otherwise.
+    #[serde(default)]
+    pub parent_id: Patch<Uuid>,
+}
+
+impl Default for TaskInput {
+    fn default() -> Self {
+        Self {
+            include_completed: default_include_completed(),
+            list_id: None,
+            parent_id: Patch::Missing,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListInput {
+    /// Copies completed tasks too.
+    #[serde(default = "default_include_completed")]
+    pub include_completed: bool,
+    /// The parent of the copy, the same as the original if missing.
+    #[serde(default)]
+    pub parent_id: Patch<Uuid>,
+}
+
+impl Default for ListInput {
+    fn default() -> Self {
+        Self {
+            include_completed: default_include_completed(),
+            parent_id: Patch::Missing,
+        }
+    }
+}
+
+fn default_include_completed() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateResponse {
+    pub id: Uuid,
+    /// The id of every copied list and task, by the id of its original.
+    pub ids: HashMap<Uuid, Uuid>,
+}
+
+/// Copies a task with its subtasks and their labels.
+#[post("/<id>/duplicate", data = "<input>", format = "application/json")]
+async fn duplicate_task(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+    input: Option<Json<TaskInput>>,
+) -> APIResult {
+    let input = input.map(Json::into_inner).unwrap_or_default();
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to duplicate task in database.")?;
+    check_task_owner(&mut tx, auth_user.id, id).await?;
+
+    let tasks = subtree_tasks(&mut tx, Some(id), &[], input.include_completed).await?;
+    let original = &tasks[0];
+    let (list_id, parent_id) = match input.parent_id {
+        Patch::Value(parent_id) => {
+            let parent_list_id = check_task(&mut tx, auth_user.id, parent_id).await?;
+            if input
+                .list_id
+                .is_some_and(|list_id| list_id != parent_list_id)
+            {
+                return Err(bad_request(SUBTASK_LIST_MESSAGE));
+            }
+            (parent_list_id, Some(parent_id))
+        }
+        parent_id => {
+            let list_id = match input.list_id {
+                Some(list_id) => {
+                    check_list(&mut tx, auth_user.id, list_id).await?;
+                    list_id
+                }
+                None => original.list_id,
+            };
+            let keeps_parent = matches!(parent_id, Patch::Missing) && list_id == original.list_id;
+            (list_id, original.parent_id.filter(|_| keeps_parent))
+        }
+    };
+
+    let mut ids = HashMap::new();
+    let changes = insert_tasks(&mut tx, tasks, &mut ids, Some((list_id, parent_id))).await?;
+    let new_id = ids[&id];
+    commit_duplicate(tx, auth_user.id, changes, new_id, ids).await
+}
+
+/// Copies a list with its sublists, and all their tasks and labels.
+#[post("/<id>/duplicate", data = "<input>", format = "application/json")]
+async fn duplicate_list(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+    input: Option<Json<ListInput>>,
+) -> APIResult {
+    let input = input.map(Json::into_inner).unwrap_or_default();
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to duplicate list in database.")?;
+
+    let lists: Vec<ListModel> = sqlx::query_as(
+        "WITH RECURSIVE subtree AS (
+            SELECT id, 0 AS depth, ARRAY[id] AS path FROM lists
+                WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+            UNION ALL
+            SELECT lists.id, subtree.depth + 1, subtree.path || lists.id FROM lists
+                INNER JOIN subtree ON lists.parent_id = subtree.id
+                WHERE lists.deleted_at IS NULL AND NOT lists.id = ANY(subtree.path)
+        )
+        SELECT lists.* FROM subtree
+            INNER JOIN lists ON lists.id = subtree.id
+        ORDER BY subtree.depth, lists.rank, lists.id",
+    )
+    .bind(id)
+    .bind(auth_user.id)
+    .fetch_all(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch lists from database.")?;
+    if lists.is_empty() {
+        return Err(not_found("List not found."));
+    }
+    let parent_id = match input.parent_id {
+        Patch::Missing => lists[0].parent_id,
+        Patch::Null => None,
+        Patch::Value(parent_id) => {
+            check_list(&mut tx, auth_user.id, parent_id).await?;
+            Some(parent_id)
+        }
+    };
+
+    let list_ids: Vec<Uuid> = lists.iter().map(|list| list.id).collect();
+    let mut ids: HashMap<Uuid, Uuid> = list_ids.iter().map(|id| (*id, Uuid::new_v4())).collect();
+    let mut rows = vec![];
+    for list in lists {
+        let is_root = list.id == id;
+        rows.push((
+            ids[&list.id],
+            auth_user.id,
+            if is_root {
+                parent_id
+            } else {
+                list.parent_id.map(|parent_id| ids[&parent_id])
+            },
+            list.title,
+            list.description,
+            list.color,
+            // The copy goes after its new siblings, its content keeps its order
+            (!is_root).then_some(list.rank),
+        ));
+    }
+    let query = crate::insert_query!(
+        "lists";
+        (id: 0, user_id: 1, parent_id: 2, title: 3, description: 4, color: 5, rank: 6);
+        rows;
+        "RETURNING to_jsonb(lists.*) AS row"
+    );
+    let mut changes = insert_changes(&mut tx, "lists", &query).await?;
+
+    let tasks = subtree_tasks(&mut tx, None, &list_ids, input.include_completed).await?;
+    changes.extend(insert_tasks(&mut tx, tasks, &mut ids, None).await?);
+    let new_id = ids[&id];
+    commit_duplicate(tx, auth_user.id, changes, new_id, ids).await
+}
+
+/// Tasks with their subtasks, parents first, either from a single task or
+/// from the top level tasks of some lists.
+///
+/// Unless `include_completed` is set, completed tasks are left out with
+/// their subtasks, apart from a task copied on its own.
+async fn subtree_tasks(
+    db: &mut PgConnection,
+    task_id: Option<Uuid>,
+    list_ids: &[Uuid],
+    include_completed: bool,
+) -> Result<Vec<TaskModel>, APIResponse> {
+    sqlx::query_as(
+        "WITH RECURSIVE subtree AS (
+            SELECT id, 0 AS depth, ARRAY[id] AS path FROM tasks
+                WHERE (id = $1 OR (parent_id IS NULL AND list_id = ANY($2)))
+                    AND deleted_at IS NULL
+                    AND ($1 IS NOT NULL OR $3 OR NOT completed)
+            UNION ALL
+            SELECT tasks.id, subtree.depth + 1, subtree.path || tasks.id FROM tasks
+                INNER JOIN subtree ON tasks.parent_id = subtree.id
+                WHERE tasks.deleted_at IS NULL
+                    AND ($3 OR NOT tasks.completed)
+                    AND NOT tasks.id = ANY(subtree.path)
+        )
+        SELECT tasks.* FROM subtree
+            INNER JOIN tasks ON tasks.id = subtree.id
+        ORDER BY subtree.depth, tasks.rank, tasks.id",
+    )
+    .bind(task_id)
+    .bind(list_ids)
+    .bind(include_completed)
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch tasks from database.")
+}
+
+/// The id of the copy of `id`, which has to be inserted already.
+fn copy_of(ids: &HashMap<Uuid, Uuid>, id: Uuid) -> Result<Uuid, APIResponse> {
+    ids.get(&id)
+        .copied()
+        .ok_or_else(|| internal_server_error("Failed to copy the hierarchy of the tasks."))
+}
+
+/// Inserts copies of tasks, parents first, adding their new ids to `ids`.
+///
+/// Tasks go in the lists `ids` maps their list to, except when a task is
+/// copied on its own, where `target` is the list and parent of the copy.
+async fn insert_tasks(
+    db: &mut PgConnection,
+    tasks: Vec<TaskModel>,
+    ids: &mut HashMap<Uuid, Uuid>,
+    target: Option<(Uuid, Option<Uuid>)>,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    if tasks.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut rows = vec![];
+    let mut old_ids = vec![];
+    let mut new_ids = vec![];
+    for (index, task) in tasks.into_iter().enumerate() {
+        let new_id = Uuid::new_v4();
+        ids.insert(task.id, new_id);
+        old_ids.push(task.id);
+        new_ids.push(new_id);
+
+        let (list_id, parent_id, rank) = match target {
+            Some((list_id, parent_id)) if index == 0 => (list_id, parent_id, None),
+            Some((list_id, _)) => (
+                list_id,
+                task.parent_id
+                    .map(|parent_id| copy_of(ids, parent_id))
+                    .transpose()?,
+                Some(task.rank),
+            ),
+            None => (
+                copy_of(ids, task.list_id)?,
+                task.parent_id
+                    .map(|parent_id| copy_of(ids, parent_id))
+                    .transpose()?,
+                Some(task.rank),
+            ),
+        };
+        rows.push((
+            new_id,
+            parent_id,
+            list_id,
+            task.title,
+            task.description,
+            task.due_at.to_string(),
+            task.due_text,
+            task.completed,
+            task.completed_at
+                .map(|completed_at| completed_at.to_string()),
+            task.recurrence,
+            task.recurrence_from_completion,
+            task.recurrence_keep_history,
+            task.priority,
+            rank,
+        ));
+    }
+    let query = crate::insert_query!(
+        "tasks";
+        (
+            id: 0, parent_id: 1, list_id: 2, title: 3, description: 4, due_at: 5,
+            due_text: 6, completed: 7, completed_at: 8, recurrence: 9,
+            recurrence_from_completion: 10, recurrence_keep_history: 11, priority: 12,
+            rank: 13
+        );
+        rows;
+        "RETURNING to_jsonb(tasks.*) AS row"
+    );
+    let mut changes = insert_changes(db, "tasks", &query).await?;
+
+    let labels = sqlx::query_scalar!(
+        r#"INSERT INTO task_labels (task_id, label_id)
+            SELECT copies.new_id, task_labels.label_id
+                FROM UNNEST($1::UUID[], $2::UUID[]) AS copies(old_id, new_id)
+                INNER JOIN task_labels ON task_labels.task_id = copies.old_id
+                INNER JOIN labels ON labels.id = task_labels.label_id
+            WHERE labels.deleted_at IS NULL
+            RETURNING to_jsonb(task_labels.*) AS "row!""#,
+        &old_ids,
+        &new_ids
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to copy labels in database.")?;
+    changes.extend(labels.into_iter().map(|row| ActionChange {
+        table: String::from("task_labels"),
+        before: None,
+        after: Some(row),
+    }));
+    Ok(changes)
+}
+
+async fn commit_duplicate(
+    mut tx: sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    changes: Vec<ActionChange>,
+    id: Uuid,
+    ids: HashMap<Uuid, Uuid>,
+) -> APIResult {
+    record_action(&mut tx, user_id, "duplicate", changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to commit transaction.")?;
+
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::to_value(DuplicateResponse { id, ids })
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket
+        .mount("/tasks", routes![duplicate_task])
+        .mount("/lists", routes![duplicate_list])
+}
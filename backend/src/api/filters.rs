@@ -0,0 +1,180 @@
+use rocket::{http::Status, Build, Rocket};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Row};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api::{
+        tasks::{GetModel, GetModelBuilder},
+        utils::{
+            timezone::local_now,
+            validation::{validate_filter_query, validate_patch_filter_query},
+            GetAllResponse, Patch, GET_LIMIT,
+        },
+    },
+    database::BackendDb,
+    filter_query::{FilterExpr, FilterParam},
+    guards::auth::Auth,
+    models::{task::TaskModel, user::UserModel},
+    responses::{result_bad_request, result_not_found, APIResponse, APIResult, MapAPIResponse},
+};
+
+crate::api_get! {
+    model_table: "filters",
+    model_type: crate::models::filter::FilterModel
+}
+
+crate::api_post! {
+    model_table: "filters",
+    input: PostInput,
+    input_fields: { title, query }
+}
+
+crate::api_patch! {
+    model_table: "filters",
+    input: PatchInput,
+    input_fields: { title, query }
+}
+
+crate::api_delete! {
+    model_table: "filters"
+}
+
+/// Evaluates a saved filter, returning the matching tasks by due date.
+#[get("/<id>/tasks?<limit>&<page>")]
+async fn get_tasks(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+    limit: Option<u32>,
+    page: Option<u32>,
+) -> APIResult {
+    let limit = limit.unwrap_or(GET_LIMIT);
+    let page = page.unwrap_or(0);
+
+    let query = sqlx::query_scalar!(
+        "SELECT query FROM filters WHERE id = $1 AND user_id = $2",
+        id,
+        auth_user.id
+    )
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch filter from database.")?;
+    let Some(query) = query else {
+        return result_not_found("Filter not found.");
+    };
+
+    let now = local_now(&mut db, &auth_user.timezone).await?;
+    let filter = match query
+        .parse::<FilterExpr>()
+        .and_then(|filter| filter.compile(now, &auth_user.timezone, 4))
+    {
+        Ok(filter) => filter,
+        Err(e) => return result_bad_request(&e),
+    };
+
+    let query_string = format!(
+        r#"
+        SELECT
+            base_tasks.*,
+            child_tasks.id AS child_id,
+            task_labels.label_id
+            FROM (
+                SELECT tasks.*, task_blocked(tasks.id) AS blocked FROM tasks
+                    INNER JOIN lists
+                    ON lists.id = tasks.list_id
+                WHERE user_id = $1
+                    AND tasks.deleted_at IS NULL
+                    AND lists.archived_at IS NULL
+                    AND {}
+                ORDER BY tasks.due_at, tasks.rank, tasks.id
+                LIMIT $2 OFFSET $3
+            ) base_tasks
+            LEFT JOIN tasks child_tasks
+                ON base_tasks.id = child_tasks.parent_id
+                AND child_tasks.deleted_at IS NULL
+            LEFT JOIN task_labels
+                ON base_tasks.id = task_labels.task_id
+                AND task_labels.label_id IN (SELECT id FROM labels WHERE deleted_at IS NULL)
+            ORDER BY base_tasks.due_at, base_tasks.rank, base_tasks.id, child_tasks.rank, child_tasks.id"#,
+        filter.condition
+    );
+    let mut query = sqlx::query(&query_string)
+        .bind(auth_user.id)
+        .bind(limit as i64)
+        .bind((page * limit) as i64);
+    for param in filter.params {
+        query = match param {
+            FilterParam::Text(value) => query.bind(value),
+            FilterParam::Timestamp(value) => query.bind(value),
+            FilterParam::SmallInt(value) => query.bind(value),
+        };
+    }
+    let rows = query
+        .fetch_all(&mut *db)
+        .await
+        .map_internal_server_error("Error fetching items")?;
+
+    let mut order = vec![];
+    let mut items: HashMap<Uuid, GetModelBuilder> = HashMap::new();
+    for row in rows {
+        let task = TaskModel::from_row(&row).map_internal_server_error("Error fetching items")?;
+        let blocked = row.get::<bool, _>("blocked");
+        let item = items.entry(task.id).or_insert_with(|| {
+            order.push(task.id);
+            GetModelBuilder {
+                get_model: GetModel {
+                    blocked,
+                    ..task.into()
+                },
+                child_ids: vec![],
+                label_ids: HashSet::new(),
+            }
+        });
+        if let Some(child_id) = row.get::<Option<Uuid>, _>("child_id") {
+            item.add_child(child_id);
+        }
+        if let Some(label_id) = row.get::<Option<Uuid>, _>("label_id") {
+            item.label_ids.insert(label_id);
+        }
+    }
+
+    let items = order
+        .into_iter()
+        .filter_map(|id| items.remove(&id))
+        .map(|item| item.build())
+        .collect();
+    let resp = GetAllResponse::<GetModel> { items, limit, page };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PatchInput {
+    #[serde(default)]
+    pub title: Patch<String>,
+    #[serde(default)]
+    #[validate(custom = "validate_patch_filter_query")]
+    pub query: Patch<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PostInput {
+    pub title: String,
+    #[validate(custom = "validate_filter_query")]
+    pub query: String,
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount(
+        "/filters",
+        routes![get_all, get_single, get_tasks, post, patch, delete],
+    )
+}
@@ -1,18 +1,62 @@
 crate::api_crud! {
     model_table: "labels",
-    model_fields: { title, description, color },
-    get_model: crate::models::label::LabelModel,
-    post_input: PostInput,
-    patch_input: PatchInput
+    get: {
+        model_type: crate::models::label::LabelModel,
+    },
+    post: {
+        input: PostInput,
+        input_fields: { title, description, color }
+    },
+    patch: {
+        input: PatchInput,
+        input_fields: { title, description, color, rank },
+        before_update: place_patched_label
+    },
+    delete: {}
 }
 
 use super::utils::validation::{validate_color, validate_patch_color};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
 use uuid::Uuid;
 use validator::Validate;
 
-use super::utils::Patch;
+use super::utils::{
+    placement::{rank_among, Placement},
+    Patch,
+};
+use crate::{
+    models::user::UserModel,
+    responses::{APIResponse, MapAPIResponse},
+};
+
+/// Ranks a label that is moved next to another label.
+async fn place_patched_label(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    input: &mut PatchInput,
+) -> Result<(), APIResponse> {
+    let Some(placement) = Placement::from_input(input.move_before, input.move_after)? else {
+        return Ok(());
+    };
+    let siblings: Vec<_> = sqlx::query!(
+        "SELECT id, rank FROM labels
+            WHERE user_id = $1 AND id <> $2 AND deleted_at IS NULL
+            ORDER BY rank, id",
+        user.id,
+        id
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch labels from database.")?
+    .into_iter()
+    .map(|row| (row.id, row.rank))
+    .collect();
+    input.rank = Patch::Value(rank_among(&siblings, Some(placement))?);
+    Ok(())
+}
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct PatchInput {
@@ -23,6 +67,13 @@ pub struct PatchInput {
     #[serde(default)]
     #[validate(custom = "validate_patch_color")]
     pub color: Patch<String>,
+    /// Moves the label right before or after another label.
+    #[serde(default)]
+    pub move_before: Option<Uuid>,
+    #[serde(default)]
+    pub move_after: Option<Uuid>,
+    #[serde(skip_deserializing)]
+    pub rank: Patch<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
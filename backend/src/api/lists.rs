@@ -1,18 +1,639 @@
 crate::api_tree_crud! {
     model_table: "lists",
-    model_fields: { title, description, color, parent_id },
-    get_model: GetModel,
-    post_input: PostInput,
-    patch_input: PatchInput
+    get: {
+        model_type: GetModel,
+        get_fields: { id, user_id, title, description, color, parent_id, rank, archived_at },
+        query_all_where: "deleted_at IS NULL AND archived_at IS NULL",
+        child_where: "child_query.deleted_at IS NULL
+            AND (child_query.archived_at IS NULL OR base_query.archived_at IS NOT NULL)"
+    },
+    post: {
+        input: PostInput,
+        input_fields: { title, description, color, parent_id },
+        before_insert: check_post_parent
+    },
+    patch: {
+        input: PatchInput,
+        input_fields: { title, description, color, parent_id, rank },
+        before_update: prepare_patch
+    },
+    delete: { custom }
 }
 
 use super::utils::validation::{validate_color, validate_patch_color};
 use once_cell::sync::Lazy;
+use rocket::{
+    form::{self, error::ErrorKind},
+    http::Status,
+    Build, Rocket, State,
+};
+use rocket_db_pools::Connection;
 use serde::{Deserialize, Serialize};
+use sqlx::{Connection as _, PgConnection};
+use time::PrimitiveDateTime;
 use uuid::Uuid;
 use validator::Validate;
 
-use super::utils::Patch;
+use super::{
+    actions::{record_action, ActionChange},
+    trash,
+    utils::{
+        ownership::check_list,
+        placement::{rank_among, Placement},
+        serde::option_primitive_date_iso_serialize,
+        tree::{nest, MAX_TREE_DEPTH},
+        GetAllResponse, Patch, GET_LIMIT,
+    },
+};
+use crate::{
+    config::AppConfig,
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{
+        bad_request, not_found, ok, result_bad_request, result_not_found, APIResponse, APIResult,
+        MapAPIResponse,
+    },
+};
+
+/// Returns all lists nested under their parents, down to `max_depth`
+/// levels below the top level lists if given.
+#[get("/tree?<max_depth>")]
+async fn get_tree(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    max_depth: Option<u32>,
+) -> APIResult {
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE tree AS (
+            SELECT id, 0 AS depth, ARRAY[id] AS path FROM lists
+            WHERE user_id = $1 AND parent_id IS NULL AND deleted_at IS NULL AND archived_at IS NULL
+            UNION ALL
+            SELECT lists.id, tree.depth + 1, tree.path || lists.id FROM lists
+                INNER JOIN tree ON lists.parent_id = tree.id
+            WHERE lists.deleted_at IS NULL
+                AND lists.archived_at IS NULL
+                AND NOT lists.id = ANY(tree.path)
+                AND tree.depth < LEAST($2::INTEGER, $3)
+        )
+        SELECT
+            lists.id,
+            lists.user_id,
+            lists.title,
+            lists.description,
+            lists.color,
+            lists.parent_id,
+            lists.rank,
+            lists.archived_at,
+            ARRAY(
+                SELECT child_lists.id FROM lists child_lists
+                WHERE child_lists.parent_id = lists.id
+                    AND child_lists.deleted_at IS NULL
+                    AND child_lists.archived_at IS NULL
+                ORDER BY child_lists.rank, child_lists.id
+            ) AS "child_ids!",
+            COUNT(tasks.id) FILTER (WHERE NOT tasks.completed) AS "task_count!",
+            COUNT(tasks.id) FILTER (WHERE tasks.completed) AS "completed_task_count!"
+            FROM tree
+            INNER JOIN lists ON lists.id = tree.id
+            LEFT JOIN tasks ON tasks.list_id = lists.id AND tasks.deleted_at IS NULL
+        GROUP BY lists.id, tree.depth
+        ORDER BY tree.depth, lists.rank, lists.id"#,
+        auth_user.id,
+        max_depth.map(|depth| depth.min(i32::MAX as u32) as i32),
+        MAX_TREE_DEPTH as i32
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Error fetching items")?;
+
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let item = TreeModel {
+                list: GetModel {
+                    id: row.id,
+                    user_id: row.user_id,
+                    title: row.title,
+                    description: row.description,
+                    color: row.color,
+                    parent_id: row.parent_id,
+                    rank: row.rank,
+                    archived_at: row.archived_at,
+                    child_ids: row.child_ids,
+                },
+                task_count: row.task_count,
+                completed_task_count: row.completed_task_count,
+            };
+            (row.id, row.parent_id, item)
+        })
+        .collect();
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(nest(rows))
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+pub fn mount_tree_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/lists", routes![get_tree])
+}
+
+#[derive(Debug, Clone, Copy, Default, FromFormField)]
+pub enum DeleteStrategy {
+    /// Deletes the sublists and all tasks along with the list.
+    #[default]
+    #[field(value = "cascade")]
+    Cascade,
+    /// Moves the tasks of the list and its sublists into
+    /// `target_list_id`, then deletes the lists.
+    #[field(value = "move_tasks")]
+    MoveTasks,
+    /// Moves the sublists up to the parent of the list,
+    /// then deletes the list with its own tasks.
+    #[field(value = "promote_sublists")]
+    PromoteSublists,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DeleteResponse {
+    pub deleted_list_ids: Vec<Uuid>,
+    pub deleted_task_ids: Vec<Uuid>,
+    pub moved_task_ids: Vec<Uuid>,
+    pub promoted_list_ids: Vec<Uuid>,
+}
+
+/// Moves a list into the trash, handling its tasks and
+/// sublists according to `strategy`.
+#[delete("/<id>?<strategy>&<target_list_id>")]
+async fn delete(
+    auth_user: Auth<UserModel>,
+    config: &State<AppConfig>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+    strategy: Result<DeleteStrategy, form::Errors<'_>>,
+    target_list_id: Option<Uuid>,
+) -> APIResult {
+    let strategy = match strategy {
+        Ok(strategy) => strategy,
+        Err(errors) if errors.iter().all(|error| error.kind == ErrorKind::Missing) => {
+            DeleteStrategy::default()
+        }
+        Err(_) => return result_bad_request("Unknown delete strategy."),
+    };
+    let moves_tasks = matches!(strategy, DeleteStrategy::MoveTasks);
+    if moves_tasks != target_list_id.is_some() {
+        return result_bad_request("A target list is only used, and required, to move tasks.");
+    }
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to delete in database.")?;
+    trash::purge_expired(&mut tx, config, Some(auth_user.id)).await?;
+    let list_ids = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id FROM lists
+            WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+            UNION
+            SELECT lists.id FROM lists
+                INNER JOIN subtree ON lists.parent_id = subtree.id
+            WHERE lists.deleted_at IS NULL
+        )
+        SELECT id AS "id!" FROM subtree"#,
+        id,
+        auth_user.id
+    )
+    .fetch_all(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch lists from database.")?;
+    if list_ids.is_empty() {
+        return result_not_found("Item not found.");
+    }
+
+    let mut resp = DeleteResponse::default();
+    let mut changes = vec![];
+    match (strategy, target_list_id) {
+        (DeleteStrategy::MoveTasks, Some(target_list_id)) => {
+            if list_ids.contains(&target_list_id) {
+                return result_bad_request("The target list is deleted along with the list.");
+            }
+            check_list(&mut tx, auth_user.id, target_list_id).await?;
+            // Top level tasks go after the target's own, subtasks keep their rank
+            let mut siblings: Vec<_> = sqlx::query!(
+                "SELECT id, rank FROM tasks
+                    WHERE list_id = $1 AND parent_id IS NULL AND deleted_at IS NULL
+                    ORDER BY rank, id",
+                target_list_id
+            )
+            .fetch_all(&mut tx)
+            .await
+            .map_internal_server_error("Failed to fetch tasks from database.")?
+            .into_iter()
+            .map(|row| (row.id, row.rank))
+            .collect();
+            let moved_ids = sqlx::query_scalar!(
+                "SELECT id FROM tasks
+                    WHERE list_id = ANY($1) AND parent_id IS NULL AND deleted_at IS NULL
+                    ORDER BY array_position($1, list_id), rank, id",
+                &list_ids
+            )
+            .fetch_all(&mut tx)
+            .await
+            .map_internal_server_error("Failed to fetch tasks from database.")?;
+            let (moved_ids, ranks) = place_last(&mut siblings, moved_ids)?;
+            let rows = sqlx::query!(
+                r#"
+                WITH before AS (
+                    SELECT id, to_jsonb(tasks.*) AS row FROM tasks
+                    WHERE list_id = ANY($1) AND deleted_at IS NULL
+                )
+                UPDATE tasks SET list_id = $2, rank = COALESCE(placed.rank, tasks.rank)
+                    FROM before
+                        LEFT JOIN UNNEST($3::UUID[], $4::TEXT[]) AS placed(id, rank)
+                            ON placed.id = before.id
+                    WHERE tasks.id = before.id
+                    RETURNING tasks.id, before.row AS "before!", to_jsonb(tasks.*) AS "after!""#,
+                &list_ids,
+                target_list_id,
+                &moved_ids,
+                &ranks
+            )
+            .fetch_all(&mut tx)
+            .await
+            .map_internal_server_error("Failed to move tasks in database.")?;
+            for row in rows {
+                resp.moved_task_ids.push(row.id);
+                changes.push(ActionChange {
+                    table: String::from("tasks"),
+                    before: Some(row.before),
+                    after: Some(row.after),
+                });
+            }
+        }
+        (DeleteStrategy::PromoteSublists, _) => {
+            // The sublists go after the last sibling of the deleted list
+            let mut siblings: Vec<_> = sqlx::query!(
+                "SELECT id, rank FROM lists
+                    WHERE user_id = $1
+                        AND parent_id IS NOT DISTINCT FROM (SELECT parent_id FROM lists WHERE id = $2)
+                        AND id <> $2
+                        AND deleted_at IS NULL
+                    ORDER BY rank, id",
+                auth_user.id,
+                id
+            )
+            .fetch_all(&mut tx)
+            .await
+            .map_internal_server_error("Failed to fetch lists from database.")?
+            .into_iter()
+            .map(|row| (row.id, row.rank))
+            .collect();
+            let promoted_ids = sqlx::query_scalar!(
+                "SELECT id FROM lists WHERE parent_id = $1 AND deleted_at IS NULL ORDER BY rank, id",
+                id
+            )
+            .fetch_all(&mut tx)
+            .await
+            .map_internal_server_error("Failed to fetch lists from database.")?;
+            let (promoted_ids, ranks) = place_last(&mut siblings, promoted_ids)?;
+            let rows = sqlx::query!(
+                r#"
+                WITH before AS (
+                    SELECT id, to_jsonb(lists.*) AS row FROM lists
+                    WHERE parent_id = $1 AND deleted_at IS NULL
+                )
+                UPDATE lists SET
+                    parent_id = (SELECT parent_id FROM lists WHERE id = $1),
+                    rank = placed.rank
+                    FROM before
+                        INNER JOIN UNNEST($2::UUID[], $3::TEXT[]) AS placed(id, rank)
+                            ON placed.id = before.id
+                    WHERE lists.id = before.id
+                    RETURNING lists.id, before.row AS "before!", to_jsonb(lists.*) AS "after!""#,
+                id,
+                &promoted_ids,
+                &ranks
+            )
+            .fetch_all(&mut tx)
+            .await
+            .map_internal_server_error("Failed to move lists in database.")?;
+            for row in rows {
+                resp.promoted_list_ids.push(row.id);
+                changes.push(ActionChange {
+                    table: String::from("lists"),
+                    before: Some(row.before),
+                    after: Some(row.after),
+                });
+            }
+        }
+        _ => (),
+    }
+
+    for change in trash::soft_delete(&mut tx, "lists", id, auth_user.id).await? {
+        let deleted_id = change
+            .after
+            .as_ref()
+            .and_then(|row| serde_json::from_value(row["id"].clone()).ok());
+        match (change.table.as_str(), deleted_id) {
+            ("lists", Some(deleted_id)) => resp.deleted_list_ids.push(deleted_id),
+            ("tasks", Some(deleted_id)) => resp.deleted_task_ids.push(deleted_id),
+            _ => (),
+        }
+        changes.push(change);
+    }
+    record_action(&mut tx, auth_user.id, "delete", changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to delete in database.")?;
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// Ranks `ids` one after another behind the last of `siblings`, and
+/// returns them next to their new ranks.
+fn place_last(
+    siblings: &mut Vec<(Uuid, String)>,
+    ids: Vec<Uuid>,
+) -> Result<(Vec<Uuid>, Vec<String>), APIResponse> {
+    let mut ranks = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let rank = rank_among(siblings, None)?;
+        siblings.push((*id, rank.clone()));
+        ranks.push(rank);
+    }
+    Ok((ids, ranks))
+}
+
+/// Returns the archived lists whose parent isn't archived too, most
+/// recently archived first.
+#[get("/archived?<limit>&<page>")]
+async fn get_archived(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    limit: Option<u32>,
+    page: Option<u32>,
+) -> APIResult {
+    let limit = limit.unwrap_or(GET_LIMIT);
+    let page = page.unwrap_or(0);
+
+    let items = sqlx::query_as!(
+        GetModel,
+        r#"
+        SELECT
+            lists.id,
+            lists.user_id,
+            lists.title,
+            lists.description,
+            lists.color,
+            lists.parent_id,
+            lists.rank,
+            lists.archived_at,
+            ARRAY(
+                SELECT child_lists.id FROM lists child_lists
+                WHERE child_lists.parent_id = lists.id AND child_lists.deleted_at IS NULL
+                ORDER BY child_lists.rank, child_lists.id
+            ) AS "child_ids!"
+            FROM lists
+            LEFT JOIN lists parent_lists ON parent_lists.id = lists.parent_id
+        WHERE lists.user_id = $1
+            AND lists.deleted_at IS NULL
+            AND lists.archived_at IS NOT NULL
+            AND parent_lists.archived_at IS NULL
+        ORDER BY lists.archived_at DESC, lists.id
+        LIMIT $2 OFFSET $3"#,
+        auth_user.id,
+        limit as i64,
+        (page * limit) as i64
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Error fetching items")?;
+    let resp = GetAllResponse::<GetModel> { items, limit, page };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// Archives a list with its sublists, hiding them and their tasks.
+#[post("/<id>/archive")]
+async fn archive(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>, id: Uuid) -> APIResult {
+    set_archived(&mut db, &auth_user, id, true).await?;
+    Ok(ok("List archived successfully."))
+}
+
+#[post("/<id>/unarchive")]
+async fn unarchive(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+) -> APIResult {
+    set_archived(&mut db, &auth_user, id, false).await?;
+    Ok(ok("List unarchived successfully."))
+}
+
+async fn set_archived(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    archived: bool,
+) -> Result<(), APIResponse> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to archive list in database.")?;
+    let list = sqlx::query!(
+        "SELECT lists.archived_at, parent_lists.archived_at AS parent_archived_at FROM lists
+            LEFT JOIN lists parent_lists ON parent_lists.id = lists.parent_id
+            WHERE lists.id = $1 AND lists.user_id = $2 AND lists.deleted_at IS NULL
+            FOR UPDATE OF lists",
+        id,
+        user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch list from database.")?;
+    let Some(list) = list else {
+        return Err(not_found("List not found."));
+    };
+    match (archived, list.archived_at, list.parent_archived_at) {
+        (true, Some(_), _) => return Err(bad_request("The list is already archived.")),
+        (false, None, _) => return Err(bad_request("The list isn't archived.")),
+        (false, _, Some(_)) => {
+            return Err(bad_request(
+                "The parent list is archived, unarchive it instead.",
+            ))
+        }
+        _ => (),
+    }
+
+    // Sublists archived on their own before keep their own archived_at, so
+    // only the ones sharing the list's state are archived or unarchived
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id FROM lists WHERE id = $1
+            UNION
+            SELECT lists.id FROM lists
+                INNER JOIN subtree ON lists.parent_id = subtree.id
+                WHERE lists.deleted_at IS NULL
+                    AND lists.archived_at IS NOT DISTINCT FROM $3::TIMESTAMP
+        ),
+        before AS (
+            SELECT lists.id, to_jsonb(lists.*) AS row FROM lists
+                INNER JOIN subtree ON lists.id = subtree.id
+        )
+        UPDATE lists SET
+            archived_at = CASE WHEN $2 THEN CURRENT_TIMESTAMP AT TIME ZONE 'UTC' END
+            FROM before WHERE lists.id = before.id
+            RETURNING before.row AS "before!", to_jsonb(lists.*) AS "after!""#,
+        id,
+        archived,
+        list.archived_at
+    )
+    .fetch_all(&mut tx)
+    .await
+    .map_internal_server_error("Failed to archive list in database.")?;
+    let changes = rows
+        .into_iter()
+        .map(|row| ActionChange {
+            table: String::from("lists"),
+            before: Some(row.before),
+            after: Some(row.after),
+        })
+        .collect();
+    let action_type = if archived { "archive" } else { "unarchive" };
+    record_action(&mut tx, user.id, action_type, changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to archive list in database.")
+}
+
+pub fn mount_archive_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/lists", routes![get_archived, archive, unarchive])
+}
+
+async fn check_post_parent(
+    db: &mut PgConnection,
+    user: &UserModel,
+    input: &mut PostInput,
+) -> Result<(), APIResponse> {
+    match input.parent_id {
+        Some(parent_id) => check_list(db, user.id, parent_id).await,
+        None => Ok(()),
+    }
+}
+
+async fn prepare_patch(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    input: &mut PatchInput,
+) -> Result<(), APIResponse> {
+    check_parent(db, user, id, input).await?;
+    place_patched_list(db, user, id, input).await
+}
+
+/// Rejects moving a list under itself or one of its sublists, which would
+/// cut the whole branch off from the top level.
+async fn check_parent(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    input: &PatchInput,
+) -> Result<(), APIResponse> {
+    let Patch::Value(parent_id) = &input.parent_id else {
+        return Ok(());
+    };
+    check_list(db, user.id, *parent_id).await?;
+    // Two moves at once could close a cycle neither sees alone
+    sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", user.id)
+        .fetch_one(&mut *db)
+        .await
+        .map_internal_server_error("Failed to patch in database.")?;
+    let cycle = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE ancestors AS (
+            SELECT id, parent_id FROM lists WHERE id = $1 AND user_id = $3
+            UNION
+            SELECT lists.id, lists.parent_id FROM lists
+                INNER JOIN ancestors ON lists.id = ancestors.parent_id
+        )
+        SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = $2) AS "cycle!""#,
+        parent_id,
+        id,
+        user.id
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch lists from database.")?;
+    if cycle {
+        return Err(bad_request(
+            "A list can't be moved under itself or one of its sublists.",
+        ));
+    }
+    Ok(())
+}
+
+/// Ranks a list that is moved next to a sibling or under another parent,
+/// the latter putting it after its new siblings.
+async fn place_patched_list(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    input: &mut PatchInput,
+) -> Result<(), APIResponse> {
+    let placement = Placement::from_input(input.move_before, input.move_after)?;
+    let current_parent_id = sqlx::query_scalar!(
+        "SELECT parent_id FROM lists WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+        id,
+        user.id
+    )
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch list from database.")?;
+    let Some(current_parent_id) = current_parent_id else {
+        return Ok(());
+    };
+    let parent_id = match &input.parent_id {
+        Patch::Missing => current_parent_id,
+        Patch::Null => None,
+        Patch::Value(parent_id) => Some(*parent_id),
+    };
+    if placement.is_none() && parent_id == current_parent_id {
+        return Ok(());
+    }
+
+    let siblings: Vec<_> = sqlx::query!(
+        "SELECT id, rank FROM lists
+            WHERE user_id = $1
+                AND parent_id IS NOT DISTINCT FROM $2::UUID
+                AND id <> $3
+                AND deleted_at IS NULL
+            ORDER BY rank, id",
+        user.id,
+        parent_id,
+        id
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch lists from database.")?
+    .into_iter()
+    .map(|row| (row.id, row.rank))
+    .collect();
+    input.rank = Patch::Value(rank_among(&siblings, placement)?);
+    Ok(())
+}
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct PatchInput {
@@ -23,7 +644,15 @@ pub struct PatchInput {
     #[serde(default)]
     #[validate(custom = "validate_patch_color")]
     pub color: Patch<String>,
+    #[serde(default)]
     pub parent_id: Patch<Uuid>,
+    /// Moves the list right before or after one of its siblings.
+    #[serde(default)]
+    pub move_before: Option<Uuid>,
+    #[serde(default)]
+    pub move_after: Option<Uuid>,
+    #[serde(skip_deserializing)]
+    pub rank: Patch<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -43,5 +672,17 @@ pub struct GetModel {
     pub description: Option<String>,
     pub color: String,
     pub parent_id: Option<Uuid>,
+    pub rank: String,
+    #[serde(serialize_with = "option_primitive_date_iso_serialize")]
+    pub archived_at: Option<PrimitiveDateTime>,
     pub child_ids: Vec<Uuid>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct TreeModel {
+    #[serde(flatten)]
+    pub list: GetModel,
+    /// Open tasks in the list itself, subtasks included.
+    pub task_count: i64,
+    pub completed_task_count: i64,
+}
@@ -1,21 +1,51 @@
 use rocket::{Build, Rocket};
 
+pub mod actions;
+pub mod attachments;
 pub mod auth;
+pub mod bulk;
+pub mod comments;
+pub mod dependencies;
+pub mod duplicate;
+pub mod filters;
 pub mod general;
 pub mod labels;
 pub mod lists;
+pub mod quick_add;
+pub mod reminders;
+pub mod search;
 pub mod sessions;
 pub mod tasks;
+pub mod templates;
+pub mod time_entries;
+pub mod trash;
 pub mod users;
 pub mod utils;
+pub mod views;
 
 pub fn mount_rocket(mut rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket = actions::mount_rocket(rocket);
+    rocket = attachments::mount_rocket(rocket);
     rocket = auth::mount_rocket(rocket);
+    rocket = bulk::mount_rocket(rocket);
+    rocket = comments::mount_rocket(rocket);
+    rocket = dependencies::mount_rocket(rocket);
+    rocket = duplicate::mount_rocket(rocket);
+    rocket = filters::mount_rocket(rocket);
     rocket = general::mount_rocket(rocket);
     rocket = lists::mount_rocket(rocket);
+    rocket = lists::mount_tree_rocket(rocket);
+    rocket = lists::mount_archive_rocket(rocket);
     rocket = labels::mount_rocket(rocket);
     rocket = tasks::mount_rocket(rocket);
+    rocket = templates::mount_rocket(rocket);
+    rocket = time_entries::mount_rocket(rocket);
+    rocket = quick_add::mount_rocket(rocket);
+    rocket = reminders::mount_rocket(rocket);
+    rocket = search::mount_rocket(rocket);
+    rocket = trash::mount_rocket(rocket);
     rocket = sessions::mount_rocket(rocket);
     rocket = users::mount_rocket(rocket);
+    rocket = views::mount_rocket(rocket);
     rocket
 }
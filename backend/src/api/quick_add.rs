@@ -0,0 +1,310 @@
+use rocket::{http::Status, serde::json::Json, Build, Rocket};
+use rocket_db_pools::Connection;
+use rocket_validation::Validated;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{postgres::PgRow, Connection as _, Row};
+use time::PrimitiveDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        tasks::{format_due_at, resolve_due_text},
+        utils::{ownership::check_list, timezone::local_now},
+    },
+    database::BackendDb,
+    due_date,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{bad_request, result_bad_request, APIResponse, APIResult, MapAPIResponse},
+};
+
+/// Color given to labels created on the fly by quick add.
+const NEW_LABEL_COLOR: &str = "#808080";
+
+/// Used when the text doesn't mention a due date.
+const DEFAULT_DUE_TEXT: &str = "today";
+
+/// Creates a task from a single line like
+/// `Buy milk tomorrow #Groceries @errands !p1`.
+///
+/// `#` picks the list, `@` adds labels, `!p1` to `!p4` sets the priority,
+/// and the longest phrase that reads as a due date becomes the due date.
+/// Whatever is left over is the title.
+#[post("/quick", data = "<input>", format = "application/json")]
+async fn post_quick(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    input: Validated<Json<QuickAddInput>>,
+) -> APIResult {
+    let input = input.into_deep_inner();
+    let parsed = parse_tokens(&input.text);
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to create task in database.")?;
+    let mut tokens = vec![];
+    let mut changes = vec![];
+
+    let list_id = match parsed.list {
+        Some(list) => {
+            let list_id = sqlx::query_scalar!(
+                "SELECT id FROM lists
+                    WHERE user_id = $1
+                        AND deleted_at IS NULL
+                        AND archived_at IS NULL
+                        AND LOWER(title) = LOWER($2)
+                    ORDER BY title = $2 DESC LIMIT 1",
+                auth_user.id,
+                token_title(list)
+            )
+            .fetch_optional(&mut tx)
+            .await
+            .map_internal_server_error("Failed to fetch list from database.")?;
+            let Some(list_id) = list_id else {
+                return result_bad_request(&format!("List '{}' not found.", token_title(list)));
+            };
+            tokens.push(TokenModel::new(list, "list", json!(list_id)));
+            list_id
+        }
+        None => {
+            let Some(list_id) = input.list_id else {
+                return result_bad_request("No list given, add one with #list.");
+            };
+            check_list(&mut tx, auth_user.id, list_id).await?;
+            // Like a #list, an archived list takes no new tasks
+            let archived = sqlx::query_scalar!(
+                r#"SELECT archived_at IS NOT NULL AS "archived!" FROM lists WHERE id = $1"#,
+                list_id
+            )
+            .fetch_one(&mut tx)
+            .await
+            .map_internal_server_error("Failed to fetch list from database.")?;
+            if archived {
+                return result_bad_request("List not found.");
+            }
+            list_id
+        }
+    };
+
+    let mut label_ids = vec![];
+    for label in parsed.labels {
+        let label_id = sqlx::query_scalar!(
+            "SELECT id FROM labels
+                WHERE user_id = $1 AND deleted_at IS NULL AND LOWER(title) = LOWER($2)
+                ORDER BY title = $2 DESC LIMIT 1",
+            auth_user.id,
+            token_title(label)
+        )
+        .fetch_optional(&mut tx)
+        .await
+        .map_internal_server_error("Failed to fetch label from database.")?;
+        let (label_id, created) = match label_id {
+            Some(label_id) => (label_id, false),
+            None if input.create_labels => {
+                let created = sqlx::query!(
+                    r#"INSERT INTO labels (user_id, title, color) VALUES ($1, $2, $3)
+                        RETURNING id, to_jsonb(labels.*) AS "row!""#,
+                    auth_user.id,
+                    token_title(label),
+                    NEW_LABEL_COLOR
+                )
+                .fetch_one(&mut tx)
+                .await
+                .map_internal_server_error("Failed to create label in database.")?;
+                changes.push(ActionChange {
+                    table: String::from("labels"),
+                    before: None,
+                    after: Some(created.row),
+                });
+                (created.id, true)
+            }
+            None => {
+                return result_bad_request(&format!("Label '{}' not found.", token_title(label)));
+            }
+        };
+        if !label_ids.contains(&label_id) {
+            label_ids.push(label_id);
+        }
+        let mut token = TokenModel::new(label, "label", json!(label_id));
+        token.created = created;
+        tokens.push(token);
+    }
+
+    if let Some((token, priority)) = parsed.priority {
+        tokens.push(TokenModel::new(token, "priority", json!(priority)));
+    }
+
+    let now = local_now(&mut tx, &auth_user.timezone).await?;
+    let (due_text, title) = find_due_text(&parsed.words, now);
+    let title = title.join(" ");
+    if title.is_empty() {
+        return result_bad_request("The task needs a title.");
+    }
+    let due_text = due_text.unwrap_or_else(|| String::from(DEFAULT_DUE_TEXT));
+    let due_date = resolve_due_text(&mut tx, &auth_user, &due_text)
+        .await?
+        .ok_or_else(|| bad_request("Couldn't understand the due date."))?;
+    let recurrence = due_date.recurrence.map(|rule| rule.to_string());
+    let due_at = format_due_at(due_date.due_at)?;
+    tokens.push(TokenModel::new(
+        &due_text,
+        "due_date",
+        json!({
+            "due_at": due_at,
+            "recurrence": recurrence,
+        }),
+    ));
+    tokens.push(TokenModel::new(&title, "title", json!(title)));
+
+    let query = crate::insert_query!(
+        "tasks";
+        list_id: list_id,
+        due_at: due_at,
+        due_text: due_text,
+        title: title,
+        recurrence: recurrence,
+        priority: parsed.priority.map(|(_, priority)| priority);
+        "RETURNING id, to_jsonb(tasks.*) AS row"
+    );
+    let created: PgRow = sqlx::query(&query)
+        .fetch_one(&mut tx)
+        .await
+        .map_internal_server_error("Failed to create task in database.")?;
+    let task_id: Uuid = created.get("id");
+    changes.push(ActionChange {
+        table: String::from("tasks"),
+        before: None,
+        after: Some(created.get("row")),
+    });
+
+    for label_id in label_ids {
+        let row = sqlx::query_scalar!(
+            r#"INSERT INTO task_labels (task_id, label_id) VALUES ($1, $2)
+                RETURNING to_jsonb(task_labels.*) AS "row!""#,
+            task_id,
+            label_id
+        )
+        .fetch_one(&mut tx)
+        .await
+        .map_internal_server_error("Failed to attach label in database.")?;
+        changes.push(ActionChange {
+            table: String::from("task_labels"),
+            before: None,
+            after: Some(row),
+        });
+    }
+
+    record_action(&mut tx, auth_user.id, "create", changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to create task in database.")?;
+
+    let resp = QuickAddResponse {
+        id: task_id,
+        tokens,
+    };
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[derive(Debug, Default)]
+struct ParsedTokens<'a> {
+    list: Option<&'a str>,
+    labels: Vec<&'a str>,
+    priority: Option<(&'a str, i16)>,
+    words: Vec<&'a str>,
+}
+
+fn parse_tokens(text: &str) -> ParsedTokens<'_> {
+    let mut parsed = ParsedTokens::default();
+    for word in text.split_whitespace() {
+        let priority = word
+            .to_ascii_lowercase()
+            .strip_prefix("!p")
+            .and_then(|priority| priority.parse::<i16>().ok())
+            .filter(|priority| (1..=4).contains(priority));
+
+        if word.len() > 1 && word.starts_with('#') && parsed.list.is_none() {
+            parsed.list = Some(word);
+        } else if word.len() > 1 && word.starts_with('@') {
+            parsed.labels.push(word);
+        } else if let (Some(priority), None) = (priority, parsed.priority) {
+            parsed.priority = Some((word, priority));
+        } else {
+            parsed.words.push(word);
+        }
+    }
+    parsed
+}
+
+/// `#Home_Office` names the list "Home Office".
+fn token_title(token: &str) -> String {
+    token[1..].replace('_', " ")
+}
+
+/// Longest run of words tried as a due date, enough for
+/// `every monday and thursday at 9am` and a bit more.
+const MAX_DUE_WORDS: usize = 8;
+
+/// Splits the words into the longest run that reads as a due date,
+/// preferring ones further to the end, and the remaining title words.
+fn find_due_text<'a>(words: &[&'a str], now: PrimitiveDateTime) -> (Option<String>, Vec<&'a str>) {
+    for length in (1..=words.len().min(MAX_DUE_WORDS)).rev() {
+        for start in (0..=words.len() - length).rev() {
+            let candidate = words[start..start + length].join(" ");
+            if due_date::parse(&candidate, now).is_some() {
+                let title = [&words[..start], &words[start + length..]].concat();
+                return (Some(candidate), title);
+            }
+        }
+    }
+    (None, words.to_vec())
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct QuickAddInput {
+    #[validate(length(max = 1000, message = "Text must have at most 1000 characters."))]
+    pub text: String,
+    /// List to use when the text doesn't name one.
+    pub list_id: Option<Uuid>,
+    #[serde(default)]
+    pub create_labels: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuickAddResponse {
+    pub id: Uuid,
+    pub tokens: Vec<TokenModel>,
+}
+
+/// How a part of the text was understood.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenModel {
+    pub text: String,
+    pub kind: String,
+    pub value: Value,
+    /// Whether the item was created because it didn't exist yet.
+    pub created: bool,
+}
+
+impl TokenModel {
+    fn new(text: &str, kind: &str, value: Value) -> TokenModel {
+        TokenModel {
+            text: text.to_owned(),
+            kind: kind.to_owned(),
+            value,
+            created: false,
+        }
+    }
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/tasks", routes![post_quick])
+}
@@ -0,0 +1,241 @@
+use rocket::{http::Status, serde::json::Json, Build, Rocket, State};
+use rocket_db_pools::Connection;
+use rocket_validation::Validated;
+use serde::{Deserialize, Serialize};
+use sqlx::Connection as _;
+use time::{format_description::well_known::Iso8601, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        tasks::check_task_owner,
+        utils::{
+            serde::option_primitive_date_iso_serialize, validation::validate_reminder_channel,
+        },
+    },
+    config::AppConfig,
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    reminders::notifiers::resolve_webhook,
+    responses::{
+        bad_request, ok, result_bad_request, result_not_found, APIResponse, APIResult,
+        MapAPIResponse,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetModel {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    #[serde(serialize_with = "option_primitive_date_iso_serialize")]
+    pub remind_at: Option<PrimitiveDateTime>,
+    pub offset_minutes: Option<i32>,
+    pub channel: String,
+    pub target: Option<String>,
+    /// When the reminder fires next, or last fired. Missing for relative
+    /// reminders on tasks without a due date.
+    #[serde(serialize_with = "option_primitive_date_iso_serialize")]
+    pub fire_at: Option<PrimitiveDateTime>,
+    #[serde(serialize_with = "option_primitive_date_iso_serialize")]
+    pub delivered_at: Option<PrimitiveDateTime>,
+    pub attempts: i32,
+    #[serde(serialize_with = "option_primitive_date_iso_serialize")]
+    pub next_attempt_at: Option<PrimitiveDateTime>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PostInput {
+    /// A fixed time to remind at.
+    pub remind_at: Option<String>,
+    /// Minutes after the task's due date, negative to remind before it.
+    pub offset_minutes: Option<i32>,
+    #[validate(custom = "validate_reminder_channel")]
+    pub channel: String,
+    /// The email address or webhook url to deliver to.
+    pub target: Option<String>,
+}
+
+fn parse_remind_at(remind_at: &str) -> Result<PrimitiveDateTime, APIResponse> {
+    let remind_at = OffsetDateTime::parse(remind_at, &Iso8601::DEFAULT)
+        .map_err(|_| bad_request("Invalid reminder time."))?
+        .to_offset(UtcOffset::UTC);
+    Ok(PrimitiveDateTime::new(remind_at.date(), remind_at.time()))
+}
+
+async fn validate_target(
+    channel: &str,
+    target: Option<&str>,
+    config: &AppConfig,
+) -> Result<(), APIResponse> {
+    match (channel, target) {
+        ("email", Some(target)) if validator::validate_email(target) => Ok(()),
+        ("email", _) => Err(bad_request(
+            "Email reminders need an email address as target.",
+        )),
+        ("webhook", Some(target)) => match reqwest::Url::parse(target) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {
+                resolve_webhook(&url, &config.webhook_allowed_hosts)
+                    .await
+                    .map_err(|e| bad_request(&e))?;
+                Ok(())
+            }
+            _ => Err(bad_request(
+                "Webhook reminders need an http(s) url as target.",
+            )),
+        },
+        ("webhook", None) => Err(bad_request(
+            "Webhook reminders need an http(s) url as target.",
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[get("/<task_id>/reminders")]
+async fn get_all(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+) -> APIResult {
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let reminders = sqlx::query_as!(
+        GetModel,
+        r#"
+        SELECT
+            reminders.id,
+            reminders.task_id,
+            reminders.remind_at,
+            reminders.offset_minutes,
+            reminders.channel,
+            reminders.target,
+            COALESCE(
+                reminders.remind_at,
+                tasks.due_at + reminders.offset_minutes * INTERVAL '1 minute'
+            ) AS fire_at,
+            reminders.delivered_at,
+            reminders.attempts,
+            reminders.next_attempt_at,
+            reminders.last_error
+            FROM reminders
+            INNER JOIN tasks ON tasks.id = reminders.task_id
+        WHERE reminders.task_id = $1
+        ORDER BY fire_at NULLS LAST, reminders.id
+        "#,
+        task_id
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch reminders from database.")?;
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(reminders)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[post("/<task_id>/reminders", data = "<input>", format = "application/json")]
+async fn post(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    config: &State<AppConfig>,
+    task_id: Uuid,
+    input: Validated<Json<PostInput>>,
+) -> APIResult {
+    let input = input.into_deep_inner();
+    let remind_at = match (&input.remind_at, input.offset_minutes) {
+        (Some(remind_at), None) => Some(parse_remind_at(remind_at)?),
+        (None, Some(_)) => None,
+        _ => return result_bad_request("Give either remind_at or offset_minutes."),
+    };
+    validate_target(&input.channel, input.target.as_deref(), config).await?;
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to create reminder in database.")?;
+    let created = sqlx::query!(
+        r#"INSERT INTO reminders (task_id, remind_at, offset_minutes, channel, target)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, to_jsonb(reminders.*) AS "row!""#,
+        task_id,
+        remind_at,
+        input.offset_minutes,
+        input.channel,
+        input.target
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to create reminder in database.")?;
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "create",
+        vec![ActionChange {
+            table: String::from("reminders"),
+            before: None,
+            after: Some(created.row),
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to create reminder in database.")?;
+
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::json!({ "id": created.id }),
+    ))
+}
+
+#[delete("/<task_id>/reminders/<id>")]
+async fn delete(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    id: Uuid,
+) -> APIResult {
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to delete reminder from database.")?;
+    let deleted = sqlx::query_scalar!(
+        r#"DELETE FROM reminders WHERE id = $1 AND task_id = $2
+            RETURNING to_jsonb(reminders.*) AS "row!""#,
+        id,
+        task_id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to delete reminder from database.")?;
+    let Some(deleted) = deleted else {
+        return result_not_found("Reminder not found.");
+    };
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "delete",
+        vec![ActionChange {
+            table: String::from("reminders"),
+            before: Some(deleted),
+            after: None,
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to delete reminder from database.")?;
+
+    Ok(ok("Reminder deleted successfully."))
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/tasks", routes![get_all, post, delete])
+}
@@ -0,0 +1,162 @@
+use rocket::{http::Status, Build, Rocket};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    api::utils::{GetAllResponse, GET_LIMIT},
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{result_bad_request, APIResponse, APIResult, MapAPIResponse},
+};
+
+/// Options for `ts_headline`, matches are wrapped in `<mark>`.
+const HEADLINE_OPTIONS: &str = "StartSel=<mark>, StopSel=</mark>, MaxWords=20, MinWords=5";
+
+/// Searches the titles of tasks, lists and labels and the descriptions
+/// of tasks, best matches first. Every word matches as a prefix, so
+/// "gro" finds "Groceries".
+#[get("/?<q>&<include_completed>&<limit>&<page>")]
+async fn search(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    q: &str,
+    include_completed: Option<bool>,
+    limit: Option<u32>,
+    page: Option<u32>,
+) -> APIResult {
+    let limit = limit.unwrap_or(GET_LIMIT);
+    let page = page.unwrap_or(0);
+    let Some(ts_query) = prefix_ts_query(q) else {
+        return result_bad_request("The search needs at least one word.");
+    };
+
+    // The vectors are written out the same way as in the search indexes.
+    let rows = sqlx::query!(
+        r#"
+        WITH search AS (SELECT to_tsquery('english', $2) AS query)
+        SELECT * FROM (
+            SELECT
+                tasks.id AS "id!",
+                'task' AS "item_type!",
+                tasks.title AS "title!",
+                ts_headline(
+                    'english',
+                    tasks.title || ' ' || COALESCE(tasks.description, ''),
+                    search.query,
+                    $3
+                ) AS "snippet!",
+                ts_rank(
+                    setweight(to_tsvector('english', tasks.title), 'A') ||
+                    setweight(to_tsvector('english', COALESCE(tasks.description, '')), 'B'),
+                    search.query
+                ) AS "rank!",
+                tasks.completed AS "completed?",
+                tasks.list_id AS "list_id?"
+                FROM search, tasks
+                INNER JOIN lists
+                    ON lists.id = tasks.list_id
+            WHERE lists.user_id = $1
+                AND tasks.deleted_at IS NULL
+                AND lists.archived_at IS NULL
+                AND ($4 OR NOT tasks.completed)
+                AND (
+                    setweight(to_tsvector('english', tasks.title), 'A') ||
+                    setweight(to_tsvector('english', COALESCE(tasks.description, '')), 'B')
+                ) @@ search.query
+            UNION ALL
+            SELECT
+                lists.id,
+                'list',
+                lists.title,
+                ts_headline('english', lists.title, search.query, $3),
+                ts_rank(to_tsvector('english', lists.title), search.query),
+                NULL,
+                NULL
+                FROM search, lists
+            WHERE lists.user_id = $1
+                AND lists.deleted_at IS NULL
+                AND lists.archived_at IS NULL
+                AND to_tsvector('english', lists.title) @@ search.query
+            UNION ALL
+            SELECT
+                labels.id,
+                'label',
+                labels.title,
+                ts_headline('english', labels.title, search.query, $3),
+                ts_rank(to_tsvector('english', labels.title), search.query),
+                NULL,
+                NULL
+                FROM search, labels
+            WHERE labels.user_id = $1
+                AND labels.deleted_at IS NULL
+                AND to_tsvector('english', labels.title) @@ search.query
+        ) results
+        ORDER BY "rank!" DESC, "title!", "id!" LIMIT $5 OFFSET $6"#,
+        auth_user.id,
+        ts_query,
+        HEADLINE_OPTIONS,
+        include_completed.unwrap_or(false),
+        limit as i64,
+        (page * limit) as i64
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Error searching items")?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| GetModel {
+            id: row.id,
+            item_type: row.item_type,
+            title: row.title,
+            snippet: row.snippet,
+            rank: row.rank,
+            completed: row.completed,
+            list_id: row.list_id,
+        })
+        .collect();
+    let resp = GetAllResponse::<GetModel> { items, limit, page };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// Turns free text into a `tsquery` where every word has to match as a
+/// prefix (`buy milk` becomes `buy:* & milk:*`).
+///
+/// Only letters and digits are kept, so the text can't inject operators.
+fn prefix_ts_query(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}:*", word.to_lowercase()))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(words.join(" & "))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetModel {
+    pub id: Uuid,
+    /// Either "task", "list" or "label".
+    pub item_type: String,
+    pub title: String,
+    /// The matching text with matches wrapped in `<mark>`.
+    pub snippet: String,
+    pub rank: f32,
+    /// Only set for tasks.
+    pub completed: Option<bool>,
+    /// Only set for tasks.
+    pub list_id: Option<Uuid>,
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/search", routes![search])
+}
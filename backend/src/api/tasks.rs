@@ -1,89 +1,180 @@
 use once_cell::sync::Lazy;
 use rocket::{http::Status, serde::json::Json, Build, Rocket};
 use rocket_db_pools::Connection;
+use rocket_validation::Validated;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Connection as _, PgConnection};
 use std::collections::{HashMap, HashSet};
-use time::PrimitiveDateTime;
+use time::{format_description::well_known::Iso8601, OffsetDateTime, PrimitiveDateTime, UtcOffset};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    api::utils::{serde::primitive_date_iso_serialize, GetAllResponse, GET_LIMIT},
+    api::{
+        actions::{record_action, ActionChange},
+        utils::{
+            serde::{option_primitive_date_iso_serialize, primitive_date_iso_serialize},
+            timezone::{local_now, to_local, to_utc},
+            GetAllResponse, GET_LIMIT,
+        },
+    },
     database::BackendDb,
+    due_date::{self, ParsedDueDate},
     guards::auth::Auth,
-    models::user::UserModel,
+    models::{task::TaskModel, user::UserModel},
+    recurrence::RRule,
     responses::{
-        internal_server_error, not_found, ok, result_not_found, APIResponse, APIResult,
-        MapAPIResponse,
+        bad_request, conflict, internal_server_error, not_found, ok, result_bad_request,
+        result_not_found, APIResponse, APIResult, MapAPIResponse,
     },
 };
 
-use super::utils::Patch;
+use super::utils::{
+    ownership::{check_label, check_list, check_task},
+    placement::{rank_among, Placement},
+    tree::{nest, MAX_TREE_DEPTH},
+    validation::{
+        validate_patch_priority, validate_patch_recurrence, validate_priority, validate_recurrence,
+    },
+    Patch,
+};
 
-#[get("/?<limit>&<page>")]
+#[get("/?<limit>&<page>&<filter..>")]
 async fn get_all(
     auth_user: Auth<UserModel>,
     mut db: Connection<BackendDb>,
     limit: Option<u32>,
     page: Option<u32>,
+    filter: TaskFilter<'_>,
 ) -> APIResult {
     let limit = limit.unwrap_or(GET_LIMIT);
     let page = page.unwrap_or(0);
+    let due_before = filter.due_before.map(parse_due_at).transpose()?;
+    let due_after = filter.due_after.map(parse_due_at).transpose()?;
+    let fields = filter.fields.map(parse_fields).transpose()?;
+    if filter
+        .priority
+        .is_some_and(|priority| validate_priority(priority).is_err())
+    {
+        return result_bad_request("Invalid priority filter.");
+    }
 
     let query: Vec<_> = sqlx::query!(
         r#"
-        SELECT 
+        SELECT
             base_tasks.*,
             child_tasks.id AS "child_id?",
             task_labels.label_id as "label_id?"
             FROM (
-                SELECT tasks.* FROM tasks
+                SELECT
+                    tasks.*,
+                    task_blocked(tasks.id) AS "blocked!",
+                    ROW_NUMBER() OVER (
+                        ORDER BY
+                            CASE WHEN $12 THEN NULL WHEN $11 = 'rank' THEN tasks.rank END,
+                            CASE WHEN $12 AND $11 = 'rank' THEN tasks.rank END DESC,
+                            CASE WHEN $12 THEN NULL WHEN $11 = 'due_at' THEN tasks.due_at END,
+                            CASE WHEN $12 AND $11 = 'due_at' THEN tasks.due_at END DESC,
+                            CASE WHEN $12 THEN NULL WHEN $11 = 'created_at' THEN tasks.created_at END,
+                            CASE WHEN $12 AND $11 = 'created_at' THEN tasks.created_at END DESC,
+                            CASE WHEN $12 THEN NULL WHEN $11 = 'title' THEN tasks.title END,
+                            CASE WHEN $12 AND $11 = 'title' THEN tasks.title END DESC,
+                            CASE WHEN $12 THEN NULL WHEN $11 = 'priority' THEN tasks.priority END,
+                            CASE WHEN $12 AND $11 = 'priority' THEN tasks.priority END DESC,
+                            tasks.created_at, tasks.id
+                    ) AS "position!"
+                    FROM tasks
                     INNER JOIN lists
                     ON lists.id = tasks.list_id
-                WHERE user_id = $1 LIMIT $2 OFFSET $3
+                WHERE user_id = $1
+                    AND tasks.deleted_at IS NULL
+                    AND ($4::UUID IS NULL OR tasks.list_id = $4)
+                    AND ($4::UUID IS NOT NULL OR lists.archived_at IS NULL)
+                    AND ($5::UUID IS NULL OR EXISTS (
+                        SELECT 1 FROM task_labels
+                        WHERE task_labels.task_id = tasks.id AND task_labels.label_id = $5
+                    ))
+                    AND ($6::BOOL IS NULL OR tasks.completed = $6)
+                    AND ($7::UUID IS NULL OR tasks.parent_id = $7)
+                    AND (NOT $8 OR tasks.parent_id IS NULL)
+                    AND ($9::TIMESTAMP IS NULL OR tasks.due_at < $9)
+                    AND ($10::TIMESTAMP IS NULL OR tasks.due_at >= $10)
+                    AND ($13::SMALLINT IS NULL OR tasks.priority = $13)
+                ORDER BY "position!" LIMIT $2 OFFSET $3
             ) base_tasks
             LEFT JOIN tasks child_tasks 
                 ON base_tasks.id = child_tasks.parent_id
+                AND child_tasks.deleted_at IS NULL
             LEFT JOIN task_labels 
-                ON base_tasks.id = task_labels.task_id"#,
+                ON base_tasks.id = task_labels.task_id
+                AND task_labels.label_id IN (SELECT id FROM labels WHERE deleted_at IS NULL)
+            ORDER BY child_tasks.rank, child_tasks.id"#,
         auth_user.id,
         limit as i64,
-        (page * limit) as i64
+        (page * limit) as i64,
+        filter.list_id,
+        filter.label_id,
+        filter.completed,
+        filter.parent_id,
+        filter.top_level.unwrap_or(false),
+        due_before,
+        due_after,
+        filter.sort.unwrap_or(TaskSort::Rank).as_str(),
+        matches!(filter.order, Some(SortOrder::Desc)),
+        filter.priority
     )
     .fetch_all(&mut *db)
     .await
     .map_internal_server_error("Error fetching items")?;
 
-    let mut items: HashMap<Uuid, GetModelBuilder> = HashMap::new();
+    let mut items: HashMap<Uuid, (i64, GetModelBuilder)> = HashMap::new();
     for row in query {
-        let item = items.entry(row.id).or_insert_with(|| GetModelBuilder {
-            get_model: GetModel {
-                id: row.id,
-                parent_id: row.parent_id,
-                list_id: row.list_id,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-                due_at: row.due_at,
-                due_text: row.due_text,
-                completed: row.completed,
-                title: row.title,
-                description: row.description,
-                child_ids: vec![],
-                label_ids: vec![],
-            },
-            child_ids: HashSet::new(),
-            label_ids: HashSet::new(),
+        let (_, item) = items.entry(row.id).or_insert_with(|| {
+            (
+                row.position,
+                GetModelBuilder {
+                    get_model: GetModel {
+                        id: row.id,
+                        parent_id: row.parent_id,
+                        list_id: row.list_id,
+                        created_at: row.created_at,
+                        updated_at: row.updated_at,
+                        due_at: row.due_at,
+                        due_text: row.due_text,
+                        completed: row.completed,
+                        completed_at: row.completed_at,
+                        blocked: row.blocked,
+                        title: row.title,
+                        description: row.description,
+                        recurrence: row.recurrence,
+                        recurrence_from_completion: row.recurrence_from_completion,
+                        recurrence_keep_history: row.recurrence_keep_history,
+                        priority: row.priority,
+                        rank: row.rank,
+                        child_ids: vec![],
+                        label_ids: vec![],
+                    },
+                    child_ids: vec![],
+                    label_ids: HashSet::new(),
+                },
+            )
         });
         if let Some(child_id) = row.child_id {
-            item.child_ids.insert(child_id);
+            item.add_child(child_id);
         }
         if let Some(label_id) = row.label_id {
             item.label_ids.insert(label_id);
         }
     }
 
-    let items = items.into_values().map(|row| row.build()).collect();
-    let resp = GetAllResponse::<GetModel> { items, limit, page };
+    let mut items: Vec<_> = items.into_values().collect();
+    items.sort_by_key(|(position, _)| *position);
+    let items = items
+        .into_iter()
+        .map(|(_, item)| select_fields(item.build(), fields.as_deref()))
+        .collect::<Result<_, _>>()?;
+    let resp = GetAllResponse::<Value> { items, limit, page };
 
     Ok(APIResponse::new(
         Status::Ok,
@@ -92,6 +183,64 @@ async fn get_all(
     ))
 }
 
+/// How many completed tasks are shown at once when a list shows them.
+const COMPLETED_LIMIT: u32 = 20;
+
+/// Fields of [`GetModel`] that can be picked with `fields`.
+const GET_FIELDS: &[&str] = &[
+    "id",
+    "parent_id",
+    "list_id",
+    "created_at",
+    "updated_at",
+    "due_at",
+    "due_text",
+    "completed",
+    "completed_at",
+    "blocked",
+    "title",
+    "description",
+    "recurrence",
+    "recurrence_from_completion",
+    "recurrence_keep_history",
+    "priority",
+    "rank",
+    "child_ids",
+    "label_ids",
+];
+
+fn parse_fields(fields: &str) -> Result<Vec<String>, APIResponse> {
+    let fields: Vec<_> = fields
+        .split(',')
+        .map(|field| field.trim().to_owned())
+        .filter(|field| !field.is_empty())
+        .collect();
+    if let Some(field) = fields
+        .iter()
+        .find(|field| !GET_FIELDS.contains(&field.as_str()))
+    {
+        return Err(bad_request(&format!("Unknown field '{field}'.")));
+    }
+    Ok(fields)
+}
+
+/// Leaves only the requested fields of a task, the id is always kept.
+fn select_fields(item: GetModel, fields: Option<&[String]>) -> Result<Value, APIResponse> {
+    let mut value = serde_json::to_value(item)
+        .map_internal_server_error("Failed to convert item into json.")?;
+    if let (Some(fields), Value::Object(map)) = (fields, &mut value) {
+        map.retain(|key, _| key == "id" || fields.contains(key));
+    }
+    Ok(value)
+}
+
+fn parse_due_at(due_at: &str) -> Result<PrimitiveDateTime, APIResponse> {
+    let due_at = OffsetDateTime::parse(due_at, &Iso8601::DEFAULT)
+        .map_err(|_| bad_request("Invalid due date filter."))?
+        .to_offset(UtcOffset::UTC);
+    Ok(PrimitiveDateTime::new(due_at.date(), due_at.time()))
+}
+
 #[get("/<id>")]
 async fn get_single(
     auth_user: Auth<UserModel>,
@@ -105,15 +254,18 @@ async fn get_single(
             child_tasks.id AS "child_id?",
             task_labels.label_id as "label_id?"
             FROM (
-                SELECT tasks.* FROM tasks
+                SELECT tasks.*, task_blocked(tasks.id) AS "blocked!" FROM tasks
                     INNER JOIN lists
                     ON lists.id = tasks.list_id
-                WHERE tasks.id = $1 AND user_id = $2
+                WHERE tasks.id = $1 AND user_id = $2 AND tasks.deleted_at IS NULL
             ) base_tasks
             LEFT JOIN tasks child_tasks 
                 ON base_tasks.id = child_tasks.parent_id
+                AND child_tasks.deleted_at IS NULL
             LEFT JOIN task_labels 
-                ON base_tasks.id = task_labels.task_id"#,
+                ON base_tasks.id = task_labels.task_id
+                AND task_labels.label_id IN (SELECT id FROM labels WHERE deleted_at IS NULL)
+            ORDER BY child_tasks.rank, child_tasks.id"#,
         id,
         auth_user.id
     )
@@ -136,16 +288,23 @@ async fn get_single(
                 due_at: row.due_at,
                 due_text: row.due_text,
                 completed: row.completed,
+                completed_at: row.completed_at,
+                blocked: row.blocked,
                 title: row.title,
                 description: row.description,
+                recurrence: row.recurrence,
+                recurrence_from_completion: row.recurrence_from_completion,
+                recurrence_keep_history: row.recurrence_keep_history,
+                priority: row.priority,
+                rank: row.rank,
                 child_ids: vec![],
                 label_ids: vec![],
             },
-            child_ids: HashSet::new(),
+            child_ids: vec![],
             label_ids: HashSet::new(),
         });
         if let Some(child_id) = row.child_id {
-            task.child_ids.insert(child_id);
+            task.add_child(child_id);
         }
         if let Some(label_id) = row.label_id {
             task.label_ids.insert(label_id);
@@ -160,25 +319,809 @@ async fn get_single(
     ))
 }
 
-crate::api_patch! {
-    model_table: "tasks",
-    input: PatchInput,
-    input_fields: { parent_id, list_id, due_at, due_text, completed, title, description },
-    query_where: "WHERE id = $1 AND list_id IN (SELECT id FROM lists WHERE user_id = $2)"
+/// Returns a task with all its subtasks nested under `children`, down to
+/// `max_depth` levels below the task if given.
+#[get("/<id>/tree?<max_depth>")]
+async fn get_tree(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+    max_depth: Option<u32>,
+) -> APIResult {
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT tasks.id, 0 AS depth, ARRAY[tasks.id] AS path FROM tasks
+                INNER JOIN lists ON lists.id = tasks.list_id
+            WHERE tasks.id = $1 AND lists.user_id = $2 AND tasks.deleted_at IS NULL
+            UNION ALL
+            SELECT tasks.id, subtree.depth + 1, subtree.path || tasks.id FROM tasks
+                INNER JOIN subtree ON tasks.parent_id = subtree.id
+            WHERE tasks.deleted_at IS NULL
+                AND NOT tasks.id = ANY(subtree.path)
+                AND subtree.depth < LEAST($3::INTEGER, $4)
+        )
+        SELECT
+            tasks.*,
+            task_blocked(tasks.id) AS "blocked!",
+            ARRAY(
+                SELECT child_tasks.id FROM tasks child_tasks
+                WHERE child_tasks.parent_id = tasks.id AND child_tasks.deleted_at IS NULL
+                ORDER BY child_tasks.rank, child_tasks.id
+            ) AS "child_ids!",
+            ARRAY(
+                SELECT task_labels.label_id FROM task_labels
+                    INNER JOIN labels ON labels.id = task_labels.label_id
+                WHERE task_labels.task_id = tasks.id AND labels.deleted_at IS NULL
+            ) AS "label_ids!"
+            FROM subtree
+            INNER JOIN tasks ON tasks.id = subtree.id
+        ORDER BY subtree.depth, tasks.rank, tasks.id"#,
+        id,
+        auth_user.id,
+        max_depth.map(|depth| depth.min(i32::MAX as u32) as i32),
+        MAX_TREE_DEPTH as i32
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Error fetching items")?;
+    if rows.is_empty() {
+        return result_not_found("Item not found.");
+    }
+
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let item = GetModel {
+                id: row.id,
+                parent_id: row.parent_id,
+                list_id: row.list_id,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                due_at: row.due_at,
+                due_text: row.due_text,
+                completed: row.completed,
+                completed_at: row.completed_at,
+                blocked: row.blocked,
+                title: row.title,
+                description: row.description,
+                recurrence: row.recurrence,
+                recurrence_from_completion: row.recurrence_from_completion,
+                recurrence_keep_history: row.recurrence_keep_history,
+                priority: row.priority,
+                rank: row.rank,
+                child_ids: row.child_ids,
+                label_ids: row.label_ids,
+            };
+            // The task is the root even if it sits on a cycle of parents
+            let parent_id = row.parent_id.filter(|_| row.id != id);
+            (row.id, parent_id, item)
+        })
+        .collect();
+    let Some(tree) = nest(rows).into_iter().next() else {
+        return result_not_found("Item not found.");
+    };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(&tree)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[patch("/<id>", data = "<input>", format = "application/json")]
+async fn patch(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    input: Validated<Json<PatchInput>>,
+    id: Uuid,
+) -> APIResult {
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to patch in database.")?;
+    let changes = patch_task(&mut tx, &auth_user, id, input.into_deep_inner()).await?;
+    record_action(&mut tx, auth_user.id, "update", changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to patch in database.")?;
+    Ok(ok("Patch successful."))
+}
+
+/// Patches a task the user owns, returning the changes to record.
+///
+/// Shared by single and bulk patches, so both go through the same checks.
+pub async fn patch_task(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    mut input: PatchInput,
+) -> Result<Vec<ActionChange>, APIResponse> {
     // Only let the user patch tasks they own.
+    const QUERY_WHERE: &str = "WHERE id = $1 AND deleted_at IS NULL \
+        AND list_id IN (SELECT id FROM lists WHERE user_id = $2)";
+    static BEFORE_QUERY_STRING: Lazy<String> =
+        Lazy::new(|| format!("SELECT to_jsonb(tasks.*) FROM tasks {QUERY_WHERE} FOR UPDATE"));
+    static RETURNING: Lazy<String> =
+        Lazy::new(|| format!("{QUERY_WHERE} RETURNING to_jsonb(tasks.*)"));
+
+    prepare_patch(db, user, id, &mut input).await?;
+    let update_str = crate::update_query! {
+        "tasks";
+        parent_id: input.parent_id,
+        list_id: input.list_id,
+        due_at: input.due_at,
+        due_text: input.due_text,
+        completed: input.completed,
+        title: input.title,
+        description: input.description,
+        recurrence: input.recurrence,
+        recurrence_from_completion: input.recurrence_from_completion,
+        recurrence_keep_history: input.recurrence_keep_history,
+        priority: input.priority,
+        rank: input.rank;
+        *RETURNING
+    };
+    let Some(update_str) = update_str else {
+        return Err(bad_request("Empty patch request."));
+    };
+
+    let before: Option<Value> = sqlx::query_scalar(&BEFORE_QUERY_STRING)
+        .bind(id)
+        .bind(user.id)
+        .fetch_optional(&mut *db)
+        .await
+        .map_internal_server_error("Failed to patch in database.")?;
+    let Some(before) = before else {
+        return Err(not_found("Item not found."));
+    };
+    let after: Value = sqlx::query_scalar(&update_str)
+        .bind(id)
+        .bind(user.id)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(_) => bad_request("Invalid patch request."),
+            _ => internal_server_error("Failed to patch in database."),
+        })?;
+    let mut changes = apply_patch(db, user, &before, &after).await?;
+    changes.insert(
+        0,
+        ActionChange {
+            table: String::from("tasks"),
+            before: Some(before),
+            after: Some(after),
+        },
+    );
+    Ok(changes)
 }
 
 crate::api_post! {
     model_table: "tasks",
     input: PostInput,
-    input_fields: { parent_id, list_id, due_at, due_text, completed, title, description },
-    user_id: false
+    input_fields: {
+        parent_id, list_id, due_at, due_text, completed, title, description,
+        recurrence, recurrence_from_completion, recurrence_keep_history, priority
+    },
+    user_id: false,
+    before_insert: prepare_post
 }
 
-crate::api_delete! {
-    model_table: "tasks",
-    query_where: "WHERE id = $1 AND list_id IN (SELECT id FROM lists WHERE user_id = $2)"
-    // Only let the user delete tasks they own.
+#[get("/due_date?<text>")]
+async fn get_due_date(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    text: &str,
+) -> APIResult {
+    let Some(due_date) = resolve_due_text(&mut db, &auth_user, text).await? else {
+        return result_bad_request("Couldn't understand the due date.");
+    };
+    let resp = DueDateModel {
+        due_at: due_date.due_at,
+        recurrence: due_date.recurrence.map(|rule| rule.to_string()),
+        timezone: auth_user.timezone.clone(),
+    };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// The completed tasks of a list, most recently completed first.
+///
+/// Completed tasks aren't synced, clients fetch them when they are shown.
+#[get("/completed?<list_id>&<limit>&<page>")]
+async fn get_completed(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    list_id: Uuid,
+    limit: Option<u32>,
+    page: Option<u32>,
+) -> APIResult {
+    let limit = limit.unwrap_or(COMPLETED_LIMIT);
+    let page = page.unwrap_or(0);
+    check_list(&mut db, auth_user.id, list_id).await?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            tasks.*,
+            task_blocked(tasks.id) AS "blocked!",
+            ARRAY(
+                SELECT child_tasks.id FROM tasks child_tasks
+                WHERE child_tasks.parent_id = tasks.id AND child_tasks.deleted_at IS NULL
+                ORDER BY child_tasks.rank, child_tasks.id
+            ) AS "child_ids!",
+            ARRAY(
+                SELECT task_labels.label_id FROM task_labels
+                    INNER JOIN labels ON labels.id = task_labels.label_id
+                WHERE task_labels.task_id = tasks.id AND labels.deleted_at IS NULL
+            ) AS "label_ids!"
+            FROM tasks
+        WHERE tasks.list_id = $1 AND tasks.completed AND tasks.deleted_at IS NULL
+        ORDER BY tasks.completed_at DESC, tasks.id
+        LIMIT $2 OFFSET $3"#,
+        list_id,
+        limit as i64,
+        (page * limit) as i64
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Error fetching items")?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| GetModel {
+            id: row.id,
+            parent_id: row.parent_id,
+            list_id: row.list_id,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            due_at: row.due_at,
+            due_text: row.due_text,
+            completed: row.completed,
+            completed_at: row.completed_at,
+            blocked: row.blocked,
+            title: row.title,
+            description: row.description,
+            recurrence: row.recurrence,
+            recurrence_from_completion: row.recurrence_from_completion,
+            recurrence_keep_history: row.recurrence_keep_history,
+            priority: row.priority,
+            rank: row.rank,
+            child_ids: row.child_ids,
+            label_ids: row.label_ids,
+        })
+        .collect();
+    let resp = GetAllResponse::<GetModel> { items, limit, page };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// Reads `due_text` in the user's timezone, returning the due date in UTC.
+pub async fn resolve_due_text(
+    db: &mut PgConnection,
+    user: &UserModel,
+    due_text: &str,
+) -> Result<Option<ParsedDueDate>, APIResponse> {
+    let now = local_now(db, &user.timezone).await?;
+    let Some(due_date) = due_date::parse(due_text, now) else {
+        return Ok(None);
+    };
+    Ok(Some(ParsedDueDate {
+        due_at: to_utc(db, due_date.due_at, &user.timezone).await?,
+        recurrence: due_date.recurrence,
+    }))
+}
+
+async fn prepare_post(
+    db: &mut PgConnection,
+    user: &UserModel,
+    input: &mut PostInput,
+) -> Result<(), APIResponse> {
+    check_list(db, user.id, input.list_id).await?;
+    if let Some(parent_id) = input.parent_id {
+        if check_task(db, user.id, parent_id).await? != input.list_id {
+            return Err(bad_request(SUBTASK_LIST_MESSAGE));
+        }
+    }
+    resolve_post_due_text(db, user, input).await
+}
+
+/// Fills in `due_at` from `due_text` when only the latter was given.
+/// A repeating phrase like "every monday" also sets the recurrence.
+async fn resolve_post_due_text(
+    db: &mut PgConnection,
+    user: &UserModel,
+    input: &mut PostInput,
+) -> Result<(), APIResponse> {
+    if input.due_at.is_some() {
+        return Ok(());
+    }
+    let Some(due_date) = resolve_due_text(db, user, &input.due_text).await? else {
+        return Err(bad_request("Couldn't understand the due date."));
+    };
+    input.due_at = Some(format_due_at(due_date.due_at)?);
+    if input.recurrence.is_none() {
+        input.recurrence = due_date.recurrence.map(|rule| rule.to_string());
+    }
+    Ok(())
+}
+
+async fn prepare_patch(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    input: &mut PatchInput,
+) -> Result<(), APIResponse> {
+    check_blockers(db, user, id, input).await?;
+    check_parent(db, user, id, input).await?;
+    resolve_patch_due_text(db, user, input).await?;
+    place_patched_task(db, user, id, input).await
+}
+
+async fn check_blockers(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    input: &PatchInput,
+) -> Result<(), APIResponse> {
+    if !matches!(input.completed, Patch::Value(true)) || input.force {
+        return Ok(());
+    }
+    let blocked = sqlx::query_scalar!(
+        r#"SELECT task_blocked(tasks.id) AS "blocked!" FROM tasks
+            INNER JOIN lists ON lists.id = tasks.list_id
+            WHERE tasks.id = $1 AND lists.user_id = $2 AND NOT tasks.completed"#,
+        id,
+        user.id
+    )
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch task from database.")?;
+    if blocked == Some(true) {
+        return Err(conflict(
+            "Task is blocked by open tasks, complete them first or force it.",
+        ));
+    }
+    Ok(())
+}
+
+pub const SUBTASK_LIST_MESSAGE: &str = "A subtask must be in the same list as its parent.";
+
+/// Keeps subtasks in the list of their parent and out of their own subtree.
+///
+/// A task moved under a parent follows it to its list, while a subtask
+/// can only change lists along with its parent or once detached from it.
+async fn check_parent(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    input: &mut PatchInput,
+) -> Result<(), APIResponse> {
+    if let Patch::Value(list_id) = input.list_id {
+        check_list(db, user.id, list_id).await?;
+    }
+    let parent_id = match (&input.parent_id, &input.list_id) {
+        (Patch::Value(parent_id), _) => *parent_id,
+        (Patch::Missing, Patch::Value(list_id)) => {
+            let current = sqlx::query!(
+                "SELECT tasks.parent_id, tasks.list_id FROM tasks
+                    INNER JOIN lists ON lists.id = tasks.list_id
+                    WHERE tasks.id = $1 AND lists.user_id = $2",
+                id,
+                user.id
+            )
+            .fetch_optional(&mut *db)
+            .await
+            .map_internal_server_error("Failed to fetch task from database.")?;
+            return match current {
+                Some(current) if current.parent_id.is_some() && current.list_id != *list_id => {
+                    Err(bad_request(SUBTASK_LIST_MESSAGE))
+                }
+                _ => Ok(()),
+            };
+        }
+        _ => return Ok(()),
+    };
+
+    let parent_list_id = check_task(db, user.id, parent_id).await?;
+
+    // Two moves at once could close a cycle neither sees alone
+    sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", user.id)
+        .fetch_one(&mut *db)
+        .await
+        .map_internal_server_error("Failed to patch in database.")?;
+    let cycle = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE ancestors AS (
+            SELECT tasks.id, tasks.parent_id FROM tasks
+                INNER JOIN lists ON lists.id = tasks.list_id
+            WHERE tasks.id = $1 AND lists.user_id = $3
+            UNION
+            SELECT tasks.id, tasks.parent_id FROM tasks
+                INNER JOIN ancestors ON tasks.id = ancestors.parent_id
+        )
+        SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = $2) AS "cycle!""#,
+        parent_id,
+        id,
+        user.id
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch tasks from database.")?;
+    if cycle {
+        return Err(bad_request(
+            "A task can't be moved under itself or one of its subtasks.",
+        ));
+    }
+
+    match input.list_id {
+        Patch::Value(list_id) if list_id != parent_list_id => {
+            Err(bad_request(SUBTASK_LIST_MESSAGE))
+        }
+        _ => {
+            input.list_id = Patch::Value(parent_list_id);
+            Ok(())
+        }
+    }
+}
+
+async fn resolve_patch_due_text(
+    db: &mut PgConnection,
+    user: &UserModel,
+    input: &mut PatchInput,
+) -> Result<(), APIResponse> {
+    let (Patch::Value(due_text), Patch::Missing) = (&input.due_text, &input.due_at) else {
+        return Ok(());
+    };
+    let Some(due_date) = resolve_due_text(db, user, due_text).await? else {
+        return Err(bad_request("Couldn't understand the due date."));
+    };
+    input.due_at = Patch::Value(format_due_at(due_date.due_at)?);
+    if let (Patch::Missing, Some(rule)) = (&input.recurrence, due_date.recurrence) {
+        input.recurrence = Patch::Value(rule.to_string());
+    }
+    Ok(())
+}
+
+/// Ranks a task that is moved next to a sibling or to another list or
+/// parent, the latter putting it after its new siblings.
+///
+/// Top level tasks are siblings within their list, subtasks within their parent.
+async fn place_patched_task(
+    db: &mut PgConnection,
+    user: &UserModel,
+    id: Uuid,
+    input: &mut PatchInput,
+) -> Result<(), APIResponse> {
+    let placement = Placement::from_input(input.move_before, input.move_after)?;
+    let current = sqlx::query!(
+        "SELECT tasks.list_id, tasks.parent_id FROM tasks
+            INNER JOIN lists ON lists.id = tasks.list_id
+            WHERE tasks.id = $1 AND lists.user_id = $2 AND tasks.deleted_at IS NULL",
+        id,
+        user.id
+    )
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch task from database.")?;
+    let Some(current) = current else {
+        return Ok(());
+    };
+    let list_id = match &input.list_id {
+        Patch::Value(list_id) => *list_id,
+        _ => current.list_id,
+    };
+    let parent_id = match &input.parent_id {
+        Patch::Missing => current.parent_id,
+        Patch::Null => None,
+        Patch::Value(parent_id) => Some(*parent_id),
+    };
+    if placement.is_none() && list_id == current.list_id && parent_id == current.parent_id {
+        return Ok(());
+    }
+
+    let siblings: Vec<_> = sqlx::query!(
+        "SELECT tasks.id, tasks.rank FROM tasks
+            INNER JOIN lists ON lists.id = tasks.list_id
+            WHERE lists.user_id = $1
+                AND tasks.id <> $2
+                AND tasks.deleted_at IS NULL
+                AND tasks.parent_id IS NOT DISTINCT FROM $3::UUID
+                AND ($3::UUID IS NOT NULL OR tasks.list_id = $4)
+            ORDER BY tasks.rank, tasks.id",
+        user.id,
+        id,
+        parent_id,
+        list_id
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch tasks from database.")?
+    .into_iter()
+    .map(|row| (row.id, row.rank))
+    .collect();
+    input.rank = Patch::Value(rank_among(&siblings, placement)?);
+    Ok(())
+}
+
+/// Fails with not found unless the task exists, isn't deleted and
+/// belongs to the user.
+pub async fn check_task_owner(
+    db: &mut PgConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> Result<(), APIResponse> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM tasks
+                INNER JOIN lists ON lists.id = tasks.list_id
+            WHERE tasks.id = $1 AND lists.user_id = $2 AND tasks.deleted_at IS NULL
+        ) AS "exists!""#,
+        task_id,
+        user_id
+    )
+    .fetch_one(db)
+    .await
+    .map_internal_server_error("Failed to fetch task from database.")?;
+    if !exists {
+        return Err(not_found("Task not found."));
+    }
+    Ok(())
+}
+
+pub fn format_due_at(due_at: PrimitiveDateTime) -> Result<String, APIResponse> {
+    due_at
+        .assume_utc()
+        .format(&Iso8601::DEFAULT)
+        .map_internal_server_error("Failed to format due date.")
+}
+
+async fn apply_patch(
+    db: &mut PgConnection,
+    user: &UserModel,
+    before: &Value,
+    after: &Value,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    let mut changes = move_subtasks(db, before, after).await?;
+    changes.extend(advance_recurrence(db, user, before, after).await?);
+    Ok(changes)
+}
+
+fn parse_uuid(value: &Value) -> Result<Uuid, APIResponse> {
+    value
+        .as_str()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| internal_server_error("Failed to parse task."))
+}
+
+/// Carries the whole subtree of a task moved to another list along with it.
+async fn move_subtasks(
+    db: &mut PgConnection,
+    before: &Value,
+    after: &Value,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    if before["list_id"] == after["list_id"] {
+        return Ok(vec![]);
+    }
+    let id = parse_uuid(&after["id"])?;
+    let list_id = parse_uuid(&after["list_id"])?;
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id FROM tasks WHERE parent_id = $1
+            UNION
+            SELECT tasks.id FROM tasks
+                INNER JOIN subtree ON tasks.parent_id = subtree.id
+        ), before AS (
+            SELECT id, to_jsonb(tasks.*) AS row FROM tasks
+                WHERE id IN (SELECT id FROM subtree)
+        )
+        UPDATE tasks SET list_id = $2
+            FROM before WHERE tasks.id = before.id
+            RETURNING before.row AS "before!", to_jsonb(tasks.*) AS "after!""#,
+        id,
+        list_id
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to move subtasks.")?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ActionChange {
+            table: String::from("tasks"),
+            before: Some(row.before),
+            after: Some(row.after),
+        })
+        .collect())
+}
+
+/// Moves a recurring task on to its next occurrence when it gets completed.
+///
+/// The task is either reopened with its next due date, or left completed
+/// while a fresh copy of it takes its place. Subtasks are reset either way.
+async fn advance_recurrence(
+    db: &mut PgConnection,
+    user: &UserModel,
+    before: &Value,
+    after: &Value,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    if before["completed"] == Value::Bool(true) || after["completed"] != Value::Bool(true) {
+        return Ok(vec![]);
+    }
+    let id = parse_uuid(&after["id"])?;
+    let task = sqlx::query_as!(TaskModel, "SELECT * FROM tasks WHERE id = $1", id)
+        .fetch_one(&mut *db)
+        .await
+        .map_internal_server_error("Failed to fetch task from database.")?;
+    let Some(rule) = task
+        .recurrence
+        .as_ref()
+        .and_then(|rule| rule.parse::<RRule>().ok())
+    else {
+        return Ok(vec![]);
+    };
+
+    // Occurrences follow the user's wall clock, not UTC
+    let due_at = to_local(db, task.due_at, &user.timezone).await?;
+    let dtstart = if task.recurrence_from_completion {
+        let now = local_now(db, &user.timezone).await?;
+        now.date().with_time(due_at.time())
+    } else {
+        due_at
+    };
+    let Some(next_due_at) = rule.next_after(dtstart) else {
+        return Ok(vec![]);
+    };
+    let next_due_at = to_utc(db, next_due_at, &user.timezone).await?;
+    let next_rule = rule.advanced().to_string();
+
+    if task.recurrence_keep_history {
+        spawn_next_occurrence(db, &task, next_due_at, &next_rule).await
+    } else {
+        reopen_next_occurrence(db, &task, next_due_at, &next_rule).await
+    }
+}
+
+async fn reopen_next_occurrence(
+    db: &mut PgConnection,
+    task: &TaskModel,
+    next_due_at: PrimitiveDateTime,
+    next_rule: &str,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id FROM tasks WHERE id = $1
+            UNION
+            SELECT tasks.id FROM tasks
+                INNER JOIN subtree ON tasks.parent_id = subtree.id
+                WHERE tasks.deleted_at IS NULL
+        ), before AS (
+            SELECT id, to_jsonb(tasks.*) AS row FROM tasks
+                WHERE id IN (SELECT id FROM subtree) AND (completed OR id = $1)
+        )
+        UPDATE tasks SET
+            completed = FALSE,
+            due_at = tasks.due_at + ($2::TIMESTAMP - $3::TIMESTAMP),
+            recurrence = CASE WHEN tasks.id = $1 THEN $4 ELSE tasks.recurrence END
+            FROM before WHERE tasks.id = before.id
+            RETURNING before.row AS "before!", to_jsonb(tasks.*) AS "after!""#,
+        task.id,
+        next_due_at,
+        task.due_at,
+        next_rule
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to advance recurring task.")?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ActionChange {
+            table: String::from("tasks"),
+            before: Some(row.before),
+            after: Some(row.after),
+        })
+        .collect())
+}
+
+async fn spawn_next_occurrence(
+    db: &mut PgConnection,
+    task: &TaskModel,
+    next_due_at: PrimitiveDateTime,
+    next_rule: &str,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    // The completed task stays behind as history, so it must not recur again
+    let history = sqlx::query!(
+        r#"
+        WITH before AS (SELECT to_jsonb(tasks.*) AS row FROM tasks WHERE id = $1)
+        UPDATE tasks SET recurrence = NULL FROM before WHERE id = $1
+            RETURNING before.row AS "before!", to_jsonb(tasks.*) AS "after!""#,
+        task.id
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to advance recurring task.")?;
+
+    // Parents are returned before their children so undoing
+    // removes the copies from the bottom up
+    let created = sqlx::query!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id, 0 AS depth, ARRAY[id] AS path FROM tasks WHERE id = $1
+            UNION ALL
+            SELECT tasks.id, subtree.depth + 1, subtree.path || tasks.id FROM tasks
+                INNER JOIN subtree ON tasks.parent_id = subtree.id
+                WHERE tasks.deleted_at IS NULL AND NOT tasks.id = ANY(subtree.path)
+        ), mapping AS MATERIALIZED (
+            SELECT id AS old_id, uuid_generate_v4() AS new_id, depth FROM subtree
+        ), new_tasks AS (
+            INSERT INTO tasks (
+                id, parent_id, list_id, due_at, due_text, completed, title, description,
+                recurrence, recurrence_from_completion, recurrence_keep_history, priority, rank
+            )
+            SELECT
+                mapping.new_id,
+                COALESCE(parent_mapping.new_id, tasks.parent_id),
+                tasks.list_id,
+                tasks.due_at + ($2::TIMESTAMP - $3::TIMESTAMP),
+                tasks.due_text,
+                FALSE,
+                tasks.title,
+                tasks.description,
+                CASE WHEN tasks.id = $1 THEN $4 ELSE tasks.recurrence END,
+                tasks.recurrence_from_completion,
+                tasks.recurrence_keep_history,
+                tasks.priority,
+                tasks.rank
+                FROM tasks
+                INNER JOIN mapping ON mapping.old_id = tasks.id
+                LEFT JOIN mapping parent_mapping ON parent_mapping.old_id = tasks.parent_id
+            RETURNING tasks.id, to_jsonb(tasks.*) AS row
+        ), new_labels AS (
+            INSERT INTO task_labels (task_id, label_id)
+            SELECT mapping.new_id, task_labels.label_id FROM task_labels
+                INNER JOIN mapping ON mapping.old_id = task_labels.task_id
+            RETURNING to_jsonb(task_labels.*) AS row
+        )
+        SELECT 'tasks' AS "table!", new_tasks.row AS "row!", mapping.depth FROM new_tasks
+            INNER JOIN mapping ON mapping.new_id = new_tasks.id
+        UNION ALL
+        SELECT 'task_labels', new_labels.row, NULL FROM new_labels
+        ORDER BY depth NULLS LAST"#,
+        task.id,
+        next_due_at,
+        task.due_at,
+        next_rule
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to advance recurring task.")?;
+
+    let mut changes = vec![ActionChange {
+        table: String::from("tasks"),
+        before: Some(history.before),
+        after: Some(history.after),
+    }];
+    changes.extend(created.into_iter().map(|row| ActionChange {
+        table: row.table,
+        before: None,
+        after: Some(row.row),
+    }));
+    Ok(changes)
+}
+
+crate::api_soft_delete! {
+    model_table: "tasks"
 }
 
 #[post("/<id>/labels", data = "<input>", format = "application/json")]
@@ -191,7 +1134,7 @@ async fn post_label(
     let res = sqlx::query!(
         "SELECT tasks.id, lists.user_id FROM tasks 
             INNER JOIN lists ON tasks.list_id = lists.id
-            WHERE tasks.id = $1 AND lists.user_id = $2
+            WHERE tasks.id = $1 AND lists.user_id = $2 AND tasks.deleted_at IS NULL
         ",
         id,
         auth_user.id
@@ -203,15 +1146,35 @@ async fn post_label(
     if let None = res {
         return result_not_found("Task not found");
     }
+    check_label(&mut db, auth_user.id, input.id).await?;
 
-    sqlx::query!(
-        "INSERT INTO task_labels(task_id, label_id) VALUES ($1, $2)",
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to attach label in database.")?;
+    let created = sqlx::query_scalar!(
+        r#"INSERT INTO task_labels(task_id, label_id) VALUES ($1, $2)
+            RETURNING to_jsonb(task_labels.*) AS "row!""#,
         id,
         input.id
     )
-    .execute(&mut *db)
+    .fetch_one(&mut tx)
     .await
     .map_internal_server_error("Failed to attach label in database.")?;
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "attach_label",
+        vec![ActionChange {
+            table: String::from("task_labels"),
+            before: None,
+            after: Some(created),
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to attach label in database.")?;
 
     Ok(ok("Label attached successfully."))
 }
@@ -223,23 +1186,40 @@ async fn delete_label(
     id: Uuid,
     label_id: Uuid,
 ) -> APIResult {
-    sqlx::query!(
-        "DELETE FROM task_labels 
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to detach label in database.")?;
+    let deleted = sqlx::query_scalar!(
+        r#"DELETE FROM task_labels 
             WHERE label_id = $1 AND 
                 task_id = $2 AND
-                label_id IN (SELECT id FROM labels WHERE user_id = $3)",
+                label_id IN (SELECT id FROM labels WHERE user_id = $3)
+            RETURNING to_jsonb(task_labels.*) AS "row!""#,
         label_id,
         id,
         auth_user.id
     )
-    .execute(&mut *db)
+    .fetch_all(&mut tx)
     .await
     .map_internal_server_error("Failed to detach label in database.")?;
+    let changes = deleted
+        .into_iter()
+        .map(|row| ActionChange {
+            table: String::from("task_labels"),
+            before: Some(row),
+            after: None,
+        })
+        .collect();
+    record_action(&mut tx, auth_user.id, "detach_label", changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to detach label in database.")?;
 
     Ok(ok("Label detached successfully."))
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
 pub struct PatchInput {
     #[serde(default)]
     pub parent_id: Patch<Uuid>,
@@ -256,18 +1236,46 @@ pub struct PatchInput {
     #[serde(default)]
     pub description: Patch<String>,
     #[serde(default)]
+    #[validate(custom = "validate_patch_recurrence")]
+    pub recurrence: Patch<String>,
+    #[serde(default)]
+    pub recurrence_from_completion: Patch<bool>,
+    #[serde(default)]
+    pub recurrence_keep_history: Patch<bool>,
+    /// From 1 (highest) to 4 (lowest).
+    #[serde(default)]
+    #[validate(custom = "validate_patch_priority")]
+    pub priority: Patch<i16>,
+    #[serde(default)]
     pub label_ids: Patch<Vec<Uuid>>,
+    /// Moves the task right before or after one of its siblings.
+    #[serde(default)]
+    pub move_before: Option<Uuid>,
+    #[serde(default)]
+    pub move_after: Option<Uuid>,
+    /// Completes the task even though it is blocked.
+    #[serde(default)]
+    pub force: bool,
+    #[serde(skip_deserializing)]
+    pub rank: Patch<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct PostInput {
     pub parent_id: Option<Uuid>,
     pub list_id: Uuid,
-    pub due_at: String,
+    pub due_at: Option<String>,
     pub due_text: String,
     pub completed: Option<bool>,
     pub title: String,
     pub description: Option<String>,
+    #[validate(custom = "validate_recurrence")]
+    pub recurrence: Option<String>,
+    pub recurrence_from_completion: Option<bool>,
+    pub recurrence_keep_history: Option<bool>,
+    /// From 1 (highest) to 4 (lowest), 4 when missing.
+    #[validate(custom = "validate_priority")]
+    pub priority: Option<i16>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -283,28 +1291,145 @@ pub struct GetModel {
     pub due_at: PrimitiveDateTime,
     pub due_text: String,
     pub completed: bool,
+    #[serde(serialize_with = "option_primitive_date_iso_serialize")]
+    pub completed_at: Option<PrimitiveDateTime>,
+    /// Whether a task this one depends on is still open.
+    pub blocked: bool,
     pub title: String,
     pub description: Option<String>,
+    pub recurrence: Option<String>,
+    pub recurrence_from_completion: bool,
+    pub recurrence_keep_history: bool,
+    pub priority: i16,
+    pub rank: String,
     pub child_ids: Vec<Uuid>,
     pub label_ids: Vec<Uuid>,
 }
 
+pub use filter::TaskFilter;
+
+mod filter {
+    // The `FromForm` derive puts `#[allow(private_in_public)]` on the items
+    // it generates next to the struct, which newer compilers warn about
+    #![allow(renamed_and_removed_lints)]
+
+    use uuid::Uuid;
+
+    use super::{SortOrder, TaskSort};
+
+    /// Query parameters narrowing down and ordering `GET /tasks`.
+    #[derive(Debug, FromForm)]
+    pub struct TaskFilter<'r> {
+        pub list_id: Option<Uuid>,
+        pub label_id: Option<Uuid>,
+        pub completed: Option<bool>,
+        pub parent_id: Option<Uuid>,
+        /// Only tasks without a parent.
+        pub top_level: Option<bool>,
+        pub due_before: Option<&'r str>,
+        pub due_after: Option<&'r str>,
+        pub priority: Option<i16>,
+        pub sort: Option<TaskSort>,
+        pub order: Option<SortOrder>,
+        /// Comma separated fields to return.
+        pub fields: Option<&'r str>,
+    }
+}
+
+#[derive(Debug, Clone, Copy, FromFormField)]
+pub enum TaskSort {
+    /// The manual order, see [`crate::rank`].
+    #[field(value = "rank")]
+    Rank,
+    #[field(value = "due_at")]
+    DueAt,
+    #[field(value = "created_at")]
+    CreatedAt,
+    #[field(value = "title")]
+    Title,
+    /// Most important first, unless the order is reversed.
+    #[field(value = "priority")]
+    Priority,
+}
+
+impl TaskSort {
+    fn as_str(self) -> &'static str {
+        match self {
+            TaskSort::Rank => "rank",
+            TaskSort::DueAt => "due_at",
+            TaskSort::CreatedAt => "created_at",
+            TaskSort::Title => "title",
+            TaskSort::Priority => "priority",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, FromFormField)]
+pub enum SortOrder {
+    #[field(value = "asc")]
+    Asc,
+    #[field(value = "desc")]
+    Desc,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DueDateModel {
+    #[serde(serialize_with = "primitive_date_iso_serialize")]
+    pub due_at: PrimitiveDateTime,
+    pub recurrence: Option<String>,
+    pub timezone: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LabelPostInput {
     pub id: Uuid,
 }
 
 #[derive(Debug)]
-struct GetModelBuilder {
+pub struct GetModelBuilder {
     pub get_model: GetModel,
-    pub child_ids: HashSet<Uuid>,
+    pub child_ids: Vec<Uuid>,
     pub label_ids: HashSet<Uuid>,
 }
 
+impl From<TaskModel> for GetModel {
+    fn from(task: TaskModel) -> Self {
+        GetModel {
+            id: task.id,
+            parent_id: task.parent_id,
+            list_id: task.list_id,
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+            due_at: task.due_at,
+            due_text: task.due_text,
+            completed: task.completed,
+            completed_at: task.completed_at,
+            blocked: false,
+            title: task.title,
+            description: task.description,
+            recurrence: task.recurrence,
+            recurrence_from_completion: task.recurrence_from_completion,
+            recurrence_keep_history: task.recurrence_keep_history,
+            priority: task.priority,
+            rank: task.rank,
+            child_ids: vec![],
+            label_ids: vec![],
+        }
+    }
+}
+
 impl GetModelBuilder {
+    /// Children keep the order they are added in, once per child
+    /// even though the rows repeat them for every label.
+    pub fn add_child(&mut self, child_id: Uuid) {
+        if !self.child_ids.contains(&child_id) {
+            self.child_ids.push(child_id);
+        }
+    }
+
     pub fn build(self) -> GetModel {
         let mut get_model = self.get_model;
-        get_model.child_ids = self.child_ids.into_iter().collect();
+        get_model.child_ids = self.child_ids;
         get_model.label_ids = self.label_ids.into_iter().collect();
         get_model
     }
@@ -315,7 +1440,10 @@ pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
         "/tasks",
         routes![
             get_single,
+            get_tree,
             get_all,
+            get_due_date,
+            get_completed,
             post,
             patch,
             delete,
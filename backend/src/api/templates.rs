@@ -0,0 +1,522 @@
+use rocket::{http::Status, serde::json::Json, Build, Rocket};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Connection as _, PgConnection, Row};
+use std::collections::{HashMap, HashSet};
+use time::{macros::format_description, Date, Duration, PrimitiveDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        tasks::SUBTASK_LIST_MESSAGE,
+        utils::{
+            ownership::{check_list, check_task},
+            timezone::{local_now, to_utc_all},
+            Patch, PostResponse,
+        },
+    },
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{
+        bad_request, result_bad_request, result_not_found, APIResponse, APIResult, MapAPIResponse,
+    },
+};
+
+crate::api_get! {
+    model_table: "templates",
+    model_type: crate::models::template::TemplateModel,
+    query_single_where: "WHERE id = $1 AND user_id = $2",
+    query_all_where: "WHERE user_id = $1 ORDER BY title, id"
+}
+
+crate::api_patch! {
+    model_table: "templates",
+    input: PatchInput,
+    input_fields: { title }
+}
+
+crate::api_delete! {
+    model_table: "templates"
+}
+
+/// A saved task or list tree, stored flat with parents before their
+/// children so it can be inserted in a single batch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateContent {
+    /// Empty in task templates.
+    pub lists: Vec<TemplateList>,
+    pub tasks: Vec<TemplateTask>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateList {
+    /// Index of the parent in `lists`, missing for the root.
+    pub parent: Option<usize>,
+    pub title: String,
+    pub description: Option<String>,
+    pub color: String,
+    pub rank: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateTask {
+    /// Index of the parent in `tasks`, missing for root tasks.
+    pub parent: Option<usize>,
+    /// Index of the list in `lists`, missing in task templates.
+    pub list: Option<usize>,
+    pub title: String,
+    pub description: Option<String>,
+    /// Seconds from midnight of the anchor day, in local time. The anchor
+    /// is the day of the earliest due date when the template is saved.
+    pub due_offset: i64,
+    pub due_text: String,
+    pub recurrence: Option<String>,
+    pub recurrence_from_completion: bool,
+    pub recurrence_keep_history: bool,
+    pub priority: i16,
+    pub rank: String,
+    pub label_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostInput {
+    pub title: String,
+    pub task_id: Option<Uuid>,
+    pub list_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PatchInput {
+    #[serde(default)]
+    pub title: Patch<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstantiateInput {
+    /// The day due dates are computed from, today if missing.
+    pub anchor: Option<String>,
+    /// Where task templates go, inferred from `parent_id` if missing.
+    pub list_id: Option<Uuid>,
+    /// A parent task for task templates, a parent list for list templates.
+    pub parent_id: Option<Uuid>,
+}
+
+/// Saves a task with its subtasks, or a list with its sublists and
+/// their tasks, as a template.
+#[post("/", data = "<input>", format = "application/json")]
+async fn post(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    input: Json<PostInput>,
+) -> APIResult {
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to create template in database.")?;
+    let (kind, content) = match (input.task_id, input.list_id) {
+        (Some(task_id), None) => {
+            check_task(&mut tx, auth_user.id, task_id).await?;
+            let tasks = snapshot_tasks(&mut tx, &auth_user, Some(task_id), &[]).await?;
+            (
+                "task",
+                TemplateContent {
+                    lists: vec![],
+                    tasks,
+                },
+            )
+        }
+        (None, Some(list_id)) => {
+            check_list(&mut tx, auth_user.id, list_id).await?;
+            ("list", snapshot_list(&mut tx, &auth_user, list_id).await?)
+        }
+        _ => return result_bad_request("A template is made from either a task or a list."),
+    };
+    let content = serde_json::to_value(content)
+        .map_internal_server_error("Failed to convert template into json.")?;
+
+    let created = sqlx::query!(
+        r#"INSERT INTO templates (user_id, title, kind, content) VALUES ($1, $2, $3, $4)
+            RETURNING id, to_jsonb(templates.*) AS "row!""#,
+        auth_user.id,
+        input.title,
+        kind,
+        content
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to create template in database.")?;
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "create",
+        vec![ActionChange {
+            table: String::from("templates"),
+            before: None,
+            after: Some(created.row),
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to create template in database.")?;
+
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::to_value(PostResponse { id: created.id })
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+async fn snapshot_list(
+    db: &mut PgConnection,
+    user: &UserModel,
+    list_id: Uuid,
+) -> Result<TemplateContent, APIResponse> {
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id, 0 AS depth, ARRAY[id] AS path FROM lists WHERE id = $1 AND user_id = $2
+            UNION ALL
+            SELECT lists.id, subtree.depth + 1, subtree.path || lists.id FROM lists
+                INNER JOIN subtree ON lists.parent_id = subtree.id
+                WHERE lists.deleted_at IS NULL AND NOT lists.id = ANY(subtree.path)
+        )
+        SELECT
+            lists.id AS "id!",
+            lists.parent_id,
+            lists.title AS "title!",
+            lists.description,
+            lists.color AS "color!",
+            lists.rank AS "rank!"
+            FROM subtree
+            INNER JOIN lists ON lists.id = subtree.id
+        ORDER BY subtree.depth, lists.rank, lists.id"#,
+        list_id,
+        user.id
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch lists from database.")?;
+
+    let indices: HashMap<Uuid, usize> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| (row.id, index))
+        .collect();
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+    let lists = rows
+        .into_iter()
+        .map(|row| TemplateList {
+            parent: row
+                .parent_id
+                .and_then(|parent_id| indices.get(&parent_id).copied()),
+            title: row.title,
+            description: row.description,
+            color: row.color,
+            rank: row.rank,
+        })
+        .collect();
+    let tasks = snapshot_tasks(db, user, None, &ids).await?;
+
+    Ok(TemplateContent { lists, tasks })
+}
+
+/// Snapshots a task and its subtasks, or all the tasks of some lists.
+///
+/// `list` of the tasks is set to their position in `list_ids`, which is
+/// also their index in the template.
+async fn snapshot_tasks(
+    db: &mut PgConnection,
+    user: &UserModel,
+    task_id: Option<Uuid>,
+    list_ids: &[Uuid],
+) -> Result<Vec<TemplateTask>, APIResponse> {
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id, 0 AS depth, ARRAY[id] AS path FROM tasks
+                WHERE (id = $1 OR (parent_id IS NULL AND list_id = ANY($2)))
+                    AND deleted_at IS NULL
+            UNION ALL
+            SELECT tasks.id, subtree.depth + 1, subtree.path || tasks.id FROM tasks
+                INNER JOIN subtree ON tasks.parent_id = subtree.id
+                WHERE tasks.deleted_at IS NULL AND NOT tasks.id = ANY(subtree.path)
+        )
+        SELECT
+            tasks.id AS "id!",
+            tasks.parent_id,
+            tasks.list_id AS "list_id!",
+            tasks.title AS "title!",
+            tasks.description,
+            (tasks.due_at AT TIME ZONE 'UTC') AT TIME ZONE $3 AS "local_due!",
+            tasks.due_text AS "due_text!",
+            tasks.recurrence,
+            tasks.recurrence_from_completion AS "recurrence_from_completion!",
+            tasks.recurrence_keep_history AS "recurrence_keep_history!",
+            tasks.priority AS "priority!",
+            tasks.rank AS "rank!",
+            ARRAY(
+                SELECT task_labels.label_id FROM task_labels
+                    INNER JOIN labels ON labels.id = task_labels.label_id
+                WHERE task_labels.task_id = tasks.id AND labels.deleted_at IS NULL
+                ORDER BY labels.rank, labels.id
+            ) AS "label_ids!"
+            FROM subtree
+            INNER JOIN tasks ON tasks.id = subtree.id
+        ORDER BY subtree.depth, tasks.rank, tasks.id"#,
+        task_id,
+        list_ids,
+        user.timezone
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch tasks from database.")?;
+
+    let Some(anchor) = rows.iter().map(|row| row.local_due.date()).min() else {
+        return Ok(vec![]);
+    };
+    let anchor = anchor.midnight();
+    let indices: HashMap<Uuid, usize> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| (row.id, index))
+        .collect();
+    Ok(rows
+        .into_iter()
+        .map(|row| TemplateTask {
+            parent: row
+                .parent_id
+                .and_then(|parent_id| indices.get(&parent_id).copied()),
+            list: list_ids.iter().position(|list_id| *list_id == row.list_id),
+            title: row.title,
+            description: row.description,
+            due_offset: (row.local_due - anchor).whole_seconds(),
+            due_text: row.due_text,
+            recurrence: row.recurrence,
+            recurrence_from_completion: row.recurrence_from_completion,
+            recurrence_keep_history: row.recurrence_keep_history,
+            priority: row.priority,
+            rank: row.rank,
+            label_ids: row.label_ids,
+        })
+        .collect())
+}
+
+/// Creates a fresh copy of a template, with due dates moved so the
+/// earliest one falls on the anchor day.
+#[post("/<id>/instantiate", data = "<input>", format = "application/json")]
+async fn instantiate(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+    input: Json<InstantiateInput>,
+) -> APIResult {
+    let template = sqlx::query!(
+        "SELECT kind, content FROM templates WHERE id = $1 AND user_id = $2",
+        id,
+        auth_user.id
+    )
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch template from database.")?;
+    let Some(template) = template else {
+        return result_not_found("Template not found.");
+    };
+    let content: TemplateContent = serde_json::from_value(template.content)
+        .map_internal_server_error("Failed to read template.")?;
+    let list_ids: Vec<Uuid> = content.lists.iter().map(|_| Uuid::new_v4()).collect();
+    let task_ids: Vec<Uuid> = content.tasks.iter().map(|_| Uuid::new_v4()).collect();
+    let root_id = if template.kind == "list" {
+        list_ids.first()
+    } else {
+        task_ids.first()
+    };
+    let Some(&root_id) = root_id else {
+        return result_bad_request("The template is empty.");
+    };
+    let anchor = match &input.anchor {
+        Some(anchor) => Date::parse(anchor, format_description!("[year]-[month]-[day]"))
+            .map_err(|_| bad_request("Invalid anchor date."))?,
+        None => local_now(&mut db, &auth_user.timezone).await?.date(),
+    };
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to instantiate template in database.")?;
+    let target_list_id = if template.kind == "list" {
+        if let Some(parent_id) = input.parent_id {
+            check_list(&mut tx, auth_user.id, parent_id).await?;
+        }
+        None
+    } else {
+        Some(match (input.list_id, input.parent_id) {
+            (list_id, Some(parent_id)) => {
+                let parent_list_id = check_task(&mut tx, auth_user.id, parent_id).await?;
+                if list_id.is_some_and(|list_id| list_id != parent_list_id) {
+                    return result_bad_request(SUBTASK_LIST_MESSAGE);
+                }
+                parent_list_id
+            }
+            (Some(list_id), None) => {
+                check_list(&mut tx, auth_user.id, list_id).await?;
+                list_id
+            }
+            (None, None) => return result_bad_request("A list is required for task templates."),
+        })
+    };
+
+    let mut changes = vec![];
+    if !content.lists.is_empty() {
+        let mut rows = vec![];
+        for (index, list) in content.lists.into_iter().enumerate() {
+            let parent_id = match list.parent {
+                Some(parent) => Some(list_ids[parent]),
+                None => input.parent_id,
+            };
+            // The root goes after its new siblings, the rest keep their order
+            let rank = list.parent.map(|_| list.rank);
+            rows.push((
+                list_ids[index],
+                auth_user.id,
+                parent_id,
+                list.title,
+                list.description,
+                list.color,
+                rank,
+            ));
+        }
+        let query = crate::insert_query!(
+            "lists";
+            (id: 0, user_id: 1, parent_id: 2, title: 3, description: 4, color: 5, rank: 6);
+            rows;
+            "RETURNING to_jsonb(lists.*) AS row"
+        );
+        changes.extend(insert_changes(&mut tx, "lists", &query).await?);
+    }
+
+    let local_dues: Vec<PrimitiveDateTime> = content
+        .tasks
+        .iter()
+        .map(|task| {
+            anchor
+                .midnight()
+                .checked_add(Duration::seconds(task.due_offset))
+        })
+        .collect::<Option<_>>()
+        .ok_or_else(|| bad_request("The anchor date is out of range for this template."))?;
+    let dues = to_utc_all(&mut tx, &local_dues, &auth_user.timezone).await?;
+    let label_ids: Vec<Uuid> = content
+        .tasks
+        .iter()
+        .flat_map(|task| task.label_ids.iter().copied())
+        .collect();
+    let existing_labels: HashSet<Uuid> = sqlx::query_scalar!(
+        "SELECT id FROM labels WHERE id = ANY($1) AND user_id = $2 AND deleted_at IS NULL",
+        &label_ids,
+        auth_user.id
+    )
+    .fetch_all(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch labels from database.")?
+    .into_iter()
+    .collect();
+    let mut task_labels = vec![];
+    if !content.tasks.is_empty() {
+        let mut rows = vec![];
+        for (index, task) in content.tasks.into_iter().enumerate() {
+            let parent_id = match task.parent {
+                Some(parent) => Some(task_ids[parent]),
+                // Root tasks of list templates stay at the top of their list
+                None if task.list.is_some() => None,
+                None => input.parent_id,
+            };
+            let list_id = match task.list {
+                Some(list) => list_ids[list],
+                None => target_list_id.unwrap_or_default(),
+            };
+            // The root of a task template goes after its new siblings
+            let rank = (task.parent.is_some() || task.list.is_some()).then_some(task.rank);
+            task_labels.extend(
+                task.label_ids
+                    .into_iter()
+                    .filter(|label_id| existing_labels.contains(label_id))
+                    .map(|label_id| (task_ids[index], label_id)),
+            );
+            rows.push((
+                task_ids[index],
+                parent_id,
+                list_id,
+                task.title,
+                task.description,
+                dues[index].to_string(),
+                task.due_text,
+                task.recurrence,
+                task.recurrence_from_completion,
+                task.recurrence_keep_history,
+                task.priority,
+                rank,
+            ));
+        }
+        let query = crate::insert_query!(
+            "tasks";
+            (
+                id: 0, parent_id: 1, list_id: 2, title: 3, description: 4, due_at: 5,
+                due_text: 6, recurrence: 7, recurrence_from_completion: 8,
+                recurrence_keep_history: 9, priority: 10, rank: 11
+            );
+            rows;
+            "RETURNING to_jsonb(tasks.*) AS row"
+        );
+        changes.extend(insert_changes(&mut tx, "tasks", &query).await?);
+    }
+    if !task_labels.is_empty() {
+        let query = crate::insert_query!(
+            "task_labels";
+            (task_id: 0, label_id: 1);
+            task_labels;
+            "RETURNING to_jsonb(task_labels.*) AS row"
+        );
+        changes.extend(insert_changes(&mut tx, "task_labels", &query).await?);
+    }
+
+    record_action(&mut tx, auth_user.id, "instantiate", changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to instantiate template in database.")?;
+
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::to_value(PostResponse { id: root_id })
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+pub async fn insert_changes(
+    db: &mut PgConnection,
+    table: &str,
+    query: &str,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    let rows: Vec<PgRow> = sqlx::query(query)
+        .fetch_all(&mut *db)
+        .await
+        .map_internal_server_error("Failed to instantiate template in database.")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| ActionChange {
+            table: String::from(table),
+            before: None,
+            after: Some(row.get("row")),
+        })
+        .collect())
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount(
+        "/templates",
+        routes![get_all, get_single, post, instantiate, patch, delete],
+    )
+}
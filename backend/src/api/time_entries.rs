@@ -0,0 +1,440 @@
+use rocket::{http::Status, serde::json::Json, Build, Rocket};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use sqlx::Connection as _;
+use time::{
+    format_description::well_known::Iso8601, Date, Duration, OffsetDateTime, PrimitiveDateTime,
+    Time, UtcOffset,
+};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        tasks::check_task_owner,
+        utils::{
+            serde::{option_primitive_date_iso_serialize, primitive_date_iso_serialize},
+            timezone::{local_now, to_utc},
+            GetAllResponse, PostResponse, GET_LIMIT,
+        },
+    },
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{
+        bad_request, conflict, ok, result_bad_request, result_not_found, APIResponse, APIResult,
+        MapAPIResponse,
+    },
+};
+
+/// The furthest back totals reach unless a range is given.
+const DEFAULT_TOTAL_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetModel {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    #[serde(serialize_with = "primitive_date_iso_serialize")]
+    pub started_at: PrimitiveDateTime,
+    /// Missing while the timer is running.
+    #[serde(serialize_with = "option_primitive_date_iso_serialize")]
+    pub ended_at: Option<PrimitiveDateTime>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostInput {
+    pub started_at: String,
+    pub ended_at: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TimerInput {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, FromFormField)]
+pub enum TotalGroup {
+    #[field(value = "task")]
+    Task,
+    #[field(value = "list")]
+    List,
+    /// Time on a task with several labels counts towards each of them.
+    #[field(value = "label")]
+    Label,
+}
+
+impl TotalGroup {
+    fn as_str(self) -> &'static str {
+        match self {
+            TotalGroup::Task => "task",
+            TotalGroup::List => "list",
+            TotalGroup::Label => "label",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotalModel {
+    pub id: Uuid,
+    pub title: String,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotalsResponse {
+    pub from: String,
+    pub to: String,
+    pub timezone: String,
+    pub items: Vec<TotalModel>,
+}
+
+fn parse_time(time: &str) -> Result<PrimitiveDateTime, APIResponse> {
+    let time = OffsetDateTime::parse(time, &Iso8601::DEFAULT)
+        .map_err(|_| bad_request("Invalid time."))?
+        .to_offset(UtcOffset::UTC);
+    Ok(PrimitiveDateTime::new(time.date(), time.time()))
+}
+
+/// Lists the time entries of a task, latest first.
+#[get("/<task_id>/time_entries?<limit>&<page>")]
+async fn get_all(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    limit: Option<u32>,
+    page: Option<u32>,
+) -> APIResult {
+    let limit = limit.unwrap_or(GET_LIMIT);
+    let page = page.unwrap_or(0);
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let items = sqlx::query_as!(
+        GetModel,
+        "SELECT id, task_id, started_at, ended_at, note FROM time_entries
+            WHERE task_id = $1 AND user_id = $2
+            ORDER BY started_at DESC, id
+            LIMIT $3 OFFSET $4",
+        task_id,
+        auth_user.id,
+        limit as i64,
+        (page * limit) as i64
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch time entries from database.")?;
+    let resp = GetAllResponse::<GetModel> { items, limit, page };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// Adds time spent on a task after the fact.
+#[post(
+    "/<task_id>/time_entries",
+    data = "<input>",
+    format = "application/json"
+)]
+async fn post(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    input: Json<PostInput>,
+) -> APIResult {
+    let started_at = parse_time(&input.started_at)?;
+    let ended_at = parse_time(&input.ended_at)?;
+    if ended_at < started_at {
+        return result_bad_request("A time entry can't end before it starts.");
+    }
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to create time entry in database.")?;
+    let created = sqlx::query!(
+        r#"INSERT INTO time_entries (user_id, task_id, started_at, ended_at, note)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, to_jsonb(time_entries.*) AS "row!""#,
+        auth_user.id,
+        task_id,
+        started_at,
+        ended_at,
+        input.note
+    )
+    .fetch_one(&mut tx)
+    .await
+    .map_internal_server_error("Failed to create time entry in database.")?;
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "create",
+        vec![ActionChange {
+            table: String::from("time_entries"),
+            before: None,
+            after: Some(created.row),
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to create time entry in database.")?;
+
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::to_value(PostResponse { id: created.id })
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// Starts timing a task, unless a timer is already running.
+#[post("/<task_id>/timer", data = "<input>", format = "application/json")]
+async fn start_timer(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    task_id: Uuid,
+    input: Option<Json<TimerInput>>,
+) -> APIResult {
+    let input = input.map(Json::into_inner).unwrap_or_default();
+    check_task_owner(&mut db, auth_user.id, task_id).await?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to start timer in database.")?;
+    let created = sqlx::query!(
+        r#"INSERT INTO time_entries (user_id, task_id, started_at, note)
+            VALUES ($1, $2, CURRENT_TIMESTAMP AT TIME ZONE 'UTC', $3)
+            ON CONFLICT (user_id) WHERE ended_at IS NULL DO NOTHING
+            RETURNING id, to_jsonb(time_entries.*) AS "row!""#,
+        auth_user.id,
+        task_id,
+        input.note
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to start timer in database.")?;
+    let Some(created) = created else {
+        return Err(conflict("A timer is already running, stop it first."));
+    };
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "start_timer",
+        vec![ActionChange {
+            table: String::from("time_entries"),
+            before: None,
+            after: Some(created.row),
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to start timer in database.")?;
+
+    Ok(APIResponse::new(
+        Status::Created,
+        serde_json::to_value(PostResponse { id: created.id })
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+/// The running timer of the user, whichever task it is on.
+#[get("/running")]
+async fn get_running(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>) -> APIResult {
+    let running = sqlx::query_as!(
+        GetModel,
+        "SELECT id, task_id, started_at, ended_at, note FROM time_entries
+            WHERE user_id = $1 AND ended_at IS NULL",
+        auth_user.id
+    )
+    .fetch_optional(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch time entry from database.")?;
+    let Some(running) = running else {
+        return result_not_found("No timer is running.");
+    };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(running)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[post("/stop")]
+async fn stop_timer(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>) -> APIResult {
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to stop timer in database.")?;
+    let stopped = sqlx::query!(
+        r#"
+        WITH before AS (
+            SELECT id, to_jsonb(time_entries.*) AS row FROM time_entries
+                WHERE user_id = $1 AND ended_at IS NULL
+                FOR UPDATE
+        )
+        UPDATE time_entries SET
+            ended_at = GREATEST(started_at, CURRENT_TIMESTAMP AT TIME ZONE 'UTC')
+            FROM before WHERE time_entries.id = before.id
+            RETURNING before.row AS "before!", to_jsonb(time_entries.*) AS "after!""#,
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to stop timer in database.")?;
+    let Some(stopped) = stopped else {
+        return result_not_found("No timer is running.");
+    };
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "stop_timer",
+        vec![ActionChange {
+            table: String::from("time_entries"),
+            before: Some(stopped.before),
+            after: Some(stopped.after),
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to stop timer in database.")?;
+
+    Ok(ok("Timer stopped successfully."))
+}
+
+#[delete("/<id>")]
+async fn delete(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>, id: Uuid) -> APIResult {
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to delete time entry from database.")?;
+    let deleted = sqlx::query_scalar!(
+        r#"DELETE FROM time_entries WHERE id = $1 AND user_id = $2
+            RETURNING to_jsonb(time_entries.*) AS "row!""#,
+        id,
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to delete time entry from database.")?;
+    let Some(deleted) = deleted else {
+        return result_not_found("Time entry not found.");
+    };
+    record_action(
+        &mut tx,
+        auth_user.id,
+        "delete",
+        vec![ActionChange {
+            table: String::from("time_entries"),
+            before: Some(deleted),
+            after: None,
+        }],
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to delete time entry from database.")?;
+
+    Ok(ok("Time entry deleted successfully."))
+}
+
+/// Time spent per task, list or label between two days in the user's
+/// timezone, both included. Entries crossing the range only count the part
+/// within it, and a running timer counts up to now.
+#[get("/totals?<from>&<to>&<group_by>")]
+async fn get_totals(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    from: Option<Date>,
+    to: Option<Date>,
+    group_by: Option<TotalGroup>,
+) -> APIResult {
+    let today = local_now(&mut db, &auth_user.timezone).await?.date();
+    let to = to.unwrap_or(today);
+    let from = match from {
+        Some(from) => from,
+        None => match to.checked_sub(Duration::days(DEFAULT_TOTAL_DAYS - 1)) {
+            Some(from) => from,
+            None => return result_bad_request("The range is out of bounds."),
+        },
+    };
+    if to < from {
+        return result_bad_request("The range has to end after it starts.");
+    }
+    let Some(day_after) = to.checked_add(Duration::days(1)) else {
+        return result_bad_request("The range is out of bounds.");
+    };
+    let start = to_utc(&mut db, from.with_time(Time::MIDNIGHT), &auth_user.timezone).await?;
+    let end = to_utc(
+        &mut db,
+        day_after.with_time(Time::MIDNIGHT),
+        &auth_user.timezone,
+    )
+    .await?;
+
+    let items = sqlx::query_as!(
+        TotalModel,
+        r#"
+        WITH entries AS (
+            SELECT
+                task_id,
+                EXTRACT(EPOCH FROM
+                    LEAST(COALESCE(ended_at, CURRENT_TIMESTAMP AT TIME ZONE 'UTC'), $3)
+                    - GREATEST(started_at, $2)
+                ) AS seconds
+                FROM time_entries
+            WHERE user_id = $1
+                AND started_at < $3
+                AND COALESCE(ended_at, CURRENT_TIMESTAMP AT TIME ZONE 'UTC') > $2
+        )
+        SELECT
+            CASE $4 WHEN 'task' THEN tasks.id WHEN 'list' THEN lists.id ELSE labels.id END
+                AS "id!",
+            CASE $4 WHEN 'task' THEN tasks.title WHEN 'list' THEN lists.title ELSE labels.title END
+                AS "title!",
+            SUM(entries.seconds)::BIGINT AS "seconds!"
+            FROM entries
+            INNER JOIN tasks ON tasks.id = entries.task_id AND tasks.deleted_at IS NULL
+            INNER JOIN lists ON lists.id = tasks.list_id AND lists.deleted_at IS NULL
+            LEFT JOIN task_labels ON $4 = 'label' AND task_labels.task_id = tasks.id
+            LEFT JOIN labels ON labels.id = task_labels.label_id AND labels.deleted_at IS NULL
+        WHERE $4 <> 'label' OR labels.id IS NOT NULL
+        GROUP BY 1, 2
+        ORDER BY 3 DESC, 2"#,
+        auth_user.id,
+        start,
+        end,
+        group_by.unwrap_or(TotalGroup::Task).as_str()
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to fetch time entries from database.")?;
+
+    let resp = TotalsResponse {
+        from: from.to_string(),
+        to: to.to_string(),
+        timezone: auth_user.timezone.clone(),
+        items,
+    };
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket
+        .mount("/tasks", routes![get_all, post, start_timer])
+        .mount(
+            "/time_entries",
+            routes![get_running, stop_timer, delete, get_totals],
+        )
+}
@@ -0,0 +1,417 @@
+use rocket::{http::Status, Build, Rocket, State};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Connection as _, PgConnection};
+use time::PrimitiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        actions::{record_action, ActionChange},
+        utils::{serde::primitive_date_iso_serialize, GetAllResponse, GET_LIMIT},
+    },
+    config::AppConfig,
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{
+        internal_server_error, ok, result_bad_request, result_not_found, APIResponse, APIResult,
+        MapAPIResponse,
+    },
+};
+
+#[get("/?<limit>&<page>")]
+async fn get_all(
+    auth_user: Auth<UserModel>,
+    config: &State<AppConfig>,
+    mut db: Connection<BackendDb>,
+    limit: Option<u32>,
+    page: Option<u32>,
+) -> APIResult {
+    let limit = limit.unwrap_or(GET_LIMIT);
+    let page = page.unwrap_or(0);
+
+    purge_expired(&mut db, config, Some(auth_user.id)).await?;
+
+    // Only the root of each deleted subtree is listed, since
+    // restoring the root restores everything deleted along with it.
+    let rows = sqlx::query!(
+        r#"
+        SELECT * FROM (
+            SELECT
+                lists.id AS "id!",
+                'list' AS "item_type!",
+                lists.title AS "title!",
+                lists.deleted_at AS "deleted_at!"
+                FROM lists
+                LEFT JOIN lists parent_lists
+                    ON parent_lists.id = lists.parent_id
+            WHERE lists.user_id = $1
+                AND lists.deleted_at IS NOT NULL
+                AND parent_lists.deleted_at IS DISTINCT FROM lists.deleted_at
+            UNION ALL
+            SELECT tasks.id, 'task', tasks.title, tasks.deleted_at FROM tasks
+                INNER JOIN lists
+                    ON lists.id = tasks.list_id
+                LEFT JOIN tasks parent_tasks
+                    ON parent_tasks.id = tasks.parent_id
+            WHERE lists.user_id = $1
+                AND tasks.deleted_at IS NOT NULL
+                AND parent_tasks.deleted_at IS DISTINCT FROM tasks.deleted_at
+                AND lists.deleted_at IS DISTINCT FROM tasks.deleted_at
+            UNION ALL
+            SELECT id, 'label', title, deleted_at FROM labels
+            WHERE user_id = $1 AND deleted_at IS NOT NULL
+        ) trash
+        ORDER BY "deleted_at!" DESC LIMIT $2 OFFSET $3"#,
+        auth_user.id,
+        limit as i64,
+        (page * limit) as i64
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Error fetching trash")?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| GetModel {
+            id: row.id,
+            item_type: row.item_type,
+            title: row.title,
+            deleted_at: row.deleted_at,
+            purge_at: row.deleted_at + config.trash_retention,
+        })
+        .collect();
+    let resp = GetAllResponse::<GetModel> { items, limit, page };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[post("/<id>/restore")]
+async fn restore(
+    auth_user: Auth<UserModel>,
+    config: &State<AppConfig>,
+    mut db: Connection<BackendDb>,
+    id: Uuid,
+) -> APIResult {
+    let mut tx = db
+        .begin()
+        .await
+        .map_internal_server_error("Failed to start transaction.")?;
+    purge_expired(&mut tx, config, Some(auth_user.id)).await?;
+
+    let list = sqlx::query!(
+        r#"
+        SELECT
+            lists.deleted_at AS "deleted_at!",
+            parent_lists.deleted_at AS "parent_deleted_at?"
+            FROM lists
+            LEFT JOIN lists parent_lists
+                ON parent_lists.id = lists.parent_id
+        WHERE lists.id = $1 AND lists.user_id = $2 AND lists.deleted_at IS NOT NULL"#,
+        id,
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch trash from database.")?;
+    let task = sqlx::query!(
+        r#"
+        SELECT
+            tasks.deleted_at AS "deleted_at!",
+            lists.deleted_at AS "list_deleted_at?",
+            parent_tasks.deleted_at AS "parent_deleted_at?"
+            FROM tasks
+            INNER JOIN lists
+                ON lists.id = tasks.list_id
+            LEFT JOIN tasks parent_tasks
+                ON parent_tasks.id = tasks.parent_id
+        WHERE tasks.id = $1 AND lists.user_id = $2 AND tasks.deleted_at IS NOT NULL"#,
+        id,
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch trash from database.")?;
+
+    let label_deleted_at = sqlx::query_scalar!(
+        "SELECT deleted_at FROM labels WHERE id = $1 AND user_id = $2",
+        id,
+        auth_user.id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_internal_server_error("Failed to fetch trash from database.")?
+    .flatten();
+
+    let rows: Vec<(String, Value, Value)> = if let Some(list) = list {
+        if list.parent_deleted_at.is_some() {
+            return result_bad_request("Parent list is in the trash. Restore it first.");
+        }
+        sqlx::query!(
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id FROM lists WHERE id = $1
+                UNION
+                SELECT lists.id FROM lists
+                    INNER JOIN subtree
+                    ON lists.parent_id = subtree.id
+                WHERE lists.deleted_at = $2
+            ), restored_lists AS (
+                UPDATE lists SET deleted_at = NULL
+                WHERE id IN (SELECT id FROM subtree)
+                RETURNING to_jsonb(lists.*) AS row
+            ), restored_tasks AS (
+                UPDATE tasks SET deleted_at = NULL
+                WHERE list_id IN (SELECT id FROM subtree) AND deleted_at = $2
+                RETURNING to_jsonb(tasks.*) AS row
+            )
+            SELECT
+                'lists' AS "table!",
+                jsonb_set(row, '{deleted_at}', to_jsonb($2::TIMESTAMP)) AS "before!",
+                row AS "after!"
+                FROM restored_lists
+            UNION ALL
+            SELECT 'tasks', jsonb_set(row, '{deleted_at}', to_jsonb($2::TIMESTAMP)), row
+                FROM restored_tasks"#,
+            id,
+            list.deleted_at
+        )
+        .fetch_all(&mut tx)
+        .await
+        .map_internal_server_error("Failed to restore list in database.")?
+        .into_iter()
+        .map(|row| (row.table, row.before, row.after))
+        .collect()
+    } else if let Some(task) = task {
+        if task.list_deleted_at.is_some() {
+            return result_bad_request("List is in the trash. Restore it first.");
+        }
+        if task.parent_deleted_at.is_some() {
+            return result_bad_request("Parent task is in the trash. Restore it first.");
+        }
+        sqlx::query!(
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id FROM tasks WHERE id = $1
+                UNION
+                SELECT tasks.id FROM tasks
+                    INNER JOIN subtree
+                    ON tasks.parent_id = subtree.id
+                WHERE tasks.deleted_at = $2
+            )
+            UPDATE tasks SET deleted_at = NULL
+            WHERE id IN (SELECT id FROM subtree)
+            RETURNING
+                jsonb_set(to_jsonb(tasks.*), '{deleted_at}', to_jsonb($2::TIMESTAMP)) AS "before!",
+                to_jsonb(tasks.*) AS "after!""#,
+            id,
+            task.deleted_at
+        )
+        .fetch_all(&mut tx)
+        .await
+        .map_internal_server_error("Failed to restore task in database.")?
+        .into_iter()
+        .map(|row| (String::from("tasks"), row.before, row.after))
+        .collect()
+    } else {
+        let label = sqlx::query!(
+            r#"
+            UPDATE labels SET deleted_at = NULL
+                WHERE id = $1 AND user_id = $2 AND deleted_at = $3
+                RETURNING
+                    jsonb_set(to_jsonb(labels.*), '{deleted_at}', to_jsonb($3::TIMESTAMP)) AS "before!",
+                    to_jsonb(labels.*) AS "after!""#,
+            id,
+            auth_user.id,
+            label_deleted_at
+        )
+        .fetch_optional(&mut tx)
+        .await
+        .map_internal_server_error("Failed to restore label in database.")?;
+        let Some(label) = label else {
+            return result_not_found("Item not found.");
+        };
+        vec![(String::from("labels"), label.before, label.after)]
+    };
+    let changes = rows
+        .into_iter()
+        .map(|(table, before, after)| ActionChange {
+            table,
+            before: Some(before),
+            after: Some(after),
+        })
+        .collect();
+
+    record_action(&mut tx, auth_user.id, "restore", changes).await?;
+    tx.commit()
+        .await
+        .map_internal_server_error("Failed to commit transaction.")?;
+    Ok(ok("Restore successful."))
+}
+
+/// Moves an item, along with its descendants, into the trash.
+///
+/// Every item deleted together shares the same `deleted_at`, which is
+/// how restoring the root knows which descendants to bring back.
+///
+/// Returns the changed rows, which is empty if the item does
+/// not exist or does not belong to the user.
+pub async fn soft_delete(
+    db: &mut PgConnection,
+    model_table: &str,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<ActionChange>, APIResponse> {
+    let rows: Vec<(String, Value)> = match model_table {
+        "lists" => sqlx::query!(
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id FROM lists
+                WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+                UNION
+                SELECT lists.id FROM lists
+                    INNER JOIN subtree
+                    ON lists.parent_id = subtree.id
+                WHERE lists.deleted_at IS NULL
+            ), deleted_lists AS (
+                UPDATE lists SET deleted_at = CURRENT_TIMESTAMP
+                WHERE id IN (SELECT id FROM subtree)
+                RETURNING to_jsonb(lists.*) AS row
+            ), deleted_tasks AS (
+                UPDATE tasks SET deleted_at = CURRENT_TIMESTAMP
+                WHERE list_id IN (SELECT id FROM subtree) AND deleted_at IS NULL
+                RETURNING to_jsonb(tasks.*) AS row
+            )
+            SELECT 'lists' AS "table!", row AS "row!" FROM deleted_lists
+            UNION ALL
+            SELECT 'tasks', row FROM deleted_tasks"#,
+            id,
+            user_id
+        )
+        .fetch_all(&mut *db)
+        .await
+        .map_internal_server_error("Failed to delete in database.")?
+        .into_iter()
+        .map(|row| (row.table, row.row))
+        .collect(),
+        "tasks" => sqlx::query!(
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT tasks.id FROM tasks
+                    INNER JOIN lists
+                    ON lists.id = tasks.list_id
+                WHERE tasks.id = $1 AND lists.user_id = $2 AND tasks.deleted_at IS NULL
+                UNION
+                SELECT tasks.id FROM tasks
+                    INNER JOIN subtree
+                    ON tasks.parent_id = subtree.id
+                WHERE tasks.deleted_at IS NULL
+            )
+            UPDATE tasks SET deleted_at = CURRENT_TIMESTAMP
+            WHERE id IN (SELECT id FROM subtree)
+            RETURNING to_jsonb(tasks.*) AS "row!""#,
+            id,
+            user_id
+        )
+        .fetch_all(&mut *db)
+        .await
+        .map_internal_server_error("Failed to delete in database.")?
+        .into_iter()
+        .map(|row| (String::from("tasks"), row.row))
+        .collect(),
+        "labels" => sqlx::query!(
+            r#"
+            UPDATE labels SET deleted_at = CURRENT_TIMESTAMP
+                WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+                RETURNING to_jsonb(labels.*) AS "row!""#,
+            id,
+            user_id
+        )
+        .fetch_all(&mut *db)
+        .await
+        .map_internal_server_error("Failed to delete in database.")?
+        .into_iter()
+        .map(|row| (String::from("labels"), row.row))
+        .collect(),
+        _ => return Err(internal_server_error("Item does not support soft deletes.")),
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|(table, after)| {
+            let mut before = after.clone();
+            before["deleted_at"] = Value::Null;
+            ActionChange {
+                table,
+                before: Some(before),
+                after: Some(after),
+            }
+        })
+        .collect())
+}
+
+/// Permanently deletes the user's trashed items that
+/// have outlived the configured trash retention, or
+/// those of every user when `user_id` is `None`.
+pub async fn purge_expired(
+    db: &mut PgConnection,
+    config: &AppConfig,
+    user_id: Option<Uuid>,
+) -> Result<(), APIResponse> {
+    let retention_seconds = config.trash_retention.whole_seconds();
+
+    // Tasks go first, since `tasks.list_id` does not cascade.
+    sqlx::query!(
+        "DELETE FROM tasks
+            WHERE deleted_at < CURRENT_TIMESTAMP - $2 * INTERVAL '1 second' AND
+                list_id IN (SELECT id FROM lists WHERE $1::UUID IS NULL OR user_id = $1)",
+        user_id,
+        retention_seconds as f64
+    )
+    .execute(&mut *db)
+    .await
+    .map_internal_server_error("Failed to purge trash.")?;
+    sqlx::query!(
+        "DELETE FROM lists
+            WHERE ($1::UUID IS NULL OR user_id = $1) AND
+                deleted_at < CURRENT_TIMESTAMP - $2 * INTERVAL '1 second'",
+        user_id,
+        retention_seconds as f64
+    )
+    .execute(&mut *db)
+    .await
+    .map_internal_server_error("Failed to purge trash.")?;
+    sqlx::query!(
+        "DELETE FROM labels
+            WHERE ($1::UUID IS NULL OR user_id = $1) AND
+                deleted_at < CURRENT_TIMESTAMP - $2 * INTERVAL '1 second'",
+        user_id,
+        retention_seconds as f64
+    )
+    .execute(&mut *db)
+    .await
+    .map_internal_server_error("Failed to purge trash.")?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetModel {
+    pub id: Uuid,
+    /// Either `list`, `task` or `label`.
+    pub item_type: String,
+    pub title: String,
+    #[serde(serialize_with = "primitive_date_iso_serialize")]
+    pub deleted_at: PrimitiveDateTime,
+    #[serde(serialize_with = "primitive_date_iso_serialize")]
+    pub purge_at: PrimitiveDateTime,
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/trash", routes![get_all, restore])
+}
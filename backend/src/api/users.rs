@@ -1,14 +1,18 @@
 use ipnetwork::IpNetwork;
-use rocket::{Build, Rocket};
+use rocket::{serde::json::Json, Build, Rocket};
 use rocket_db_pools::Connection;
+use rocket_validation::Validated;
 use serde::{Deserialize, Serialize};
 use time::PrimitiveDateTime;
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
+    api::utils::{timezone::is_valid_timezone, Patch},
     database::BackendDb,
     guards::auth::Auth,
     models::{session::SessionModel, user::UserModel},
+    responses::{bad_request, internal_server_error, ok, result_bad_request, APIResult},
 };
 
 #[get("/me")]
@@ -17,12 +21,54 @@ pub async fn get_me(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>) {
     // let user = sqlx::query_as!(UserModel, "SELECT * FROM users WHERE ", auth_user.id)
 }
 
+#[patch("/me", data = "<input>", format = "application/json")]
+pub async fn patch_me(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    input: Validated<Json<PatchInput>>,
+) -> APIResult {
+    let input = input.into_deep_inner();
+    if let Patch::Value(ref timezone) = input.timezone {
+        if !is_valid_timezone(&mut db, timezone).await? {
+            return result_bad_request("Unknown timezone.");
+        }
+    }
+
+    let update_str = crate::update_query! {
+        "users";
+        username: input.username,
+        timezone: input.timezone;
+        "WHERE id = $1"
+    };
+    let Some(update_str) = update_str else {
+        return result_bad_request("Empty patch request.");
+    };
+    sqlx::query(&update_str)
+        .bind(auth_user.id)
+        .execute(&mut *db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(_) => bad_request("Invalid patch request."),
+            _ => internal_server_error("Failed to patch in database."),
+        })?;
+    Ok(ok("Patch successful."))
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PatchInput {
+    #[serde(default)]
+    pub username: Patch<String>,
+    #[serde(default)]
+    pub timezone: Patch<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetModel {
     pub id: Uuid,
     pub username: String,
     pub created_at: PrimitiveDateTime,
     pub updated_at: PrimitiveDateTime,
+    pub timezone: String,
     pub discord_login: Option<GetDiscordUserLoginModel>,
     pub email_login: Option<GetEmailUserLoginModel>,
     pub sessions: Vec<GetSessionModel>,
@@ -48,5 +94,5 @@ pub struct GetEmailUserLoginModel {
 }
 
 pub fn mount_rocket(mut rocket: Rocket<Build>) -> Rocket<Build> {
-    rocket.mount("/users", routes![get_me])
+    rocket.mount("/users", routes![get_me, patch_me])
 }
@@ -111,6 +111,7 @@ macro_rules! api_post {
         input: $input:path,
         input_fields: { $($input_field:ident),+ },
         user_id: $user_id:expr
+        $(, before_insert: $before_insert:path)?
     ) => {
         #[post("/", data = "<input>", format = "application/json")]
         async fn post(
@@ -118,17 +119,28 @@ macro_rules! api_post {
             mut db: rocket_db_pools::Connection<crate::database::BackendDb>,
             input: rocket_validation::Validated<rocket::serde::json::Json<$input>>,
         ) -> crate::responses::APIResult {
+            use once_cell::sync::Lazy;
             use rocket::http::Status;
-            use sqlx::Row;
+            use sqlx::{Connection, Row};
             use sqlx::postgres::PgRow;
 
             use crate::{
                 responses::{APIResponse, MapAPIResponse},
-                api::utils::PostResponse,
+                api::{actions::{record_action, ActionChange}, utils::PostResponse},
             };
 
+            static RETURNING: Lazy<String> =
+                Lazy::new(|| format!("RETURNING id, to_jsonb({}.*) AS row", $model_table));
 
-            let input = input.into_deep_inner();
+            #[allow(unused_mut)]
+            let mut input = input.into_deep_inner();
+            let mut tx = db
+                .begin()
+                .await
+                .map_internal_server_error("Failed to create in database.")?;
+            $(
+                $before_insert(&mut tx, &auth_user, &mut input).await?;
+            )?
             let created: PgRow;
             let query = {
                 if $user_id {
@@ -136,24 +148,38 @@ macro_rules! api_post {
                         $model_table;
                         user_id: auth_user.id,
                         $($input_field: input.$input_field),+;
-                        "RETURNING id"
+                        *RETURNING
                     )
                 } else {
                     crate::insert_query!(
                         $model_table;
                         $($input_field: input.$input_field),+;
-                        "RETURNING id"
+                        *RETURNING
                     )
                 }
             };
             created = sqlx::query(&query)
-                .fetch_one(&mut *db)
+                .fetch_one(&mut tx)
                 .await
                 .map_err(|e| {
                     println!("got post err {}", e);
                     ()
                 })
                 .map_internal_server_error("Failed to create in database.")?;
+            record_action(
+                &mut tx,
+                auth_user.id,
+                "create",
+                vec![ActionChange {
+                    table: $model_table.to_owned(),
+                    before: None,
+                    after: Some(created.get("row")),
+                }],
+            )
+            .await?;
+            tx.commit()
+                .await
+                .map_internal_server_error("Failed to create in database.")?;
             let resp = PostResponse { id: created.get("id") };
             Ok(APIResponse::new(
                 Status::Created,
@@ -183,6 +209,8 @@ macro_rules! api_patch {
         input: $input:path,
         input_fields: { $($name:ident),+ },
         query_where: $query_where:expr
+        $(, before_update: $before_update:path)?
+        $(, after_update: $after_update:path)?
     ) => {
         #[patch("/<id>", data = "<input>", format = "application/json")]
         async fn patch(
@@ -191,30 +219,81 @@ macro_rules! api_patch {
             input: rocket_validation::Validated<rocket::serde::json::Json<$input>>,
             id: uuid::Uuid,
         ) -> crate::responses::APIResult {
-            use crate::responses::{bad_request, internal_server_error, result_not_found, ok, result_bad_request};
+            use once_cell::sync::Lazy;
+            use sqlx::Connection;
+
+            use crate::{
+                api::actions::{record_action, ActionChange},
+                responses::{
+                    bad_request, internal_server_error, ok, result_bad_request, result_not_found,
+                    MapAPIResponse,
+                },
+            };
+
+            static BEFORE_QUERY_STRING: Lazy<String> = Lazy::new(|| {
+                format!(
+                    "SELECT to_jsonb({table}.*) FROM {table} {} FOR UPDATE",
+                    $query_where,
+                    table = $model_table
+                )
+            });
+            static QUERY_WHERE: Lazy<String> = Lazy::new(|| {
+                format!("{} RETURNING to_jsonb({}.*)", $query_where, $model_table)
+            });
 
-            let input = input.into_deep_inner();
+            #[allow(unused_mut)]
+            let mut input = input.into_deep_inner();
+            let mut tx = db
+                .begin()
+                .await
+                .map_internal_server_error("Failed to patch in database.")?;
+            $(
+                $before_update(&mut tx, &auth_user, id, &mut input).await?;
+            )?
             let update_str = crate::update_query! {
                 $model_table;
                 $($name: input.$name),+;
-                $query_where
+                *QUERY_WHERE
             };
-            if let Some(update_str) = update_str {
-                let result = sqlx::query(&update_str)
-                    .bind(id)
-                    .bind(auth_user.id)
-                    .execute(&mut *db)
-                    .await
-                    .map_err(|e| match e {
-                        sqlx::Error::Database(_) => bad_request("Invalid patch request."),
-                        _ => internal_server_error("Failed to patch in database."),
-                    })?;
-                if result.rows_affected() == 0 {
-                    return result_not_found("Item not found.");
-                }
-            } else {
+            let Some(update_str) = update_str else {
                 return result_bad_request("Empty patch request.");
-            }
+            };
+
+            let before: Option<serde_json::Value> = sqlx::query_scalar(&BEFORE_QUERY_STRING)
+                .bind(id)
+                .bind(auth_user.id)
+                .fetch_optional(&mut tx)
+                .await
+                .map_internal_server_error("Failed to patch in database.")?;
+            let Some(before) = before else {
+                return result_not_found("Item not found.");
+            };
+            let after: serde_json::Value = sqlx::query_scalar(&update_str)
+                .bind(id)
+                .bind(auth_user.id)
+                .fetch_one(&mut tx)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::Database(_) => bad_request("Invalid patch request."),
+                    _ => internal_server_error("Failed to patch in database."),
+                })?;
+            #[allow(unused_mut)]
+            let mut changes = vec![];
+            $(
+                changes = $after_update(&mut tx, &auth_user, &before, &after).await?;
+            )?
+            changes.insert(
+                0,
+                ActionChange {
+                    table: $model_table.to_owned(),
+                    before: Some(before),
+                    after: Some(after),
+                },
+            );
+            record_action(&mut tx, auth_user.id, "update", changes).await?;
+            tx.commit()
+                .await
+                .map_internal_server_error("Failed to patch in database.")?;
             Ok(ok("Patch successful."))
         }
     }
@@ -259,6 +338,47 @@ macro_rules! api_delete {
     };
 }
 
+/// Generates a delete route that moves the item and its
+/// descendants into the trash instead of removing them.
+///
+/// See [`crate::api::trash`] for how each table is handled.
+#[macro_export]
+macro_rules! api_soft_delete {
+    (
+        model_table: $model_table:expr
+    ) => {
+        #[delete("/<id>")]
+        async fn delete(
+            auth_user: crate::guards::auth::Auth<crate::models::user::UserModel>,
+            config: &rocket::State<crate::config::AppConfig>,
+            mut db: rocket_db_pools::Connection<crate::database::BackendDb>,
+            id: uuid::Uuid,
+        ) -> crate::responses::APIResult {
+            use sqlx::Connection;
+
+            use crate::{
+                api::{actions::record_action, trash},
+                responses::{ok, result_not_found, MapAPIResponse},
+            };
+
+            let mut tx = db
+                .begin()
+                .await
+                .map_internal_server_error("Failed to delete in database.")?;
+            trash::purge_expired(&mut tx, config, Some(auth_user.id)).await?;
+            let changes = trash::soft_delete(&mut tx, $model_table, id, auth_user.id).await?;
+            if changes.is_empty() {
+                return result_not_found("Item not found.");
+            }
+            record_action(&mut tx, auth_user.id, "delete", changes).await?;
+            tx.commit()
+                .await
+                .map_internal_server_error("Failed to delete in database.")?;
+            Ok(ok("Delete successful."))
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! api_crud {
     (
@@ -296,12 +416,15 @@ macro_rules! api_crud {
         patch: {
             input: $patch_input:path,
             input_fields: { $($patch_input_field:ident),+ }
+            $(, before_update: $before_update:path)?
         },
         delete: {}
     ) => {
         crate::api_get! {
             model_table: $model_table,
-            model_type: $get_model
+            model_type: $get_model,
+            query_single_where: "WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+            query_all_where: "WHERE user_id = $1 AND deleted_at IS NULL ORDER BY rank, id"
         }
         crate::api_post! {
             model_table: $model_table,
@@ -311,9 +434,11 @@ macro_rules! api_crud {
         crate::api_patch! {
             model_table: $model_table,
             input: $patch_input,
-            input_fields: { $($patch_input_field),+ }
+            input_fields: { $($patch_input_field),+ },
+            query_where: "WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL"
+            $(, before_update: $before_update)?
         }
-        crate::api_delete! {
+        crate::api_soft_delete! {
             model_table: $model_table
         }
 
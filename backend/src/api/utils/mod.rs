@@ -4,7 +4,11 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 pub mod crud_macros;
+pub mod ownership;
+pub mod placement;
 pub mod serde;
+pub mod timezone;
+pub mod tree;
 pub mod tree_crud_macros;
 pub mod validation;
 
@@ -22,7 +26,7 @@ pub struct PostResponse {
     pub id: Uuid,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Patch<T> {
     Missing,
     Null,
@@ -233,7 +237,7 @@ macro_rules! insert_query {
 
             for value_tuple in $value_tuples {
 
-                let value_columns = Vec::<String>::new();
+                let mut value_columns = Vec::<String>::new();
                 $(
                     let column = value_tuple.$index;
                     let use_column = spez::spez! {
@@ -0,0 +1,71 @@
+//! Checks that ids referenced in a request body belong to the user.
+//!
+//! Foreign keys only make sure the referenced row exists, so without these
+//! a user could attach data to another user's list, task or label by id.
+//! Missing and foreign ids get the same bad request, to not leak either.
+
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::responses::{bad_request, APIResponse, MapAPIResponse};
+
+pub async fn check_list(
+    db: &mut PgConnection,
+    user_id: Uuid,
+    list_id: Uuid,
+) -> Result<(), APIResponse> {
+    let owned = sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM lists WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+        ) AS "exists!""#,
+        list_id,
+        user_id
+    )
+    .fetch_one(db)
+    .await
+    .map_internal_server_error("Failed to fetch list from database.")?;
+    if !owned {
+        return Err(bad_request("List not found."));
+    }
+    Ok(())
+}
+
+/// Returns the list of the task.
+pub async fn check_task(
+    db: &mut PgConnection,
+    user_id: Uuid,
+    task_id: Uuid,
+) -> Result<Uuid, APIResponse> {
+    let list_id = sqlx::query_scalar!(
+        "SELECT tasks.list_id FROM tasks
+            INNER JOIN lists ON lists.id = tasks.list_id
+            WHERE tasks.id = $1 AND lists.user_id = $2 AND tasks.deleted_at IS NULL",
+        task_id,
+        user_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_internal_server_error("Failed to fetch task from database.")?;
+    list_id.ok_or_else(|| bad_request("Task not found."))
+}
+
+pub async fn check_label(
+    db: &mut PgConnection,
+    user_id: Uuid,
+    label_id: Uuid,
+) -> Result<(), APIResponse> {
+    let owned = sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM labels WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+        ) AS "exists!""#,
+        label_id,
+        user_id
+    )
+    .fetch_one(db)
+    .await
+    .map_internal_server_error("Failed to fetch label from database.")?;
+    if !owned {
+        return Err(bad_request("Label not found."));
+    }
+    Ok(())
+}
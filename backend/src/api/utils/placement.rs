@@ -0,0 +1,74 @@
+use uuid::Uuid;
+
+use crate::{
+    rank,
+    responses::{bad_request, internal_server_error, APIResponse},
+};
+
+/// Where a patched item goes among its siblings, from the
+/// `move_before` and `move_after` fields of its patch.
+#[derive(Debug, Clone, Copy)]
+pub enum Placement {
+    Before(Uuid),
+    After(Uuid),
+}
+
+impl Placement {
+    pub fn from_input(
+        move_before: Option<Uuid>,
+        move_after: Option<Uuid>,
+    ) -> Result<Option<Self>, APIResponse> {
+        match (move_before, move_after) {
+            (Some(_), Some(_)) => Err(bad_request(
+                "Only one of move_before and move_after can be given.",
+            )),
+            (Some(id), None) => Ok(Some(Placement::Before(id))),
+            (None, Some(id)) => Ok(Some(Placement::After(id))),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// Returns the rank that places an item among `siblings`, given as
+/// `(id, rank)` sorted by rank and without the item itself.
+/// Without a placement the item goes after the last sibling.
+pub fn rank_among(
+    siblings: &[(Uuid, String)],
+    placement: Option<Placement>,
+) -> Result<String, APIResponse> {
+    let (before, after) = match placement {
+        None => (siblings.last(), None),
+        Some(Placement::Before(id) | Placement::After(id)) => {
+            let Some(index) = siblings
+                .iter()
+                .position(|(sibling_id, _)| *sibling_id == id)
+            else {
+                return Err(bad_request(
+                    "Items can only be moved next to their siblings.",
+                ));
+            };
+            let anchor = &siblings[index];
+            // Siblings sharing the anchor's rank are skipped over
+            match placement {
+                Some(Placement::Before(_)) => (
+                    siblings[..index]
+                        .iter()
+                        .rev()
+                        .find(|(_, rank)| *rank < anchor.1),
+                    Some(anchor),
+                ),
+                _ => (
+                    Some(anchor),
+                    siblings[index + 1..]
+                        .iter()
+                        .find(|(_, rank)| *rank > anchor.1),
+                ),
+            }
+        }
+    };
+    rank::between(
+        before.map(|(_, rank)| rank.as_str()),
+        after.map(|(_, rank)| rank.as_str()),
+    )
+    .ok_or_else(|| internal_server_error("Failed to order item."))
+}
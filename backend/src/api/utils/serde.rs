@@ -20,6 +20,19 @@ where
     )
 }
 
+pub fn option_primitive_date_iso_serialize<S>(
+    x: &Option<PrimitiveDateTime>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match x {
+        Some(x) => primitive_date_iso_serialize(x, s),
+        None => s.serialize_none(),
+    }
+}
+
 pub fn primitive_date_iso_deserialize<'de, D>(
     deserializer: D,
 ) -> Result<PrimitiveDateTime, D::Error>
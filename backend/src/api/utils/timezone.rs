@@ -0,0 +1,77 @@
+use sqlx::PgConnection;
+use time::PrimitiveDateTime;
+
+use crate::responses::{APIResponse, MapAPIResponse};
+
+/// Due dates are stored in UTC, these convert them from and
+/// to a user's local time using the database's timezone data.
+pub async fn local_now(
+    db: &mut PgConnection,
+    timezone: &str,
+) -> Result<PrimitiveDateTime, APIResponse> {
+    sqlx::query_scalar!(
+        r#"SELECT CURRENT_TIMESTAMP AT TIME ZONE $1 AS "now!""#,
+        timezone
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to resolve timezone.")
+}
+
+pub async fn to_local(
+    db: &mut PgConnection,
+    utc: PrimitiveDateTime,
+    timezone: &str,
+) -> Result<PrimitiveDateTime, APIResponse> {
+    sqlx::query_scalar!(
+        r#"SELECT ($1::TIMESTAMP AT TIME ZONE 'UTC') AT TIME ZONE $2 AS "local!""#,
+        utc,
+        timezone
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to resolve timezone.")
+}
+
+pub async fn to_utc(
+    db: &mut PgConnection,
+    local: PrimitiveDateTime,
+    timezone: &str,
+) -> Result<PrimitiveDateTime, APIResponse> {
+    sqlx::query_scalar!(
+        r#"SELECT ($1::TIMESTAMP AT TIME ZONE $2) AT TIME ZONE 'UTC' AS "utc!""#,
+        local,
+        timezone
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to resolve timezone.")
+}
+
+pub async fn is_valid_timezone(db: &mut PgConnection, timezone: &str) -> Result<bool, APIResponse> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1) AS "exists!""#,
+        timezone
+    )
+    .fetch_one(&mut *db)
+    .await
+    .map_internal_server_error("Failed to resolve timezone.")
+}
+
+/// Like [`to_utc`], for many times at once, keeping their order.
+pub async fn to_utc_all(
+    db: &mut PgConnection,
+    local: &[PrimitiveDateTime],
+    timezone: &str,
+) -> Result<Vec<PrimitiveDateTime>, APIResponse> {
+    sqlx::query_scalar!(
+        r#"SELECT (local AT TIME ZONE $2) AT TIME ZONE 'UTC' AS "utc!"
+            FROM UNNEST($1::TIMESTAMP[]) WITH ORDINALITY AS times(local, position)
+            ORDER BY position"#,
+        local,
+        timezone
+    )
+    .fetch_all(&mut *db)
+    .await
+    .map_internal_server_error("Failed to resolve timezone.")
+}
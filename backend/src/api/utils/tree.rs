@@ -0,0 +1,57 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// How many levels below the roots a tree is built out to.
+pub const MAX_TREE_DEPTH: usize = 100;
+
+/// An item along with its nested children.
+#[derive(Debug, Serialize)]
+pub struct TreeNode<T> {
+    #[serde(flatten)]
+    pub item: T,
+    pub children: Vec<TreeNode<T>>,
+}
+
+/// Nests `(id, parent_id, item)` rows, keeping the order they come in
+/// among siblings. Rows whose parent isn't in `rows` become roots, rows more
+/// than `MAX_TREE_DEPTH` levels deep or only reachable through a cycle are
+/// left out.
+pub fn nest<T>(rows: Vec<(Uuid, Option<Uuid>, T)>) -> Vec<TreeNode<T>> {
+    let ids: HashSet<Uuid> = rows.iter().map(|(id, _, _)| *id).collect();
+    let mut roots = vec![];
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let mut items = HashMap::new();
+    for (id, parent_id, item) in rows {
+        match parent_id.filter(|parent_id| ids.contains(parent_id)) {
+            Some(parent_id) => children.entry(parent_id).or_default().push(id),
+            None => roots.push(id),
+        }
+        items.insert(id, item);
+    }
+    roots
+        .into_iter()
+        .filter_map(|id| build(id, 0, &mut children, &mut items))
+        .collect()
+}
+
+fn build<T>(
+    id: Uuid,
+    depth: usize,
+    children: &mut HashMap<Uuid, Vec<Uuid>>,
+    items: &mut HashMap<Uuid, T>,
+) -> Option<TreeNode<T>> {
+    let item = items.remove(&id)?;
+    let child_ids = if depth < MAX_TREE_DEPTH {
+        children.remove(&id).unwrap_or_default()
+    } else {
+        vec![]
+    };
+    Some(TreeNode {
+        item,
+        children: child_ids
+            .into_iter()
+            .filter_map(|child_id| build(child_id, depth + 1, children, items))
+            .collect(),
+    })
+}
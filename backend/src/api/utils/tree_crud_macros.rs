@@ -4,6 +4,21 @@ macro_rules! api_tree_get {
     model_table: $model_table:expr,
     model_type: $model:path,
     get_fields: { $($get_field:ident),+ }
+  ) => {
+        crate::api_tree_get! {
+            model_table: $model_table,
+            model_type: $model,
+            get_fields: { $($get_field),+ },
+            query_all_where: "deleted_at IS NULL",
+            child_where: "child_query.deleted_at IS NULL"
+        }
+  };
+  (
+    model_table: $model_table:expr,
+    model_type: $model:path,
+    get_fields: { $($get_field:ident),+ },
+    query_all_where: $query_all_where:expr,
+    child_where: $child_where:expr
   ) => {
         #[get("/?<limit>&<page>")]
         async fn get_all(
@@ -31,11 +46,15 @@ macro_rules! api_tree_get {
                 let get_fields = vec!($(concat!("base_query.", stringify!($get_field))),+);
                 format!(r#"
                     SELECT {get_fields}, child_query.id AS child_id FROM 
-                        (SELECT * FROM {table} WHERE user_id = $1 LIMIT $2 OFFSET $3) base_query
+                        (SELECT * FROM {table} WHERE user_id = $1 AND {query_all_where} ORDER BY rank, id LIMIT $2 OFFSET $3) base_query
                         LEFT JOIN {table} child_query
-                        ON base_query.id = child_query.parent_id"#,
+                        ON base_query.id = child_query.parent_id
+                        AND {child_where}
+                        ORDER BY base_query.rank, base_query.id, child_query.rank, child_query.id"#,
                     get_fields = get_fields.join(", "),
-                    table = $model_table
+                    table = $model_table,
+                    query_all_where = $query_all_where,
+                    child_where = $child_where
                 )
             });
             let query = sqlx::query(&QUERY_STRING)
@@ -46,11 +65,16 @@ macro_rules! api_tree_get {
             .await
             .map_internal_server_error("Error fetching items")?;
 
+            let mut order = vec![];
             let mut items: HashMap<Uuid, GetModel> = HashMap::new();
             for row in query {
-                let item = items.entry(row.get("id")).or_insert_with(|| GetModel {
-                    $($get_field: row.get(stringify!($get_field)),)+
-                    child_ids: Vec::new(),
+                let id: Uuid = row.get("id");
+                let item = items.entry(id).or_insert_with(|| {
+                    order.push(id);
+                    GetModel {
+                        $($get_field: row.get(stringify!($get_field)),)+
+                        child_ids: Vec::new(),
+                    }
                 });
                 if let Some(child_id) = row.get("child_id") {
                     item.child_ids.push(child_id);
@@ -58,7 +82,7 @@ macro_rules! api_tree_get {
             }
 
             let resp = GetAllResponse::<GetModel> {
-                items: items.into_values().collect(),
+                items: order.into_iter().filter_map(|id| items.remove(&id)).collect(),
                 limit,
                 page,
             };
@@ -85,11 +109,14 @@ macro_rules! api_tree_get {
                 let get_fields = vec!($(concat!("base_query.", stringify!($get_field))),+);
                 format!(r#"
                     SELECT {get_fields}, child_query.id AS child_id FROM 
-                        (SELECT * FROM {table} WHERE id = $1 AND user_id = $2) base_query
+                        (SELECT * FROM {table} WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL) base_query
                         LEFT JOIN {table} child_query
-                        ON base_query.id = child_query.parent_id"#,
+                        ON base_query.id = child_query.parent_id
+                        AND {child_where}
+                        ORDER BY child_query.rank, child_query.id"#,
                     get_fields = get_fields.join(", "),
-                    table = $model_table
+                    table = $model_table,
+                    child_where = $child_where
                 )
             });
             let query = sqlx::query(&QUERY_STRING)
@@ -122,6 +149,13 @@ macro_rules! api_tree_get {
 
 #[macro_export]
 macro_rules! api_tree_crud {
+    // Models with their own `delete` route pass `delete: { custom }`.
+    (@delete model_table: $model_table:expr) => {
+        crate::api_soft_delete! {
+            model_table: $model_table
+        }
+    };
+    (@delete model_table: $model_table:expr, custom) => {};
     (
         model_table: $model_table:expr,
         model_fields: { $($model_field:ident),+ },
@@ -151,34 +185,43 @@ macro_rules! api_tree_crud {
         get: {
             model_type: $get_model:path,
             get_fields: { $($get_field:ident),+ }
+            $(, query_all_where: $query_all_where:expr, child_where: $child_where:expr)?
         },
         post: {
             input: $post_input:path,
             input_fields: { $($post_input_field:ident),+ }
+            $(, before_insert: $before_insert:path)?
         },
         patch: {
             input: $patch_input:path,
             input_fields: { $($patch_input_field:ident),+ }
+            $(, before_update: $before_update:path)?
         },
-        delete: {}
+        delete: { $($delete_custom:ident)? }
     ) => {
         crate::api_tree_get! {
             model_table: $model_table,
             model_type: $get_model,
             get_fields: { $($get_field),+ }
+            $(, query_all_where: $query_all_where, child_where: $child_where)?
         }
         crate::api_post! {
             model_table: $model_table,
             input: $post_input,
-            input_fields: { $($post_input_field),+ }
+            input_fields: { $($post_input_field),+ },
+            user_id: true
+            $(, before_insert: $before_insert)?
         }
         crate::api_patch! {
             model_table: $model_table,
             input: $patch_input,
-            input_fields: { $($patch_input_field),+ }
+            input_fields: { $($patch_input_field),+ },
+            query_where: "WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL"
+            $(, before_update: $before_update)?
         }
-        crate::api_delete! {
-            model_table: $model_table
+        crate::api_tree_crud! {
+            @delete model_table: $model_table
+            $(, $delete_custom)?
         }
 
         pub fn mount_rocket(rocket: rocket::Rocket<rocket::Build>) -> rocket::Rocket<rocket::Build> {
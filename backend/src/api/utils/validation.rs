@@ -3,6 +3,7 @@ use regex::Regex;
 use validator::ValidationError;
 
 use super::Patch;
+use crate::{filter_query::FilterExpr, recurrence::RRule};
 
 pub fn validate_patch_color(color: &Patch<String>) -> Result<(), ValidationError> {
     match color {
@@ -20,3 +21,60 @@ pub fn validate_color(color: &str) -> Result<(), ValidationError> {
         "Color must follow the 6 digit hex format (#ffffff).",
     ))
 }
+
+pub fn validate_patch_recurrence(recurrence: &Patch<String>) -> Result<(), ValidationError> {
+    match recurrence {
+        Patch::Missing | Patch::Null => Ok(()),
+        Patch::Value(ref recurrence_str) => validate_recurrence(recurrence_str),
+    }
+}
+
+pub fn validate_recurrence(recurrence: &str) -> Result<(), ValidationError> {
+    match recurrence.parse::<RRule>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ValidationError::new(
+            "Recurrence must be a valid RFC 5545 RRULE (FREQ=WEEKLY;BYDAY=MO).",
+        )),
+    }
+}
+
+pub fn validate_patch_priority(priority: &Patch<i16>) -> Result<(), ValidationError> {
+    match priority {
+        Patch::Missing | Patch::Null => Ok(()),
+        Patch::Value(priority) => validate_priority(*priority),
+    }
+}
+
+pub fn validate_priority(priority: i16) -> Result<(), ValidationError> {
+    if (1..=4).contains(&priority) {
+        return Ok(());
+    }
+    Err(ValidationError::new(
+        "Priority must be between 1 (highest) and 4 (lowest).",
+    ))
+}
+
+pub fn validate_patch_filter_query(query: &Patch<String>) -> Result<(), ValidationError> {
+    match query {
+        Patch::Missing | Patch::Null => Ok(()),
+        Patch::Value(ref query_str) => validate_filter_query(query_str),
+    }
+}
+
+pub fn validate_filter_query(query: &str) -> Result<(), ValidationError> {
+    match query.parse::<FilterExpr>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ValidationError::new(
+            "Query must be a valid filter ((today | overdue) & #work).",
+        )),
+    }
+}
+
+pub fn validate_reminder_channel(channel: &str) -> Result<(), ValidationError> {
+    if matches!(channel, "log" | "email" | "webhook") {
+        return Ok(());
+    }
+    Err(ValidationError::new(
+        "Channel must be one of log, email or webhook.",
+    ))
+}
@@ -0,0 +1,203 @@
+use rocket::{http::Status, Build, Rocket};
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use time::{Date, Duration};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        tasks::{GetModel, GetModelBuilder},
+        utils::timezone::local_now,
+    },
+    database::BackendDb,
+    guards::auth::Auth,
+    models::user::UserModel,
+    responses::{result_bad_request, APIResponse, APIResult, MapAPIResponse},
+};
+
+/// The furthest ahead a single request of the upcoming view can reach.
+const MAX_VIEW_DAYS: i64 = 366;
+
+/// Tasks due today in the user's timezone, with overdue tasks in their own group.
+#[get("/today")]
+async fn get_today(auth_user: Auth<UserModel>, mut db: Connection<BackendDb>) -> APIResult {
+    let today = local_now(&mut db, &auth_user.timezone).await?.date();
+    get_view(&mut db, &auth_user, today, today, today).await
+}
+
+/// Tasks grouped by the day they are due on, from today (or `from`) to a year
+/// later unless a smaller range is asked for. Overdue tasks are only part of
+/// the range that starts today.
+#[get("/upcoming?<from>&<to>")]
+async fn get_upcoming(
+    auth_user: Auth<UserModel>,
+    mut db: Connection<BackendDb>,
+    from: Option<Date>,
+    to: Option<Date>,
+) -> APIResult {
+    let today = local_now(&mut db, &auth_user.timezone).await?.date();
+    let from = from.unwrap_or(today).max(today);
+    let to = to.unwrap_or_else(|| {
+        from.checked_add(Duration::days(MAX_VIEW_DAYS - 1))
+            .unwrap_or(Date::MAX)
+    });
+    if to < from {
+        return result_bad_request("The range has to end after it starts.");
+    }
+    if to - from >= Duration::days(MAX_VIEW_DAYS) {
+        return result_bad_request("The range can't be longer than a year.");
+    }
+    get_view(&mut db, &auth_user, today, from, to).await
+}
+
+async fn get_view(
+    db: &mut Connection<BackendDb>,
+    user: &UserModel,
+    today: Date,
+    from: Date,
+    to: Date,
+) -> APIResult {
+    // Day boundaries are local midnights converted back to UTC, which is
+    // how due dates are stored.
+    let query = sqlx::query!(
+        r#"
+        SELECT
+            base_tasks.*,
+            child_tasks.id AS "child_id?",
+            task_labels.label_id as "label_id?"
+            FROM (
+                SELECT
+                    tasks.*,
+                    task_blocked(tasks.id) AS "blocked!",
+                    ((tasks.due_at AT TIME ZONE 'UTC') AT TIME ZONE $2)::DATE AS "due_day!"
+                    FROM tasks
+                    INNER JOIN lists
+                    ON lists.id = tasks.list_id
+                WHERE user_id = $1
+                    AND tasks.deleted_at IS NULL
+                    AND lists.archived_at IS NULL
+                    AND NOT tasks.completed
+                    AND tasks.due_at < (($5::DATE + 1)::TIMESTAMP AT TIME ZONE $2) AT TIME ZONE 'UTC'
+                    AND ($3 OR tasks.due_at >= ($4::DATE::TIMESTAMP AT TIME ZONE $2) AT TIME ZONE 'UTC')
+            ) base_tasks
+            LEFT JOIN tasks child_tasks
+                ON base_tasks.id = child_tasks.parent_id
+                AND child_tasks.deleted_at IS NULL
+            LEFT JOIN task_labels
+                ON base_tasks.id = task_labels.task_id
+                AND task_labels.label_id IN (SELECT id FROM labels WHERE deleted_at IS NULL)
+            ORDER BY child_tasks.rank, child_tasks.id"#,
+        user.id,
+        user.timezone,
+        from == today,
+        from,
+        to
+    )
+    .fetch_all(&mut **db)
+    .await
+    .map_internal_server_error("Error fetching items")?;
+
+    let mut items: HashMap<Uuid, (Date, GetModelBuilder)> = HashMap::new();
+    for row in query {
+        let (_, item) = items.entry(row.id).or_insert_with(|| {
+            (
+                row.due_day,
+                GetModelBuilder {
+                    get_model: GetModel {
+                        id: row.id,
+                        parent_id: row.parent_id,
+                        list_id: row.list_id,
+                        created_at: row.created_at,
+                        updated_at: row.updated_at,
+                        due_at: row.due_at,
+                        due_text: row.due_text,
+                        completed: row.completed,
+                        completed_at: row.completed_at,
+                        blocked: row.blocked,
+                        title: row.title,
+                        description: row.description,
+                        recurrence: row.recurrence,
+                        recurrence_from_completion: row.recurrence_from_completion,
+                        recurrence_keep_history: row.recurrence_keep_history,
+                        priority: row.priority,
+                        rank: row.rank,
+                        child_ids: vec![],
+                        label_ids: vec![],
+                    },
+                    child_ids: vec![],
+                    label_ids: HashSet::new(),
+                },
+            )
+        });
+        if let Some(child_id) = row.child_id {
+            item.add_child(child_id);
+        }
+        if let Some(label_id) = row.label_id {
+            item.label_ids.insert(label_id);
+        }
+    }
+
+    let mut items: Vec<_> = items
+        .into_values()
+        .map(|(day, item)| (day, item.build()))
+        .collect();
+    // Most important first within each day, then by time of day
+    items.sort_by(|(_, a), (_, b)| {
+        (a.priority, a.due_at, &a.rank, a.created_at).cmp(&(
+            b.priority,
+            b.due_at,
+            &b.rank,
+            b.created_at,
+        ))
+    });
+
+    let mut overdue = vec![];
+    let mut days: BTreeMap<Date, Vec<GetModel>> = BTreeMap::new();
+    for (day, item) in items {
+        if day < today {
+            overdue.push(item);
+        } else {
+            days.entry(day).or_default().push(item);
+        }
+    }
+
+    let resp = ViewModel {
+        timezone: user.timezone.clone(),
+        from: from.to_string(),
+        to: to.to_string(),
+        overdue,
+        days: days
+            .into_iter()
+            .map(|(day, items)| DayModel {
+                date: day.to_string(),
+                items,
+            })
+            .collect(),
+    };
+
+    Ok(APIResponse::new(
+        Status::Ok,
+        serde_json::to_value(resp)
+            .map_internal_server_error("Failed to convert response into json.")?,
+    ))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ViewModel {
+    pub timezone: String,
+    pub from: String,
+    pub to: String,
+    pub overdue: Vec<GetModel>,
+    pub days: Vec<DayModel>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DayModel {
+    pub date: String,
+    pub items: Vec<GetModel>,
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.mount("/views", routes![get_today, get_upcoming])
+}
@@ -6,7 +6,7 @@ use rocket::{
     },
     log::LogLevel,
 };
-use std::{env, fmt};
+use std::{env, fmt, path::PathBuf};
 use time::Duration;
 
 mod dev_config;
@@ -27,9 +27,64 @@ pub struct AppConfig {
     pub database_pool_size: u32,
     pub password_salt: String,
     pub session_duration: Duration,
+    pub trash_retention: Duration,
+    /// How often the trash of every user is purged of expired items.
+    pub trash_purge_interval: Duration,
+    /// How often the reminder worker looks for due reminders.
+    pub reminder_poll_interval: Duration,
+    /// Delay before retrying a failed reminder, doubled after every failure.
+    pub reminder_retry_delay: Duration,
+    pub reminder_max_attempts: i32,
+    /// Address of the SMTP server email reminders are sent through.
+    pub smtp_address: String,
+    pub smtp_from: String,
+    /// Webhook hosts that may resolve to loopback or private addresses,
+    /// which are refused for any other host.
+    pub webhook_allowed_hosts: Vec<String>,
+    pub storage: StorageConfig,
+    /// Largest file that can be attached to a task.
+    pub attachment_max_bytes: u64,
+    /// Total size of the attachments a user can keep.
+    pub attachment_quota_bytes: i64,
     pub log_level: LogLevel,
 }
 
+/// Where attachment contents are kept.
+#[derive(Clone)]
+pub enum StorageConfig {
+    Local {
+        root: PathBuf,
+    },
+    /// Any S3 compatible service, addressed path style (`endpoint/bucket/key`).
+    S3 {
+        endpoint: String,
+        bucket: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    },
+}
+
+impl StorageConfig {
+    fn from_env() -> Self {
+        match env::var("STORAGE_BACKEND").as_deref() {
+            Ok("s3") => StorageConfig::S3 {
+                endpoint: env::var("S3_ENDPOINT").expect("S3_ENDPOINT must be set"),
+                bucket: env::var("S3_BUCKET").expect("S3_BUCKET must be set"),
+                region: env::var("S3_REGION").unwrap_or_else(|_| String::from("us-east-1")),
+                access_key: env::var("S3_ACCESS_KEY").expect("S3_ACCESS_KEY must be set"),
+                secret_key: env::var("S3_SECRET_KEY").expect("S3_SECRET_KEY must be set"),
+            },
+            Ok("local") | Err(_) => StorageConfig::Local {
+                root: env::var("STORAGE_PATH")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| PathBuf::from("storage")),
+            },
+            Ok(backend) => panic!("STORAGE_BACKEND '{}' must be local or s3", backend),
+        }
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         AppConfig {
@@ -45,6 +100,19 @@ impl Default for AppConfig {
             database_pool_size: 10,
             password_salt: String::from("default"),
             session_duration: Duration::seconds(10), // TODO: Replace this after testing,
+            trash_retention: Duration::days(30),
+            trash_purge_interval: Duration::hours(1),
+            reminder_poll_interval: Duration::seconds(30),
+            reminder_retry_delay: Duration::minutes(1),
+            reminder_max_attempts: 5,
+            smtp_address: String::from("127.0.0.1:1025"),
+            smtp_from: String::from("reminders@toasttask.localhost"),
+            webhook_allowed_hosts: vec![],
+            storage: StorageConfig::Local {
+                root: PathBuf::from("storage"),
+            },
+            attachment_max_bytes: 10 * 1024 * 1024,
+            attachment_quota_bytes: 100 * 1024 * 1024,
             log_level: LogLevel::Normal,
         }
     }
@@ -64,6 +132,59 @@ impl AppConfig {
             base_url: env::var("BASE_URL").expect("BASE_URL must be set"),
             database_url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
             password_salt: env::var("PASSWORD_SALT").expect("PASSWORD_SALT must be set"),
+            trash_retention: env::var("TRASH_RETENTION_DAYS")
+                .map(|days| {
+                    Duration::days(
+                        days.parse::<i64>()
+                            .expect("TRASH_RETENTION_DAYS must be an i64"),
+                    )
+                })
+                .unwrap_or_else(|_| Self::default().trash_retention),
+            trash_purge_interval: env::var("TRASH_PURGE_MINUTES")
+                .map(|minutes| {
+                    Duration::minutes(
+                        minutes
+                            .parse::<i64>()
+                            .ok()
+                            .filter(|minutes| *minutes > 0)
+                            .expect("TRASH_PURGE_MINUTES must be a positive i64"),
+                    )
+                })
+                .unwrap_or_else(|_| Self::default().trash_purge_interval),
+            reminder_poll_interval: env::var("REMINDER_POLL_SECONDS")
+                .map(|seconds| {
+                    Duration::seconds(
+                        seconds
+                            .parse::<i64>()
+                            .ok()
+                            .filter(|seconds| *seconds > 0)
+                            .expect("REMINDER_POLL_SECONDS must be a positive i64"),
+                    )
+                })
+                .unwrap_or_else(|_| Self::default().reminder_poll_interval),
+            smtp_address: env::var("SMTP_ADDRESS").unwrap_or_else(|_| Self::default().smtp_address),
+            smtp_from: env::var("SMTP_FROM").unwrap_or_else(|_| Self::default().smtp_from),
+            webhook_allowed_hosts: env::var("WEBHOOK_ALLOWED_HOSTS")
+                .map(|hosts| {
+                    hosts
+                        .split(',')
+                        .map(|host| host.trim().to_lowercase())
+                        .filter(|host| !host.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            storage: StorageConfig::from_env(),
+            attachment_max_bytes: env::var("ATTACHMENT_MAX_MB")
+                .map(|mb| mb.parse::<u64>().expect("ATTACHMENT_MAX_MB must be a u64") * 1024 * 1024)
+                .unwrap_or_else(|_| Self::default().attachment_max_bytes),
+            attachment_quota_bytes: env::var("ATTACHMENT_QUOTA_MB")
+                .map(|mb| {
+                    mb.parse::<i64>()
+                        .expect("ATTACHMENT_QUOTA_MB must be an i64")
+                        * 1024
+                        * 1024
+                })
+                .unwrap_or_else(|_| Self::default().attachment_quota_bytes),
             ..Self::default()
         }
     }
@@ -83,6 +204,9 @@ impl AppConfig {
             .merge(("port", self.backend_port))
             .merge(("log_level", self.log_level.to_string().to_lowercase()))
             .merge(("databases", map!["backend" => db]))
+            .merge(("limits.file", self.attachment_max_bytes))
+            // Leaves room for the other fields and the multipart framing
+            .merge(("limits.data-form", self.attachment_max_bytes + 64 * 1024))
     }
 }
 
@@ -1,10 +1,20 @@
 use rocket::log::LogLevel;
+use time::Duration;
 
-use super::AppConfig;
+use super::{AppConfig, StorageConfig};
 
 pub fn config() -> AppConfig {
     AppConfig {
         log_level: LogLevel::Off,
+        reminder_poll_interval: Duration::milliseconds(200),
+        reminder_retry_delay: Duration::seconds(1),
+        // The webhook receivers of the tests listen on loopback
+        webhook_allowed_hosts: vec![String::from("127.0.0.1")],
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("toast-task-test-storage"),
+        },
+        attachment_max_bytes: 64 * 1024,
+        attachment_quota_bytes: 128 * 1024,
         ..AppConfig::from_env()
     }
 }
@@ -0,0 +1,484 @@
+use time::{Date, Duration, Month, PrimitiveDateTime, Time, Weekday};
+
+use crate::recurrence::{Frequency, RRule};
+
+/// Time of day used when the text only names a day, so that
+/// the task stays due for the whole day.
+const END_OF_DAY: Time = time::macros::time!(23:59);
+
+/// A due date read from text like "tomorrow 5pm" or "every monday".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedDueDate {
+    /// Due date in the same local time as the `now` it was parsed against.
+    pub due_at: PrimitiveDateTime,
+    pub recurrence: Option<RRule>,
+}
+
+/// Parses a due date phrase relative to `now`, in the user's local time.
+///
+/// Understands days ("today", "tomorrow", "friday", "next week", "oct 19",
+/// "2023-10-19"), offsets ("in 3 days", "in an hour"), times ("5pm",
+/// "17:30", "noon") and repeats ("every monday", "every other week").
+pub fn parse(text: &str, now: PrimitiveDateTime) -> Option<ParsedDueDate> {
+    let text = text.to_lowercase().replace(',', " ");
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "at" | "on" | "by" | "due"))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut parser = Parser {
+        words: &words,
+        position: 0,
+        now,
+    };
+    if matches!(
+        words[0],
+        "every" | "daily" | "weekly" | "monthly" | "yearly"
+    ) {
+        parser.parse_recurring()
+    } else {
+        parser.parse_single()
+    }
+}
+
+struct Parser<'a> {
+    words: &'a [&'a str],
+    position: usize,
+    now: PrimitiveDateTime,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.words.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&'a str> {
+        self.words.get(self.position + offset).copied()
+    }
+
+    fn advance(&mut self, count: usize) {
+        self.position += count;
+    }
+
+    fn done(&self) -> bool {
+        self.position >= self.words.len()
+    }
+
+    fn parse_single(&mut self) -> Option<ParsedDueDate> {
+        let today = self.now.date();
+        let mut date = None;
+        let mut time = None;
+        let mut exact = None;
+
+        while !self.done() {
+            if date.is_none() && exact.is_none() {
+                if let Some(parsed) = self.parse_offset() {
+                    exact = Some(parsed);
+                    continue;
+                }
+                if let Some(parsed) = self.parse_day(today) {
+                    date = Some(parsed);
+                    continue;
+                }
+            }
+            if time.is_none() && exact.is_none() {
+                if let Some(parsed) = self.parse_time() {
+                    time = Some(parsed);
+                    continue;
+                }
+            }
+            return None;
+        }
+
+        let due_at = match (exact, date, time) {
+            (Some(exact), _, _) => exact,
+            (None, Some(date), time) => date.with_time(time.unwrap_or(END_OF_DAY)),
+            // A bare time means its next occurrence
+            (None, None, Some(time)) => {
+                let due_at = today.with_time(time);
+                if due_at > self.now {
+                    due_at
+                } else {
+                    due_at.checked_add(Duration::days(1))?
+                }
+            }
+            (None, None, None) => return None,
+        };
+        Some(ParsedDueDate {
+            due_at,
+            recurrence: None,
+        })
+    }
+
+    fn parse_recurring(&mut self) -> Option<ParsedDueDate> {
+        let today = self.now.date();
+        let mut rule = RRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: vec![],
+            by_month_day: vec![],
+            by_month: vec![],
+            by_set_pos: vec![],
+            week_start: Weekday::Monday,
+        };
+
+        let word = self.peek()?;
+        self.advance(1);
+        match word {
+            "daily" => rule.freq = Frequency::Daily,
+            "weekly" => rule.freq = Frequency::Weekly,
+            "monthly" => rule.freq = Frequency::Monthly,
+            "yearly" => rule.freq = Frequency::Yearly,
+            _ => {
+                if self.peek() == Some("other") {
+                    rule.interval = 2;
+                    self.advance(1);
+                } else if let Some(interval) = self.peek().and_then(parse_number) {
+                    rule.interval = interval;
+                    self.advance(1);
+                }
+                self.parse_repeat(&mut rule)?;
+            }
+        }
+
+        let time = if self.done() {
+            END_OF_DAY
+        } else {
+            let time = self.parse_time()?;
+            if !self.done() {
+                return None;
+            }
+            time
+        };
+
+        // The first occurrence is the earliest matching one that is still
+        // ahead, later ones then follow the interval from there
+        let first = RRule {
+            interval: 1,
+            ..rule.clone()
+        };
+        let yesterday = today.checked_sub(Duration::days(1))?;
+        let mut due_at = first.next_after(yesterday.with_time(time))?;
+        if due_at <= self.now {
+            due_at = first.next_after(due_at)?;
+        }
+        Some(ParsedDueDate {
+            due_at,
+            recurrence: Some(rule),
+        })
+    }
+
+    /// What comes after "every": a unit ("week"), "weekday", a day of the
+    /// month ("15th") or a list of weekdays ("mon and thu").
+    fn parse_repeat(&mut self, rule: &mut RRule) -> Option<()> {
+        let word = self.peek()?;
+        if let Some(unit) = parse_unit(word) {
+            rule.freq = match unit {
+                Unit::Day => Frequency::Daily,
+                Unit::Week => Frequency::Weekly,
+                Unit::Month => Frequency::Monthly,
+                Unit::Year => Frequency::Yearly,
+                Unit::Hour | Unit::Minute => return None,
+            };
+            self.advance(1);
+        } else if word == "weekday" || word == "weekdays" {
+            rule.freq = Frequency::Weekly;
+            rule.by_day = [
+                Weekday::Monday,
+                Weekday::Tuesday,
+                Weekday::Wednesday,
+                Weekday::Thursday,
+                Weekday::Friday,
+            ]
+            .into_iter()
+            .map(|weekday| (None, weekday))
+            .collect();
+            self.advance(1);
+        } else if let Some(day) = parse_ordinal_day(word) {
+            rule.freq = Frequency::Monthly;
+            rule.by_month_day = vec![day as i32];
+            self.advance(1);
+        } else {
+            rule.freq = Frequency::Weekly;
+            while let Some(weekday) = self.peek().and_then(parse_weekday) {
+                rule.by_day.push((None, weekday));
+                self.advance(1);
+                if self.peek() == Some("and") {
+                    self.advance(1);
+                }
+            }
+            if rule.by_day.is_empty() {
+                return None;
+            }
+        }
+        Some(())
+    }
+
+    /// "in 3 days", "in an hour", "in 2 weeks"
+    fn parse_offset(&mut self) -> Option<PrimitiveDateTime> {
+        if self.peek()? != "in" {
+            return None;
+        }
+        let amount = match self.peek_at(1)? {
+            "a" | "an" | "one" => 1,
+            word => parse_number(word)?,
+        } as i64;
+        let unit = parse_unit(self.peek_at(2)?)?;
+        let due_at = match unit {
+            Unit::Minute => self.now.checked_add(Duration::minutes(amount))?,
+            Unit::Hour => self.now.checked_add(Duration::hours(amount))?,
+            Unit::Day => self.now.checked_add(Duration::days(amount))?,
+            Unit::Week => self.now.checked_add(Duration::weeks(amount))?,
+            Unit::Month => add_months(self.now.date(), amount)?.with_time(self.now.time()),
+            Unit::Year => add_months(self.now.date(), amount * 12)?.with_time(self.now.time()),
+        };
+        self.advance(3);
+        Some(due_at)
+    }
+
+    fn parse_day(&mut self, today: Date) -> Option<Date> {
+        let word = self.peek()?;
+        let (date, length) = match word {
+            "today" => (today, 1),
+            "tomorrow" | "tmr" | "tmrw" => (today.next_day()?, 1),
+            "next" => {
+                let next = self.peek_at(1)?;
+                let date = if let Some(weekday) = parse_weekday(next) {
+                    today.next_occurrence(weekday)
+                } else {
+                    match parse_unit(next)? {
+                        Unit::Week => today.next_occurrence(Weekday::Monday),
+                        Unit::Month => {
+                            let date = add_months(today, 1)?;
+                            date.replace_day(1).ok()?
+                        }
+                        Unit::Year => {
+                            Date::from_calendar_date(today.year() + 1, Month::January, 1).ok()?
+                        }
+                        _ => return None,
+                    }
+                };
+                (date, 2)
+            }
+            "end" if self.peek_at(1) == Some("of") => {
+                let date = match parse_unit(self.peek_at(2)?)? {
+                    Unit::Week => {
+                        if today.weekday() == Weekday::Sunday {
+                            today
+                        } else {
+                            today.next_occurrence(Weekday::Sunday)
+                        }
+                    }
+                    Unit::Month => today
+                        .replace_day(time::util::days_in_year_month(today.year(), today.month()))
+                        .ok()?,
+                    _ => return None,
+                };
+                (date, 3)
+            }
+            _ => {
+                if let Some(weekday) = parse_weekday(word) {
+                    // Today counts, "next friday" skips ahead
+                    let date = if today.weekday() == weekday {
+                        today
+                    } else {
+                        today.next_occurrence(weekday)
+                    };
+                    (date, 1)
+                } else {
+                    return self.parse_calendar_date(today);
+                }
+            }
+        };
+        self.advance(length);
+        Some(date)
+    }
+
+    /// "2023-10-19", "oct 19", "19 october", "october 19th 2024"
+    fn parse_calendar_date(&mut self, today: Date) -> Option<Date> {
+        let word = self.peek()?;
+        if let Ok(date) = Date::parse(
+            word,
+            time::macros::format_description!("[year]-[month]-[day]"),
+        ) {
+            self.advance(1);
+            return Some(date);
+        }
+
+        let (month, day) = match (
+            parse_month(word),
+            self.peek_at(1).and_then(parse_ordinal_day),
+        ) {
+            (Some(month), Some(day)) => (month, day),
+            _ => (
+                self.peek_at(1).and_then(parse_month)?,
+                parse_ordinal_day(word)?,
+            ),
+        };
+        let year = self
+            .peek_at(2)
+            .filter(|year| year.len() == 4)
+            .and_then(|year| year.parse::<i32>().ok());
+        match year {
+            Some(year) => {
+                self.advance(3);
+                Date::from_calendar_date(year, month, day).ok()
+            }
+            None => {
+                self.advance(2);
+                // Without a year, the date is the next one to come around
+                let date = Date::from_calendar_date(today.year(), month, day).ok();
+                match date {
+                    Some(date) if date >= today => Some(date),
+                    _ => Date::from_calendar_date(today.year() + 1, month, day).ok(),
+                }
+            }
+        }
+    }
+
+    /// "5pm", "5:30 pm", "17:30", "noon", "midnight", "morning"
+    fn parse_time(&mut self) -> Option<Time> {
+        let word = self.peek()?;
+        let named = match word {
+            "noon" | "midday" => Some(Time::from_hms(12, 0, 0).ok()?),
+            "midnight" => Some(Time::MIDNIGHT),
+            "morning" => Some(Time::from_hms(9, 0, 0).ok()?),
+            "afternoon" => Some(Time::from_hms(15, 0, 0).ok()?),
+            "evening" | "tonight" => Some(Time::from_hms(19, 0, 0).ok()?),
+            _ => None,
+        };
+        if let Some(time) = named {
+            self.advance(1);
+            return Some(time);
+        }
+
+        let (clock, meridiem, length) = if let Some(clock) = word.strip_suffix("am") {
+            (clock, Some(false), 1)
+        } else if let Some(clock) = word.strip_suffix("pm") {
+            (clock, Some(true), 1)
+        } else {
+            match self.peek_at(1) {
+                Some("am") => (word, Some(false), 2),
+                Some("pm") => (word, Some(true), 2),
+                _ => (word, None, 1),
+            }
+        };
+        let (hour, minute) = match clock.split_once(':') {
+            Some((hour, minute)) if minute.len() == 2 => {
+                (hour.parse::<u8>().ok()?, minute.parse::<u8>().ok()?)
+            }
+            None if meridiem.is_some() => (clock.parse::<u8>().ok()?, 0),
+            _ => return None,
+        };
+        let hour = match meridiem {
+            Some(_) if !(1..=12).contains(&hour) => return None,
+            Some(false) => hour % 12,
+            Some(true) => hour % 12 + 12,
+            None => hour,
+        };
+        let time = Time::from_hms(hour, minute, 0).ok()?;
+        self.advance(length);
+        Some(time)
+    }
+}
+
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+fn parse_unit(word: &str) -> Option<Unit> {
+    match word.strip_suffix('s').unwrap_or(word) {
+        "min" | "minute" => Some(Unit::Minute),
+        "hour" | "hr" => Some(Unit::Hour),
+        "day" => Some(Unit::Day),
+        "week" | "wk" => Some(Unit::Week),
+        "month" => Some(Unit::Month),
+        "year" | "yr" => Some(Unit::Year),
+        _ => None,
+    }
+}
+
+fn parse_number(word: &str) -> Option<u32> {
+    let number = match word {
+        "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        _ => word.parse().ok()?,
+    };
+    (number > 0).then_some(number)
+}
+
+/// "19", "19th", "1st"
+fn parse_ordinal_day(word: &str) -> Option<u8> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" | "mondays" => Some(Weekday::Monday),
+        "tuesday" | "tue" | "tues" | "tuesdays" => Some(Weekday::Tuesday),
+        "wednesday" | "wed" | "wednesdays" => Some(Weekday::Wednesday),
+        "thursday" | "thu" | "thur" | "thurs" | "thursdays" => Some(Weekday::Thursday),
+        "friday" | "fri" | "fridays" => Some(Weekday::Friday),
+        "saturday" | "sat" | "saturdays" => Some(Weekday::Saturday),
+        "sunday" | "sun" | "sundays" => Some(Weekday::Sunday),
+        _ => None,
+    }
+}
+
+/// Full month names or any abbreviation of at least three letters.
+fn parse_month(word: &str) -> Option<Month> {
+    const MONTHS: [(&str, Month); 12] = [
+        ("january", Month::January),
+        ("february", Month::February),
+        ("march", Month::March),
+        ("april", Month::April),
+        ("may", Month::May),
+        ("june", Month::June),
+        ("july", Month::July),
+        ("august", Month::August),
+        ("september", Month::September),
+        ("october", Month::October),
+        ("november", Month::November),
+        ("december", Month::December),
+    ];
+    let word = word.trim_end_matches('.');
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .find(|(name, _)| name.starts_with(word))
+        .map(|(_, month)| *month)
+}
+
+/// Adds months, clamping the day to the end of shorter months.
+fn add_months(date: Date, months: i64) -> Option<Date> {
+    let total = date.year() as i64 * 12 + (date.month() as i64 - 1) + months;
+    let year = total.div_euclid(12).try_into().ok()?;
+    let month = Month::try_from((total.rem_euclid(12) + 1) as u8).ok()?;
+    let day = date.day().min(time::util::days_in_year_month(year, month));
+    Date::from_calendar_date(year, month, day).ok()
+}
@@ -0,0 +1,373 @@
+use std::str::FromStr;
+
+use time::{macros::datetime, Duration, PrimitiveDateTime, Time};
+
+use crate::due_date;
+
+/// A filter over tasks like `(today | overdue) & #work & !completed`.
+///
+/// `!` binds tighter than `&`, which binds tighter than `|`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Term(FilterTerm),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterTerm {
+    All,
+    Today,
+    Tomorrow,
+    /// Due before today.
+    Overdue,
+    /// Due today or in the following days, `7 days` or `next 7 days`.
+    NextDays(u32),
+    /// `due before: friday`, read like a task's due text.
+    DueBefore(String),
+    DueAfter(String),
+    Completed,
+    Recurring,
+    Subtask,
+    /// `p1` to `p4`.
+    Priority(i16),
+    /// `#Work`, matched case-insensitively against list titles.
+    List(String),
+    /// `@errands`, matched case-insensitively against label titles.
+    Label(String),
+    /// `search: milk`, matched against task titles.
+    Search(String),
+}
+
+/// Value bound to one of the placeholders of a [`FilterSql`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterParam {
+    Text(String),
+    Timestamp(PrimitiveDateTime),
+    SmallInt(i16),
+}
+
+/// A compiled filter, a condition over the `tasks` table
+/// using `$n` placeholders for every value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterSql {
+    pub condition: String,
+    pub params: Vec<FilterParam>,
+}
+
+/// Longest filter accepted, which also bounds the length of `&` and `|` chains.
+const MAX_LENGTH: usize = 1000;
+/// How deep `(` and `!` can be nested.
+const MAX_DEPTH: usize = 32;
+
+impl FromStr for FilterExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.chars().count() > MAX_LENGTH {
+            return Err(format!("Filter is longer than {MAX_LENGTH} characters."));
+        }
+        let tokens = tokenize(s);
+        if tokens.is_empty() {
+            return Err(String::from("Filter is empty."));
+        }
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let expr = parser.parse_or(0)?;
+        if parser.position < tokens.len() {
+            return Err(String::from("Unexpected ')'."));
+        }
+        Ok(expr)
+    }
+}
+
+impl FilterExpr {
+    /// Compiles the filter into SQL, with `now` in the user's local time.
+    ///
+    /// Placeholders are numbered from `first_param` so the condition can
+    /// be added to a query that already binds its own values.
+    pub fn compile(
+        &self,
+        now: PrimitiveDateTime,
+        timezone: &str,
+        first_param: usize,
+    ) -> Result<FilterSql, String> {
+        let mut compiler = Compiler {
+            now,
+            timezone,
+            first_param,
+            params: vec![],
+        };
+        let condition = compiler.compile(self)?;
+        Ok(FilterSql {
+            condition,
+            params: compiler.params,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(String),
+}
+
+fn tokenize(s: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut term = String::new();
+    for c in s.chars() {
+        let token = match c {
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '&' => Token::And,
+            '|' => Token::Or,
+            // `!` only negates at the start of a term, so titles can contain it
+            '!' if term.trim().is_empty() => Token::Not,
+            _ => {
+                term.push(c);
+                continue;
+            }
+        };
+        push_term(&mut tokens, &mut term);
+        tokens.push(token);
+    }
+    push_term(&mut tokens, &mut term);
+    tokens
+}
+
+fn push_term(tokens: &mut Vec<Token>, term: &mut String) {
+    let trimmed = term.trim();
+    if !trimmed.is_empty() {
+        tokens.push(Token::Term(trimmed.to_owned()));
+    }
+    term.clear();
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// `depth` counts the enclosing `(` and `!`.
+    fn parse_or(&mut self, depth: usize) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_and(depth)?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.parse_and(depth)?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self, depth: usize) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_unary(depth)?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.parse_unary(depth)?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self, depth: usize) -> Result<FilterExpr, String> {
+        if depth > MAX_DEPTH {
+            return Err(String::from("Filter is nested too deeply."));
+        }
+        match self.next() {
+            Some(Token::Not) => Ok(FilterExpr::Not(Box::new(self.parse_unary(depth + 1)?))),
+            Some(Token::Open) => {
+                let expr = self.parse_or(depth + 1)?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(String::from("Missing ')'.")),
+                }
+            }
+            Some(Token::Term(term)) => Ok(FilterExpr::Term(parse_term(term)?)),
+            Some(_) => Err(String::from("Expected a filter term.")),
+            None => Err(String::from("Filter ends too early.")),
+        }
+    }
+}
+
+fn parse_term(term: &str) -> Result<FilterTerm, String> {
+    if let Some(list) = term.strip_prefix('#') {
+        return named_term(list, FilterTerm::List);
+    }
+    if let Some(label) = term.strip_prefix('@') {
+        return named_term(label, FilterTerm::Label);
+    }
+
+    let lower = term.to_lowercase();
+    if let Some((key, value)) = lower.split_once(':') {
+        let value = value.trim();
+        return match key.trim() {
+            "due before" => due_term(value, FilterTerm::DueBefore),
+            "due after" => due_term(value, FilterTerm::DueAfter),
+            "search" if !value.is_empty() => Ok(FilterTerm::Search(value.to_owned())),
+            _ => Err(format!("Unknown filter '{term}'.")),
+        };
+    }
+
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    match words.as_slice() {
+        ["all"] => Ok(FilterTerm::All),
+        ["today"] => Ok(FilterTerm::Today),
+        ["tomorrow"] => Ok(FilterTerm::Tomorrow),
+        ["overdue"] => Ok(FilterTerm::Overdue),
+        ["completed"] => Ok(FilterTerm::Completed),
+        ["recurring"] => Ok(FilterTerm::Recurring),
+        ["subtask"] | ["subtasks"] => Ok(FilterTerm::Subtask),
+        ["p1"] => Ok(FilterTerm::Priority(1)),
+        ["p2"] => Ok(FilterTerm::Priority(2)),
+        ["p3"] => Ok(FilterTerm::Priority(3)),
+        ["p4"] => Ok(FilterTerm::Priority(4)),
+        ["next", days, "days"] | [days, "days"] => days
+            .parse()
+            .ok()
+            .filter(|days| (1..=366).contains(days))
+            .map(FilterTerm::NextDays)
+            .ok_or_else(|| format!("Invalid number of days in '{term}'.")),
+        _ => Err(format!("Unknown filter '{term}'.")),
+    }
+}
+
+fn named_term(name: &str, term: fn(String) -> FilterTerm) -> Result<FilterTerm, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(String::from("Missing a name after '#' or '@'."));
+    }
+    Ok(term(name.to_owned()))
+}
+
+fn due_term(text: &str, term: fn(String) -> FilterTerm) -> Result<FilterTerm, String> {
+    // Any date works here, this only checks that the text can be read at all.
+    if due_date::parse(text, datetime!(2023-01-01 0:00)).is_none() {
+        return Err(format!("Couldn't understand the due date '{text}'."));
+    }
+    Ok(term(text.to_owned()))
+}
+
+struct Compiler<'a> {
+    now: PrimitiveDateTime,
+    timezone: &'a str,
+    first_param: usize,
+    params: Vec<FilterParam>,
+}
+
+impl Compiler<'_> {
+    fn param(&mut self, param: FilterParam) -> String {
+        self.params.push(param);
+        format!("${}", self.first_param + self.params.len() - 1)
+    }
+
+    /// A local time of the user, converted to UTC like the stored due dates.
+    fn local_time(&mut self, local: PrimitiveDateTime) -> String {
+        let local = self.param(FilterParam::Timestamp(local));
+        let timezone = self.param(FilterParam::Text(self.timezone.to_owned()));
+        format!("(({local}::TIMESTAMP AT TIME ZONE {timezone}) AT TIME ZONE 'UTC')")
+    }
+
+    fn due_between(&mut self, start: PrimitiveDateTime, end: PrimitiveDateTime) -> String {
+        let start = self.local_time(start);
+        let end = self.local_time(end);
+        format!("(tasks.due_at >= {start} AND tasks.due_at < {end})")
+    }
+
+    fn due_date(&self, text: &str) -> Result<PrimitiveDateTime, String> {
+        due_date::parse(text, self.now)
+            .map(|due_date| due_date.due_at)
+            .ok_or_else(|| format!("Couldn't understand the due date '{text}'."))
+    }
+
+    fn compile(&mut self, expr: &FilterExpr) -> Result<String, String> {
+        Ok(match expr {
+            FilterExpr::And(a, b) => format!("({} AND {})", self.compile(a)?, self.compile(b)?),
+            FilterExpr::Or(a, b) => format!("({} OR {})", self.compile(a)?, self.compile(b)?),
+            FilterExpr::Not(a) => format!("(NOT {})", self.compile(a)?),
+            FilterExpr::Term(term) => self.compile_term(term)?,
+        })
+    }
+
+    fn compile_term(&mut self, term: &FilterTerm) -> Result<String, String> {
+        let today = self.now.date().with_time(Time::MIDNIGHT);
+        Ok(match term {
+            FilterTerm::All => String::from("TRUE"),
+            FilterTerm::Today => self.due_between(today, today + Duration::days(1)),
+            FilterTerm::Tomorrow => {
+                self.due_between(today + Duration::days(1), today + Duration::days(2))
+            }
+            FilterTerm::Overdue => format!("(tasks.due_at < {})", self.local_time(today)),
+            FilterTerm::NextDays(days) => {
+                self.due_between(today, today + Duration::days(*days as i64))
+            }
+            FilterTerm::DueBefore(text) => {
+                let due_at = self.due_date(text)?;
+                format!("(tasks.due_at < {})", self.local_time(due_at))
+            }
+            FilterTerm::DueAfter(text) => {
+                let due_at = self.due_date(text)?;
+                format!("(tasks.due_at > {})", self.local_time(due_at))
+            }
+            FilterTerm::Completed => String::from("tasks.completed"),
+            FilterTerm::Recurring => String::from("(tasks.recurrence IS NOT NULL)"),
+            FilterTerm::Subtask => String::from("(tasks.parent_id IS NOT NULL)"),
+            FilterTerm::Priority(priority) => {
+                format!(
+                    "(tasks.priority = {})",
+                    self.param(FilterParam::SmallInt(*priority))
+                )
+            }
+            FilterTerm::List(title) => {
+                let title = self.param(FilterParam::Text(title.clone()));
+                format!(
+                    "(tasks.list_id IN (
+                        SELECT id FROM lists WHERE deleted_at IS NULL AND LOWER(title) = LOWER({title})
+                    ))"
+                )
+            }
+            FilterTerm::Label(title) => {
+                let title = self.param(FilterParam::Text(title.clone()));
+                format!(
+                    "(EXISTS (
+                        SELECT 1 FROM task_labels
+                            INNER JOIN labels
+                            ON labels.id = task_labels.label_id
+                        WHERE task_labels.task_id = tasks.id
+                            AND labels.deleted_at IS NULL
+                            AND LOWER(labels.title) = LOWER({title})
+                    ))"
+                )
+            }
+            FilterTerm::Search(text) => {
+                let mut pattern = String::from("%");
+                for c in text.chars() {
+                    if matches!(c, '%' | '_' | '\\') {
+                        pattern.push('\\');
+                    }
+                    pattern.push(c);
+                }
+                pattern.push('%');
+                format!(
+                    "(tasks.title ILIKE {})",
+                    self.param(FilterParam::Text(pattern))
+                )
+            }
+        })
+    }
+}
@@ -8,11 +8,18 @@ use sqlx::postgres::PgConnectOptions;
 pub mod api;
 pub mod config;
 pub mod database;
+pub mod due_date;
+pub mod filter_query;
 pub mod guards;
 pub mod handlers;
 pub mod macros;
 pub mod models;
+pub mod purge;
+pub mod rank;
+pub mod recurrence;
+pub mod reminders;
 pub mod responses;
+pub mod storage;
 pub mod utils;
 pub mod validation;
 
@@ -47,5 +54,8 @@ pub fn create_rocket(app_config: &AppConfig) -> Result<Rocket<Build>, String> {
     rocket = api::mount_rocket(rocket);
     rocket = database::mount_rocket(rocket);
     rocket = handlers::mount_rocket(rocket);
+    rocket = storage::mount_rocket(rocket, storage::from_config(app_config));
+    rocket = reminders::mount_rocket(rocket, reminders::Notifiers::from_config(app_config));
+    rocket = purge::mount_rocket(rocket);
     Ok(rocket)
 }
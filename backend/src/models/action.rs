@@ -9,4 +9,5 @@ pub struct ActionModel {
     pub created_at: PrimitiveDateTime,
     pub action_type: String,
     pub data: serde_json::Value,
+    pub undone_at: Option<PrimitiveDateTime>,
 }
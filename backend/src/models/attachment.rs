@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub struct AttachmentModel {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub storage_key: String,
+    pub created_at: PrimitiveDateTime,
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub struct FilterModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub query: String,
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
 use uuid::Uuid;
 
 #[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
@@ -8,6 +9,8 @@ pub struct LabelModel {
     pub title: String,
     pub description: Option<String>,
     pub color: String,
+    pub deleted_at: Option<PrimitiveDateTime>,
+    pub rank: String,
 }
 
 #[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
 use uuid::Uuid;
 
 #[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
@@ -9,4 +10,7 @@ pub struct ListModel {
     pub title: String,
     pub description: Option<String>,
     pub color: String,
+    pub deleted_at: Option<PrimitiveDateTime>,
+    pub rank: String,
+    pub archived_at: Option<PrimitiveDateTime>,
 }
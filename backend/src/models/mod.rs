@@ -1,8 +1,14 @@
 pub mod action;
+pub mod attachment;
 pub mod discord_user_login;
 pub mod email_user_login;
+pub mod filter;
 pub mod label;
 pub mod list;
+pub mod reminder;
 pub mod session;
 pub mod task;
+pub mod task_comment;
+pub mod template;
+pub mod time_entry;
 pub mod user;
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub struct ReminderModel {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub remind_at: Option<PrimitiveDateTime>,
+    pub offset_minutes: Option<i32>,
+    pub channel: String,
+    pub target: Option<String>,
+    pub delivered_for: Option<PrimitiveDateTime>,
+    pub delivered_at: Option<PrimitiveDateTime>,
+    pub attempts: i32,
+    pub next_attempt_at: Option<PrimitiveDateTime>,
+    pub last_error: Option<String>,
+}
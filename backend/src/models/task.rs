@@ -12,6 +12,13 @@ pub struct TaskModel {
     pub due_at: PrimitiveDateTime,
     pub due_text: String,
     pub completed: bool,
+    pub completed_at: Option<PrimitiveDateTime>,
     pub title: String,
     pub description: Option<String>,
+    pub deleted_at: Option<PrimitiveDateTime>,
+    pub recurrence: Option<String>,
+    pub recurrence_from_completion: bool,
+    pub recurrence_keep_history: bool,
+    pub priority: i16,
+    pub rank: String,
 }
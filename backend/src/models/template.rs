@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub struct TemplateModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub kind: String,
+    pub content: serde_json::Value,
+}
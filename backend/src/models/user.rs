@@ -9,6 +9,7 @@ pub struct UserModel {
     pub username: String,
     pub created_at: PrimitiveDateTime,
     pub updated_at: PrimitiveDateTime,
+    pub timezone: String,
 }
 
 impl UserModel {
@@ -0,0 +1,100 @@
+//! Permanently deletes expired trash from a worker running alongside the
+//! server.
+//!
+//! Reading or changing a user's trash already purges their expired items,
+//! the worker takes care of the users who don't come back. It also deletes
+//! the stored files of attachments that are gone, which the database queues
+//! up in `storage_deletions`.
+
+use rocket::{
+    fairing::AdHoc,
+    tokio::{self, time::MissedTickBehavior},
+    Build, Rocket, Shutdown,
+};
+use rocket_db_pools::Database;
+use sqlx::PgPool;
+
+use crate::{
+    api::trash,
+    config::AppConfig,
+    database::BackendDb,
+    storage::{self, Storage},
+};
+
+/// Most stored files deleted in one go.
+const BATCH_SIZE: i64 = 100;
+
+pub fn mount_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket.attach(AdHoc::on_liftoff("Trash purge worker", |rocket| {
+        Box::pin(async move {
+            let (Some(db), Some(config)) = (BackendDb::fetch(rocket), rocket.state::<AppConfig>())
+            else {
+                error!("Trash purge worker needs the database and the app config.");
+                return;
+            };
+            tokio::spawn(run(
+                db.0.clone(),
+                storage::from_config(config),
+                config.clone(),
+                rocket.shutdown(),
+            ));
+        })
+    }))
+}
+
+async fn run(pool: PgPool, storage: Box<dyn Storage>, config: AppConfig, shutdown: Shutdown) {
+    let mut interval = tokio::time::interval(config.trash_purge_interval.unsigned_abs());
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = interval.tick() => {}
+        }
+        if let Err(e) = purge(&pool, &config).await {
+            warn!("Failed to purge the trash: {}", e);
+        }
+        // A full batch means more files may be waiting
+        loop {
+            match delete_files(&pool, storage.as_ref()).await {
+                Ok(deleted) if deleted == BATCH_SIZE as usize => continue,
+                Ok(_) => break,
+                Err(e) => {
+                    warn!("Failed to delete stored files: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn purge(pool: &PgPool, config: &AppConfig) -> Result<(), String> {
+    let mut db = pool.acquire().await.map_err(|e| e.to_string())?;
+    trash::purge_expired(&mut db, config, None)
+        .await
+        .map_err(|e| format!("{:?}", e))
+}
+
+/// Deletes one batch of queued files and returns how many were deleted.
+/// Files that fail to delete stay queued for the next run.
+async fn delete_files(pool: &PgPool, storage: &dyn Storage) -> Result<usize, sqlx::Error> {
+    let keys = sqlx::query_scalar!(
+        "SELECT storage_key FROM storage_deletions ORDER BY created_at LIMIT $1",
+        BATCH_SIZE
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut deleted = 0;
+    for key in &keys {
+        if let Err(e) = storage.delete(key).await {
+            warn!("Failed to delete stored file {}: {}", key, e);
+            continue;
+        }
+        sqlx::query!("DELETE FROM storage_deletions WHERE storage_key = $1", key)
+            .execute(pool)
+            .await?;
+        deleted += 1;
+    }
+    Ok(deleted)
+}
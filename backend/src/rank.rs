@@ -0,0 +1,77 @@
+/// Digits of a rank, in the same order as their bytes so ranks
+/// compare like plain strings (with the `C` collation).
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Returns a rank that sorts strictly between `before` and `after`,
+/// `None` standing for the start and the end of the siblings.
+///
+/// Ranks are base 62 fractions (`"V"` is one half), so there is
+/// always room for another one and moving an item never renumbers the
+/// others. Ranks never end in `'0'`, which keeps room before them too.
+///
+/// Returns `None` if a rank contains other characters or `before`
+/// doesn't sort before `after`.
+pub fn between(before: Option<&str>, after: Option<&str>) -> Option<String> {
+    let before = match before {
+        Some(before) => digits(before)?,
+        None => vec![],
+    };
+    let after = match after {
+        Some(after) => Some(digits(after)?),
+        None => None,
+    };
+    if before.last() == Some(&0) || after.as_ref().and_then(|after| after.last()) == Some(&0) {
+        return None;
+    }
+    if let Some(after) = &after {
+        if after.is_empty() || before >= *after {
+            return None;
+        }
+    }
+
+    let rank = midpoint(&before, after.as_deref());
+    Some(
+        rank.into_iter()
+            .map(|digit| DIGITS[digit] as char)
+            .collect(),
+    )
+}
+
+fn digits(rank: &str) -> Option<Vec<usize>> {
+    rank.bytes()
+        .map(|byte| DIGITS.iter().position(|digit| *digit == byte))
+        .collect()
+}
+
+/// `before` is read as zero when empty and `after` as one when missing.
+fn midpoint(before: &[usize], after: Option<&[usize]>) -> Vec<usize> {
+    if let Some(after) = after {
+        // Digits shared by both bounds are kept as they are
+        let shared = after
+            .iter()
+            .enumerate()
+            .take_while(|(i, digit)| before.get(*i).copied().unwrap_or(0) == **digit)
+            .count();
+        if shared > 0 {
+            let mut rank = after[..shared].to_vec();
+            rank.extend(midpoint(
+                before.get(shared..).unwrap_or_default(),
+                Some(&after[shared..]),
+            ));
+            return rank;
+        }
+    }
+
+    let low = before.first().copied().unwrap_or(0);
+    let high = after.map_or(DIGITS.len(), |after| after[0]);
+    if high - low > 1 {
+        vec![(low + high) / 2]
+    } else if after.is_some_and(|after| after.len() > 1) {
+        // `after` has more digits, so its first digit alone sorts before it
+        vec![high]
+    } else {
+        let mut rank = vec![low];
+        rank.extend(midpoint(before.get(1..).unwrap_or_default(), None));
+        rank
+    }
+}
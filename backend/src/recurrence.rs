@@ -0,0 +1,449 @@
+use std::{fmt, str::FromStr};
+
+use time::{
+    format_description::FormatItem, macros::format_description, Date, Duration, Month,
+    PrimitiveDateTime, Weekday,
+};
+
+/// How many periods are searched for the next occurrence before giving up,
+/// which only happens for rules that can never match (e.g. February 30th).
+const MAX_PERIODS: i64 = 2000;
+
+const UNTIL_DATE_TIME_FORMAT: &[FormatItem] =
+    format_description!("[year][month][day]T[hour][minute][second]");
+const UNTIL_DATE_FORMAT: &[FormatItem] = format_description!("[year][month][day]");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A recurrence rule following RFC 5545 (`FREQ=WEEKLY;BYDAY=MO,WE`).
+///
+/// Supports the DAILY, WEEKLY, MONTHLY and YEARLY frequencies together with
+/// INTERVAL, COUNT, UNTIL, BYDAY, BYMONTHDAY, BYMONTH, BYSETPOS and WKST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<PrimitiveDateTime>,
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u8>,
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+}
+
+impl RRule {
+    /// Returns the first occurrence strictly after `dtstart`,
+    /// treating `dtstart` as the first occurrence of the series.
+    pub fn next_after(&self, dtstart: PrimitiveDateTime) -> Option<PrimitiveDateTime> {
+        if self.count == Some(1) {
+            return None;
+        }
+
+        for period in 0..MAX_PERIODS {
+            let mut dates = self.period_dates(dtstart.date(), period)?;
+            dates.sort();
+            dates.dedup();
+            if !self.by_set_pos.is_empty() {
+                dates = select_positions(&dates, &self.by_set_pos);
+            }
+
+            for date in dates {
+                let occurrence = date.with_time(dtstart.time());
+                if occurrence <= dtstart {
+                    continue;
+                }
+                if matches!(self.until, Some(until) if occurrence > until) {
+                    return None;
+                }
+                return Some(occurrence);
+            }
+        }
+        None
+    }
+
+    /// The rule to continue with after an occurrence has been used up.
+    pub fn advanced(&self) -> RRule {
+        RRule {
+            count: self.count.map(|count| count.saturating_sub(1)),
+            ..self.clone()
+        }
+    }
+
+    fn period_dates(&self, start: Date, period: i64) -> Option<Vec<Date>> {
+        let step = period * self.interval as i64;
+        let dates = match self.freq {
+            Frequency::Daily => {
+                let date = start.checked_add(Duration::days(step))?;
+                vec![date]
+                    .into_iter()
+                    .filter(|date| self.matches_month_day(*date) && self.matches_weekday(*date))
+                    .collect()
+            }
+            Frequency::Weekly => {
+                let offset = days_between(self.week_start, start.weekday());
+                let week = start.checked_add(Duration::days(step * 7 - offset))?;
+                if self.by_day.is_empty() {
+                    vec![week.checked_add(Duration::days(offset))?]
+                } else {
+                    (0..7)
+                        .filter_map(|day| week.checked_add(Duration::days(day)))
+                        .filter(|date| self.matches_weekday(*date))
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let (year, month) = add_months(start.year(), start.month(), step)?;
+                self.month_dates(year, month, start.day())
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(step.try_into().ok()?)?;
+                if !self.by_month.is_empty() {
+                    self.by_month
+                        .iter()
+                        .filter_map(|month| Month::try_from(*month).ok())
+                        .flat_map(|month| self.month_dates(year, month, start.day()))
+                        .collect()
+                } else if !self.by_month_day.is_empty() {
+                    (1..=12)
+                        .filter_map(|month| Month::try_from(month).ok())
+                        .flat_map(|month| self.month_dates(year, month, start.day()))
+                        .collect()
+                } else if !self.by_day.is_empty() {
+                    let first = Date::from_calendar_date(year, Month::January, 1).ok()?;
+                    let last = Date::from_calendar_date(year, Month::December, 31).ok()?;
+                    expand_by_day(first, last, &self.by_day)
+                } else {
+                    Date::from_calendar_date(year, start.month(), start.day())
+                        .into_iter()
+                        .collect()
+                }
+            }
+        };
+
+        Some(
+            dates
+                .into_iter()
+                .filter(|date| {
+                    self.by_month.is_empty() || self.by_month.contains(&(date.month() as u8))
+                })
+                .collect(),
+        )
+    }
+
+    fn month_dates(&self, year: i32, month: Month, default_day: u8) -> Vec<Date> {
+        let days_in_month = time::util::days_in_year_month(year, month);
+        let (Ok(first), Ok(last)) = (
+            Date::from_calendar_date(year, month, 1),
+            Date::from_calendar_date(year, month, days_in_month),
+        ) else {
+            return vec![];
+        };
+
+        if !self.by_month_day.is_empty() {
+            let by_day = expand_by_day(first, last, &self.by_day);
+            self.by_month_day
+                .iter()
+                .filter_map(|day| resolve_month_day(*day, days_in_month))
+                .filter_map(|day| Date::from_calendar_date(year, month, day).ok())
+                .filter(|date| self.by_day.is_empty() || by_day.contains(date))
+                .collect()
+        } else if !self.by_day.is_empty() {
+            expand_by_day(first, last, &self.by_day)
+        } else {
+            Date::from_calendar_date(year, month, default_day)
+                .into_iter()
+                .collect()
+        }
+    }
+
+    fn matches_month_day(&self, date: Date) -> bool {
+        let days_in_month = time::util::days_in_year_month(date.year(), date.month());
+        self.by_month_day.is_empty()
+            || self
+                .by_month_day
+                .iter()
+                .any(|day| resolve_month_day(*day, days_in_month) == Some(date.day()))
+    }
+
+    fn matches_weekday(&self, date: Date) -> bool {
+        self.by_day.is_empty()
+            || self
+                .by_day
+                .iter()
+                .any(|(_, weekday)| *weekday == date.weekday())
+    }
+}
+
+impl FromStr for RRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("RRULE:").unwrap_or(s);
+
+        let mut freq = None;
+        let mut rule = RRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: vec![],
+            by_month_day: vec![],
+            by_month: vec![],
+            by_set_pos: vec![],
+            week_start: Weekday::Monday,
+        };
+
+        for part in s.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid rule part '{}'.", part))?;
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Unsupported frequency '{}'.", value)),
+                    })
+                }
+                "INTERVAL" => rule.interval = parse_number(name, value, 1, u32::MAX)?,
+                "COUNT" => rule.count = Some(parse_number(name, value, 1, u32::MAX)?),
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = parse_list(name, value, 1, 31)?;
+                }
+                "BYMONTH" => {
+                    rule.by_month = value
+                        .split(',')
+                        .map(|month| parse_number(name, month, 1, 12))
+                        .collect::<Result<_, _>>()?
+                }
+                "BYSETPOS" => rule.by_set_pos = parse_list(name, value, 1, 366)?,
+                "WKST" => rule.week_start = parse_weekday(value)?,
+                _ => return Err(format!("Unsupported rule part '{}'.", name)),
+            }
+        }
+
+        rule.freq = freq.ok_or("Rule is missing FREQ.")?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err(String::from("COUNT and UNTIL can't be used together."));
+        }
+        if matches!(rule.freq, Frequency::Daily | Frequency::Weekly)
+            && rule.by_day.iter().any(|(ordinal, _)| ordinal.is_some())
+        {
+            return Err(String::from(
+                "Numbered BYDAY values are only allowed in MONTHLY and YEARLY rules.",
+            ));
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for RRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", freq)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            let until = until
+                .format(UNTIL_DATE_TIME_FORMAT)
+                .map_err(|_| fmt::Error)?;
+            write!(f, ";UNTIL={}Z", until)?;
+        }
+        if !self.by_day.is_empty() {
+            let by_day: Vec<_> = self
+                .by_day
+                .iter()
+                .map(|(ordinal, weekday)| {
+                    let weekday = weekday_code(*weekday);
+                    match ordinal {
+                        Some(ordinal) => format!("{}{}", ordinal, weekday),
+                        None => weekday.to_owned(),
+                    }
+                })
+                .collect();
+            write!(f, ";BYDAY={}", by_day.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            write!(f, ";BYMONTHDAY={}", join_numbers(&self.by_month_day))?;
+        }
+        if !self.by_month.is_empty() {
+            write!(f, ";BYMONTH={}", join_numbers(&self.by_month))?;
+        }
+        if !self.by_set_pos.is_empty() {
+            write!(f, ";BYSETPOS={}", join_numbers(&self.by_set_pos))?;
+        }
+        if self.week_start != Weekday::Monday {
+            write!(f, ";WKST={}", weekday_code(self.week_start))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_number<T: FromStr + PartialOrd>(
+    name: &str,
+    value: &str,
+    min: T,
+    max: T,
+) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .ok()
+        .filter(|number| *number >= min && *number <= max)
+        .ok_or_else(|| format!("Invalid {} value '{}'.", name, value))
+}
+
+/// Parses a list of non-zero numbers that may count from the end.
+fn parse_list(name: &str, value: &str, min: i32, max: i32) -> Result<Vec<i32>, String> {
+    value
+        .split(',')
+        .map(|number| {
+            let number = number.trim_start_matches('+');
+            match number.strip_prefix('-') {
+                Some(number) => parse_number(name, number, min, max).map(|n| -n),
+                None => parse_number(name, number, min, max),
+            }
+        })
+        .collect()
+}
+
+fn parse_until(value: &str) -> Result<PrimitiveDateTime, String> {
+    let value = value.trim_end_matches('Z');
+    PrimitiveDateTime::parse(value, UNTIL_DATE_TIME_FORMAT)
+        .or_else(|_| {
+            Date::parse(value, UNTIL_DATE_FORMAT).map(|date| date.with_hms(23, 59, 59).unwrap())
+        })
+        .map_err(|_| format!("Invalid UNTIL value '{}'.", value))
+}
+
+fn parse_by_day(value: &str) -> Result<(Option<i32>, Weekday), String> {
+    if value.len() < 2 {
+        return Err(format!("Invalid BYDAY value '{}'.", value));
+    }
+    let (ordinal, weekday) = value.split_at(value.len() - 2);
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        Some(
+            parse_list("BYDAY", ordinal, 1, 53)?
+                .pop()
+                .ok_or_else(|| format!("Invalid BYDAY value '{}'.", value))?,
+        )
+    };
+    Ok((ordinal, parse_weekday(weekday)?))
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Monday),
+        "TU" => Ok(Weekday::Tuesday),
+        "WE" => Ok(Weekday::Wednesday),
+        "TH" => Ok(Weekday::Thursday),
+        "FR" => Ok(Weekday::Friday),
+        "SA" => Ok(Weekday::Saturday),
+        "SU" => Ok(Weekday::Sunday),
+        _ => Err(format!("Invalid weekday '{}'.", value)),
+    }
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Monday => "MO",
+        Weekday::Tuesday => "TU",
+        Weekday::Wednesday => "WE",
+        Weekday::Thursday => "TH",
+        Weekday::Friday => "FR",
+        Weekday::Saturday => "SA",
+        Weekday::Sunday => "SU",
+    }
+}
+
+fn join_numbers<T: ToString>(numbers: &[T]) -> String {
+    numbers
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn days_between(from: Weekday, to: Weekday) -> i64 {
+    (to.number_days_from_monday() as i64 - from.number_days_from_monday() as i64).rem_euclid(7)
+}
+
+fn add_months(year: i32, month: Month, months: i64) -> Option<(i32, Month)> {
+    let total = year as i64 * 12 + (month as i64 - 1) + months;
+    let year = total.div_euclid(12).try_into().ok()?;
+    let month = Month::try_from((total.rem_euclid(12) + 1) as u8).ok()?;
+    Some((year, month))
+}
+
+fn resolve_month_day(day: i32, days_in_month: u8) -> Option<u8> {
+    let day = if day < 0 {
+        days_in_month as i32 + day + 1
+    } else {
+        day
+    };
+    (1..=days_in_month as i32)
+        .contains(&day)
+        .then_some(day as u8)
+}
+
+/// All dates between `first` and `last` matching the BYDAY values, where
+/// numbered values like `-1FR` pick a single match counted within the range.
+fn expand_by_day(first: Date, last: Date, by_day: &[(Option<i32>, Weekday)]) -> Vec<Date> {
+    let mut dates = vec![];
+    for (ordinal, weekday) in by_day {
+        let offset = days_between(first.weekday(), *weekday);
+        let matching: Vec<Date> = (0..)
+            .map_while(|week| first.checked_add(Duration::days(offset + week * 7)))
+            .take_while(|date| *date <= last)
+            .collect();
+        match ordinal {
+            Some(ordinal) => dates.extend(select_positions(&matching, &[*ordinal])),
+            None => dates.extend(matching),
+        }
+    }
+    dates
+}
+
+fn select_positions(dates: &[Date], positions: &[i32]) -> Vec<Date> {
+    positions
+        .iter()
+        .filter_map(|position| {
+            let index = if *position > 0 {
+                *position as i64 - 1
+            } else {
+                dates.len() as i64 + *position as i64
+            };
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| dates.get(index))
+        })
+        .copied()
+        .collect()
+}
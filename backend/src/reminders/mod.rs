@@ -0,0 +1,229 @@
+//! Delivers task reminders from a worker running alongside the server.
+//!
+//! Every poll the worker claims the reminders that are due with
+//! `FOR UPDATE SKIP LOCKED` and leases them by pushing `next_attempt_at`
+//! out, so several backend processes can share the same database without
+//! delivering a reminder twice. Deliveries run after the claim is committed,
+//! and a reminder whose worker died mid-delivery is retried once its lease
+//! runs out.
+
+use rocket::{
+    fairing::AdHoc,
+    tokio::{
+        self,
+        time::{timeout, MissedTickBehavior},
+    },
+    Build, Rocket, Shutdown,
+};
+use rocket_db_pools::Database;
+use sqlx::PgPool;
+use time::PrimitiveDateTime;
+use uuid::Uuid;
+
+use crate::{config::AppConfig, database::BackendDb};
+
+use self::notifiers::DELIVERY_TIMEOUT;
+pub use self::notifiers::{Notifier, Notifiers};
+
+pub mod notifiers;
+
+/// Most reminders claimed by one transaction.
+const BATCH_SIZE: i64 = 50;
+
+/// How long claimed reminders are kept from other workers, long enough to
+/// deliver a whole batch one after another.
+const LEASE_SECONDS: f64 = DELIVERY_TIMEOUT.as_secs_f64() * (BATCH_SIZE + 1) as f64;
+
+/// A reminder that is due, along with the task it is about.
+#[derive(Debug, Clone)]
+pub struct DueReminder {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub due_at: Option<PrimitiveDateTime>,
+    pub fire_at: PrimitiveDateTime,
+    pub channel: String,
+    pub target: Option<String>,
+    pub attempts: i32,
+}
+
+impl DueReminder {
+    /// Identifies this firing of the reminder. Retries keep the same key,
+    /// so receivers can drop a notification they have already seen.
+    pub fn delivery_key(&self) -> String {
+        format!("{}:{}", self.id, self.fire_at.assume_utc().unix_timestamp())
+    }
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>, notifiers: Notifiers) -> Rocket<Build> {
+    rocket.attach(AdHoc::on_liftoff("Reminder worker", |rocket| {
+        Box::pin(async move {
+            let (Some(db), Some(config)) = (BackendDb::fetch(rocket), rocket.state::<AppConfig>())
+            else {
+                error!("Reminder worker needs the database and the app config.");
+                return;
+            };
+            tokio::spawn(run(
+                db.0.clone(),
+                notifiers,
+                config.clone(),
+                rocket.shutdown(),
+            ));
+        })
+    }))
+}
+
+async fn run(pool: PgPool, notifiers: Notifiers, config: AppConfig, shutdown: Shutdown) {
+    let mut interval = tokio::time::interval(config.reminder_poll_interval.unsigned_abs());
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = interval.tick() => {}
+        }
+        // A full batch means more reminders may be waiting
+        loop {
+            match deliver_due(&pool, &notifiers, &config).await {
+                Ok(claimed) if claimed == BATCH_SIZE as usize => continue,
+                Ok(_) => break,
+                Err(e) => {
+                    warn!("Failed to deliver reminders: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Delivers one batch of due reminders and returns how many were claimed.
+///
+/// A reminder fires at `remind_at`, or `offset_minutes` after the task's
+/// due date, and is done once `delivered_for` holds that time. Moving the
+/// due date therefore makes a relative reminder fire again.
+pub async fn deliver_due(
+    pool: &PgPool,
+    notifiers: &Notifiers,
+    config: &AppConfig,
+) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let due = sqlx::query_as!(
+        DueReminder,
+        r#"
+        SELECT
+            reminders.id,
+            tasks.id AS task_id,
+            lists.user_id,
+            tasks.title,
+            tasks.due_at AS "due_at?",
+            fire.at AS "fire_at!",
+            reminders.channel,
+            reminders.target,
+            reminders.attempts
+            FROM reminders
+            INNER JOIN tasks ON tasks.id = reminders.task_id
+            INNER JOIN lists ON lists.id = tasks.list_id
+            CROSS JOIN LATERAL (
+                SELECT COALESCE(
+                    reminders.remind_at,
+                    tasks.due_at + reminders.offset_minutes * INTERVAL '1 minute'
+                ) AS at
+            ) fire
+        WHERE tasks.deleted_at IS NULL
+            AND lists.archived_at IS NULL
+            AND NOT tasks.completed
+            AND fire.at <= CURRENT_TIMESTAMP AT TIME ZONE 'UTC'
+            AND reminders.delivered_for IS DISTINCT FROM fire.at
+            AND (reminders.next_attempt_at IS NULL
+                OR reminders.next_attempt_at <= CURRENT_TIMESTAMP AT TIME ZONE 'UTC')
+        ORDER BY fire.at
+        LIMIT $1
+        FOR UPDATE OF reminders SKIP LOCKED
+        "#,
+        BATCH_SIZE
+    )
+    .fetch_all(&mut tx)
+    .await?;
+    let ids: Vec<Uuid> = due.iter().map(|reminder| reminder.id).collect();
+    sqlx::query!(
+        "UPDATE reminders SET
+            next_attempt_at = CURRENT_TIMESTAMP AT TIME ZONE 'UTC' + $2 * INTERVAL '1 second'
+            WHERE id = ANY($1)",
+        &ids,
+        LEASE_SECONDS
+    )
+    .execute(&mut tx)
+    .await?;
+    tx.commit().await?;
+
+    // Each outcome is saved on its own, if saving fails the rest of the
+    // batch is retried once the lease runs out
+    for reminder in &due {
+        let result = match notifiers.get(&reminder.channel) {
+            // Bounds every delivery, so the batch fits in its lease
+            Some(notifier) => timeout(DELIVERY_TIMEOUT, notifier.notify(reminder))
+                .await
+                .unwrap_or_else(|_| Err(String::from("Timed out delivering reminder."))),
+            None => Err(format!("No notifier for channel '{}'.", reminder.channel)),
+        };
+        match result {
+            Ok(()) => {
+                sqlx::query!(
+                    "UPDATE reminders SET
+                        delivered_for = $2,
+                        delivered_at = CURRENT_TIMESTAMP AT TIME ZONE 'UTC',
+                        attempts = 0,
+                        next_attempt_at = NULL,
+                        last_error = NULL
+                        WHERE id = $1",
+                    reminder.id,
+                    reminder.fire_at
+                )
+                .execute(pool)
+                .await?;
+            }
+            Err(error) => {
+                warn!("Failed to deliver reminder {}: {}", reminder.id, error);
+                let attempts = reminder.attempts + 1;
+                if attempts >= config.reminder_max_attempts {
+                    // Gives up on this firing, the next one starts over
+                    sqlx::query!(
+                        "UPDATE reminders SET
+                            delivered_for = $2,
+                            delivered_at = NULL,
+                            attempts = 0,
+                            next_attempt_at = NULL,
+                            last_error = $3
+                            WHERE id = $1",
+                        reminder.id,
+                        reminder.fire_at,
+                        error
+                    )
+                    .execute(pool)
+                    .await?;
+                } else {
+                    let delay = config.reminder_retry_delay.as_seconds_f64()
+                        * 2f64.powi(attempts.min(16) - 1);
+                    sqlx::query!(
+                        "UPDATE reminders SET
+                            attempts = $2,
+                            next_attempt_at = CURRENT_TIMESTAMP AT TIME ZONE 'UTC'
+                                + $3 * INTERVAL '1 second',
+                            last_error = $4
+                            WHERE id = $1",
+                        reminder.id,
+                        attempts,
+                        delay,
+                        error
+                    )
+                    .execute(pool)
+                    .await?;
+                }
+            }
+        }
+    }
+
+    Ok(due.len())
+}
@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use reqwest::{redirect::Policy, Url};
+use rocket::tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{lookup_host, TcpStream},
+    time::timeout,
+};
+use serde_json::json;
+use time::format_description::well_known::Iso8601;
+
+use super::DueReminder;
+use crate::config::AppConfig;
+
+/// How long a single delivery may take before it counts as failed.
+pub const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends reminders over one channel.
+#[rocket::async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, reminder: &DueReminder) -> Result<(), String>;
+}
+
+/// Notifiers by the name of the channel they deliver.
+#[derive(Default)]
+pub struct Notifiers(HashMap<String, Box<dyn Notifier>>);
+
+impl Notifiers {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let mut notifiers = Self::default();
+        notifiers.register("log", LogNotifier);
+        notifiers.register("email", EmailNotifier::new(config));
+        notifiers.register("webhook", WebhookNotifier::new(config));
+        notifiers
+    }
+
+    pub fn register(&mut self, channel: &str, notifier: impl Notifier + 'static) {
+        self.0.insert(String::from(channel), Box::new(notifier));
+    }
+
+    pub fn get(&self, channel: &str) -> Option<&dyn Notifier> {
+        self.0.get(channel).map(|notifier| notifier.as_ref())
+    }
+}
+
+fn format_time(time: time::PrimitiveDateTime) -> String {
+    time.assume_utc()
+        .format(&Iso8601::DEFAULT)
+        .unwrap_or_default()
+}
+
+fn message(reminder: &DueReminder) -> String {
+    match reminder.due_at {
+        Some(due_at) => format!(
+            "Reminder: '{}' is due at {}.",
+            reminder.title,
+            format_time(due_at)
+        ),
+        None => format!("Reminder: '{}'.", reminder.title),
+    }
+}
+
+/// Writes reminders to the server log.
+pub struct LogNotifier;
+
+#[rocket::async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, reminder: &DueReminder) -> Result<(), String> {
+        info!(
+            "{} (task {}, user {})",
+            message(reminder),
+            reminder.task_id,
+            reminder.user_id
+        );
+        Ok(())
+    }
+}
+
+/// Sends reminders as plain text emails to `target` through an SMTP
+/// server without authentication, like a local relay.
+pub struct EmailNotifier {
+    address: String,
+    from: String,
+}
+
+impl EmailNotifier {
+    pub fn new(config: &AppConfig) -> Self {
+        EmailNotifier {
+            address: config.smtp_address.clone(),
+            from: config.smtp_from.clone(),
+        }
+    }
+
+    async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body: &str,
+        message_id: &str,
+    ) -> Result<(), String> {
+        let stream = TcpStream::connect(&self.address)
+            .await
+            .map_err(|e| format!("Failed to connect to SMTP server: {}", e))?;
+        let mut stream = BufReader::new(stream);
+
+        expect_reply(&mut stream, 220).await?;
+        command(&mut stream, "HELO localhost", 250).await?;
+        command(&mut stream, &format!("MAIL FROM:<{}>", self.from), 250).await?;
+        command(&mut stream, &format!("RCPT TO:<{}>", to), 250).await?;
+        command(&mut stream, "DATA", 354).await?;
+
+        let mut data = format!(
+            "From: <{}>\r\nTo: <{}>\r\nSubject: {}\r\nMessage-ID: <{}>\r\n\
+                Content-Type: text/plain; charset=utf-8\r\n\r\n",
+            self.from,
+            to,
+            header_value(subject),
+            message_id
+        );
+        for line in body.lines() {
+            // Lines starting with a dot would otherwise end the message early
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(line);
+            data.push_str("\r\n");
+        }
+        data.push_str(".\r\n");
+        stream
+            .write_all(data.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send email: {}", e))?;
+        expect_reply(&mut stream, 250).await?;
+
+        command(&mut stream, "QUIT", 221).await
+    }
+}
+
+fn header_value(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+async fn command(stream: &mut BufReader<TcpStream>, line: &str, code: u16) -> Result<(), String> {
+    stream
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send SMTP command: {}", e))?;
+    expect_reply(stream, code).await
+}
+
+/// Reads a reply, which spans several lines while they read `250-...`.
+async fn expect_reply(stream: &mut BufReader<TcpStream>, code: u16) -> Result<(), String> {
+    loop {
+        let mut line = String::new();
+        stream
+            .read_line(&mut line)
+            .await
+            .map_err(|e| format!("Failed to read SMTP reply: {}", e))?;
+        if line.len() < 4 || !line.starts_with(&code.to_string()) {
+            return Err(format!("Unexpected SMTP reply: {}", line.trim_end()));
+        }
+        if line.as_bytes()[3] != b'-' {
+            return Ok(());
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Notifier for EmailNotifier {
+    async fn notify(&self, reminder: &DueReminder) -> Result<(), String> {
+        let Some(to) = &reminder.target else {
+            return Err(String::from("Email reminder has no address."));
+        };
+        let message_id = format!("{}@toasttask", reminder.delivery_key().replace(':', "."));
+        timeout(
+            DELIVERY_TIMEOUT,
+            self.send(
+                to,
+                &format!("Reminder: {}", reminder.title),
+                &message(reminder),
+                &message_id,
+            ),
+        )
+        .await
+        .map_err(|_| String::from("Timed out sending email."))?
+    }
+}
+
+/// Posts reminders as json to the `target` url. Retries send the same
+/// `Idempotency-Key` header.
+///
+/// The host is resolved and checked again on every delivery, and the
+/// request goes to the checked address, so a host that has been pointed at
+/// a private address since the reminder was created is refused.
+pub struct WebhookNotifier {
+    allowed_hosts: Vec<String>,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: &AppConfig) -> Self {
+        WebhookNotifier {
+            allowed_hosts: config.webhook_allowed_hosts.clone(),
+        }
+    }
+}
+
+/// Resolves the host of a webhook url, refusing loopback, private,
+/// link-local and unspecified addresses unless the host is allowed.
+pub async fn resolve_webhook(url: &Url, allowed_hosts: &[String]) -> Result<SocketAddr, String> {
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return Err(String::from("Webhook url has no host."));
+    };
+    let ip = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = match ip.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => timeout(DELIVERY_TIMEOUT, lookup_host((host, port)))
+            .await
+            .map_err(|_| String::from("Timed out resolving webhook host."))?
+            .map_err(|e| format!("Failed to resolve webhook host: {}", e))?
+            .collect(),
+    };
+    let allowed = allowed_hosts
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host));
+    // Every address has to pass, the client may connect to any of them
+    if !allowed {
+        if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+            return Err(format!(
+                "Webhook host resolves to the non-public address {}.",
+                addr.ip()
+            ));
+        }
+    }
+    addrs
+        .first()
+        .copied()
+        .ok_or_else(|| String::from("Webhook host has no addresses."))
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || first == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(ip.into()),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local, fc00::/7
+                    || first & 0xfe00 == 0xfc00
+                    // Link-local, fe80::/10
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+#[rocket::async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, reminder: &DueReminder) -> Result<(), String> {
+        let Some(url) = &reminder.target else {
+            return Err(String::from("Webhook reminder has no url."));
+        };
+        let body = json!({
+            "reminder_id": reminder.id,
+            "task_id": reminder.task_id,
+            "title": reminder.title,
+            "due_at": reminder.due_at.map(format_time),
+            "fire_at": format_time(reminder.fire_at),
+            "message": message(reminder),
+        });
+        let url = Url::parse(url).map_err(|e| format!("Invalid webhook url: {}", e))?;
+        let addr = resolve_webhook(&url, &self.allowed_hosts).await?;
+        // Pins the checked address and doesn't follow redirects elsewhere
+        let client = reqwest::Client::builder()
+            .resolve(url.host_str().unwrap_or_default(), addr)
+            .redirect(Policy::none())
+            .build()
+            .map_err(|e| format!("Failed to build webhook client: {}", e))?;
+        let response = client
+            .post(url)
+            .header("Idempotency-Key", reminder.delivery_key())
+            .json(&body)
+            .timeout(DELIVERY_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to call webhook: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Webhook answered with {}.", response.status()));
+        }
+        Ok(())
+    }
+}
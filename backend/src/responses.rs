@@ -218,6 +218,7 @@ api_response! {
     unauthorized(Status::Unauthorized),
     not_found(Status::NotFound),
     forbidden(Status::Forbidden),
+    conflict(Status::Conflict),
     unprocessable_entity(Status::UnprocessableEntity),
     ok(Status::Ok),
     created(Status::Created),
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use rocket::{
+    futures::{stream, StreamExt},
+    tokio::{fs, io::AsyncReadExt},
+};
+use uuid::Uuid;
+
+use super::{valid_key, ByteChunks, Storage};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Stores files in a directory, one file per key.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> Self {
+        LocalStorage { root }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, String> {
+        if !valid_key(key) {
+            return Err(format!("Invalid storage key '{}'.", key));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[rocket::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> Result<(), String> {
+        let path = self.path(key)?;
+        let dir = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Failed to create storage directory: {}", e))?;
+        // Written next to the target first so readers never see half a file
+        let partial = dir.join(format!(".{}.partial", Uuid::new_v4()));
+        fs::write(&partial, bytes)
+            .await
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        fs::rename(&partial, &path)
+            .await
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+
+    async fn get(&self, key: &str) -> Result<ByteChunks, String> {
+        let file = fs::File::open(self.path(key)?)
+            .await
+            .map_err(|e| format!("Failed to open file: {}", e))?;
+        Ok(stream::unfold(file, |mut file| async move {
+            let mut chunk = vec![0; CHUNK_SIZE];
+            match file.read(&mut chunk).await {
+                Ok(0) => None,
+                Ok(read) => {
+                    chunk.truncate(read);
+                    Some((chunk, file))
+                }
+                Err(e) => {
+                    warn!("Failed to read file: {}", e);
+                    None
+                }
+            }
+        })
+        .boxed())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        match fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to delete file: {}", e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Keeps the contents of attachments, the database only has their metadata.
+
+use rocket::{futures::stream::BoxStream, Build, Rocket};
+
+use crate::config::{AppConfig, StorageConfig};
+
+pub use self::{local::LocalStorage, s3::S3Storage};
+
+mod local;
+mod s3;
+
+/// Contents of a stored file, read in chunks.
+pub type ByteChunks = BoxStream<'static, Vec<u8>>;
+
+/// Stores files by key. Keys are made of `[A-Za-z0-9-]` segments
+/// separated by `/`.
+#[rocket::async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), String>;
+
+    async fn get(&self, key: &str) -> Result<ByteChunks, String>;
+
+    /// Deleting a key that doesn't exist succeeds.
+    async fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+pub fn from_config(config: &AppConfig) -> Box<dyn Storage> {
+    match &config.storage {
+        StorageConfig::Local { root } => Box::new(LocalStorage::new(root.clone())),
+        StorageConfig::S3 {
+            endpoint,
+            bucket,
+            region,
+            access_key,
+            secret_key,
+        } => Box::new(S3Storage::new(
+            endpoint, bucket, region, access_key, secret_key,
+        )),
+    }
+}
+
+pub fn mount_rocket(rocket: Rocket<Build>, storage: Box<dyn Storage>) -> Rocket<Build> {
+    rocket.manage(storage)
+}
+
+fn valid_key(key: &str) -> bool {
+    key.split('/').all(|segment| {
+        !segment.is_empty()
+            && segment
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+    })
+}
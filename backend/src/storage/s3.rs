@@ -0,0 +1,146 @@
+use hmac::{Hmac, Mac};
+use reqwest::{Method, RequestBuilder, Url};
+use rocket::futures::{stream, StreamExt};
+use sha2::{Digest, Sha256};
+use time::{macros::format_description, OffsetDateTime};
+
+use super::{valid_key, ByteChunks, Storage};
+
+/// Stores files in a bucket of an S3 compatible service, signing
+/// requests with AWS signature version 4.
+pub struct S3Storage {
+    client: reqwest::Client,
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl S3Storage {
+    pub fn new(
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        access_key: &str,
+        secret_key: &str,
+    ) -> Self {
+        S3Storage {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_owned(),
+            bucket: bucket.to_owned(),
+            region: region.to_owned(),
+            access_key: access_key.to_owned(),
+            secret_key: secret_key.to_owned(),
+        }
+    }
+
+    fn request(&self, method: Method, key: &str, body: Vec<u8>) -> Result<RequestBuilder, String> {
+        if !valid_key(key) {
+            return Err(format!("Invalid storage key '{}'.", key));
+        }
+        let url = Url::parse(&format!("{}/{}/{}", self.endpoint, self.bucket, key))
+            .map_err(|e| format!("Invalid storage url: {}", e))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_owned(),
+            _ => return Err(String::from("Storage url has no host.")),
+        };
+
+        let now = OffsetDateTime::now_utc();
+        let date_time = now
+            .format(format_description!(
+                "[year][month][day]T[hour][minute][second]Z"
+            ))
+            .map_err(|e| e.to_string())?;
+        let date = &date_time[..8];
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        // Keys only use characters that need no percent encoding
+        let canonical_request = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\n\
+                x-amz-date:{date_time}\n\n{SIGNED_HEADERS}\n{payload_hash}",
+            path = url.path(),
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            date_time,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut signing_key = hmac(
+            format!("AWS4{}", self.secret_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            signing_key = hmac(&signing_key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac(&signing_key, string_to_sign.as_bytes()));
+
+        Ok(self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", &date_time)
+            .header(
+                "authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.access_key, scope, SIGNED_HEADERS, signature
+                ),
+            )
+            .body(body))
+    }
+}
+
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+async fn send(request: RequestBuilder) -> Result<reqwest::Response, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach storage: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Storage answered with {}.", response.status()));
+    }
+    Ok(response)
+}
+
+#[rocket::async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), String> {
+        let request = self
+            .request(Method::PUT, key, bytes)?
+            .header("content-type", content_type);
+        send(request).await.map(|_| ())
+    }
+
+    async fn get(&self, key: &str) -> Result<ByteChunks, String> {
+        let response = send(self.request(Method::GET, key, vec![])?).await?;
+        Ok(stream::unfold(response, |mut response| async move {
+            match response.chunk().await {
+                Ok(Some(chunk)) => Some((chunk.to_vec(), response)),
+                Ok(None) => None,
+                Err(e) => {
+                    warn!("Failed to read from storage: {}", e);
+                    None
+                }
+            }
+        })
+        .boxed())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        // S3 answers 204 for keys that don't exist too
+        send(self.request(Method::DELETE, key, vec![])?)
+            .await
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,273 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::json;
+
+use crate::{
+    api::{
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        dependencies::utils::get_task,
+        labels::utils::setup_labels_default,
+        lists::utils::setup_lists_default,
+        tasks::{
+            labels::utils::{add_label, assert_has_label, assert_missing_label},
+            lists::utils::create_task,
+            ordering::utils::create,
+            types::GetTaskResponse,
+            utils::DEFAULT_TASKS_TEMPLATES,
+        },
+        trash::utils::assert_missing,
+    },
+    commons::{
+        self,
+        http_client::{APIClient, APIRequestBuilder},
+    },
+};
+
+#[rocket::async_test]
+async fn undo_and_redo_patch() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[0],
+        list_ids[0],
+    )
+    .await;
+    let api = client.api().path("tasks").auth(&session_response);
+    let original = api.get::<GetTaskResponse>(task_id).await;
+
+    api.patch(task_id, json!({ "title": "Renamed task" })).await;
+
+    let action = utils::undo(&client, &session_response, StatusCode::OK)
+        .await
+        .expect("Expected action");
+    assert_eq!(action.action_type, "update");
+    let task = api.get::<GetTaskResponse>(task_id).await;
+    assert_eq!(task.title, original.title);
+
+    let action = utils::redo(&client, &session_response, StatusCode::OK)
+        .await
+        .expect("Expected action");
+    assert_eq!(action.action_type, "update");
+    let task = api.get::<GetTaskResponse>(task_id).await;
+    assert_eq!(task.title, "Renamed task");
+}
+
+#[rocket::async_test]
+async fn undo_create_and_delete() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[0],
+        list_ids[0],
+    )
+    .await;
+
+    client
+        .api()
+        .path("tasks")
+        .auth(&session_response)
+        .delete(task_id)
+        .await;
+    assert_missing(&client, &session_response, "tasks", task_id).await;
+
+    let action = utils::undo(&client, &session_response, StatusCode::OK)
+        .await
+        .expect("Expected action");
+    assert_eq!(action.action_type, "delete");
+    super::tasks::lists::utils::assert_has_list(&client, &session_response, task_id, list_ids[0])
+        .await;
+
+    let action = utils::undo(&client, &session_response, StatusCode::OK)
+        .await
+        .expect("Expected action");
+    assert_eq!(action.action_type, "create");
+    assert_missing(&client, &session_response, "tasks", task_id).await;
+}
+
+#[rocket::async_test]
+async fn undo_label_attach() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let (label_ids, _) = setup_labels_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[0],
+        list_ids[0],
+    )
+    .await;
+    add_label(&client, &session_response, task_id, label_ids[0]).await;
+
+    let action = utils::undo(&client, &session_response, StatusCode::OK)
+        .await
+        .expect("Expected action");
+    assert_eq!(action.action_type, "attach_label");
+    assert_missing_label(&client, &session_response, task_id, label_ids[0]).await;
+
+    utils::redo(&client, &session_response, StatusCode::OK).await;
+    assert_has_label(&client, &session_response, task_id, label_ids[0]).await;
+}
+
+#[rocket::async_test]
+async fn new_action_clears_redo() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[0],
+        list_ids[0],
+    )
+    .await;
+    let api = client.api().path("tasks").auth(&session_response);
+
+    api.patch(task_id, json!({ "title": "First rename" })).await;
+    utils::undo(&client, &session_response, StatusCode::OK).await;
+    api.patch(task_id, json!({ "title": "Second rename" }))
+        .await;
+
+    utils::redo(&client, &session_response, StatusCode::NOT_FOUND).await;
+}
+
+#[rocket::async_test]
+async fn undo_and_redo_moves() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let p = create(&client, &session_response, "P", list_ids[0], None).await;
+    let q = create(&client, &session_response, "Q", list_ids[1], None).await;
+    let x = create(&client, &session_response, "X", list_ids[0], Some(p)).await;
+    let y = create(&client, &session_response, "Y", list_ids[0], Some(x)).await;
+    let api = client.api().path("tasks").auth(&session_response);
+
+    // Move X from P to Q, then P under X
+    api.patch(x, json!({ "parent_id": q })).await;
+    api.patch(p, json!({ "parent_id": x })).await;
+    let placement = || async {
+        let mut placement = vec![];
+        for id in [p, x, y] {
+            let task = get_task(&client, &session_response, id).await;
+            placement.push((task["parent_id"].clone(), task["list_id"].clone()));
+        }
+        placement
+    };
+    let moved = vec![
+        (json!(x), json!(list_ids[1])),
+        (json!(q), json!(list_ids[1])),
+        (json!(x), json!(list_ids[1])),
+    ];
+    assert_eq!(placement().await, moved);
+
+    // Undoing the moves never puts X back under P while P is under X
+    utils::undo(&client, &session_response, StatusCode::OK).await;
+    utils::undo(&client, &session_response, StatusCode::OK).await;
+    assert_eq!(
+        placement().await,
+        vec![
+            (json!(null), json!(list_ids[0])),
+            (json!(p), json!(list_ids[0])),
+            (json!(x), json!(list_ids[0])),
+        ]
+    );
+
+    utils::redo(&client, &session_response, StatusCode::OK).await;
+    utils::redo(&client, &session_response, StatusCode::OK).await;
+    assert_eq!(placement().await, moved);
+}
+
+#[rocket::async_test]
+async fn undo_nothing() {
+    let client = commons::setup().await;
+    let (alice_session_response, _) = email_register_and_login_user(&client, "alice").await;
+    let (bob_session_response, _) = email_register_and_login_user(&client, "bob").await;
+
+    setup_lists_default(&client, &bob_session_response).await;
+
+    // Actions are per user
+    utils::undo(&client, &alice_session_response, StatusCode::NOT_FOUND).await;
+    utils::redo(&client, &alice_session_response, StatusCode::NOT_FOUND).await;
+}
+
+#[rocket::async_test]
+async fn undo_unauth() {
+    let client = commons::setup().await;
+    let res = client
+        .post("actions/undo")
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+pub mod types {
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct ActionResponse {
+        pub id: Uuid,
+        pub action_type: String,
+    }
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+
+    use super::types::ActionResponse;
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    async fn send(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        path: &str,
+        status: StatusCode,
+    ) -> Option<ActionResponse> {
+        let res = client
+            .post(path)
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), status);
+        if status != StatusCode::OK {
+            return None;
+        }
+        Some(
+            res.json::<ActionResponse>()
+                .await
+                .expect("Expected json response"),
+        )
+    }
+
+    pub async fn undo(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        status: StatusCode,
+    ) -> Option<ActionResponse> {
+        send(client, session_response, "actions/undo", status).await
+    }
+
+    pub async fn redo(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        status: StatusCode,
+    ) -> Option<ActionResponse> {
+        send(client, session_response, "actions/redo", status).await
+    }
+}
@@ -0,0 +1,299 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use crate::{
+    api::{
+        actions,
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        lists::{
+            tree::utils::{get_item, reparent},
+            utils::setup_lists_default,
+        },
+        search::utils::search,
+        tasks::ordering::utils::create,
+    },
+    commons,
+};
+
+#[rocket::async_test]
+async fn archive_and_unarchive() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    reparent(&client, &session_response, list_ids[1], list_ids[0]).await;
+    let task_id = create(&client, &session_response, "Task", list_ids[0], None).await;
+    let nested_id = create(&client, &session_response, "Nested", list_ids[1], None).await;
+    let other_id = create(&client, &session_response, "Other", list_ids[2], None).await;
+
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[0],
+        "archive",
+        StatusCode::OK,
+    )
+    .await;
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[0],
+        "archive",
+        StatusCode::BAD_REQUEST,
+    )
+    .await;
+
+    // Archived lists and their tasks are hidden from the default listings
+    let lists = utils::get_ids(&client, &session_response, "lists").await;
+    assert!(!lists.contains(&list_ids[0]));
+    assert!(!lists.contains(&list_ids[1]));
+    assert!(lists.contains(&list_ids[2]));
+    let tasks = utils::get_ids(&client, &session_response, "tasks").await;
+    assert_eq!(tasks, [other_id]);
+    let res = client
+        .get("lists/tree")
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    let tree: Value = res.json().await.expect("Expected correct json response");
+    assert!(tree
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|list| list["id"] != list_ids[0].to_string()));
+
+    // But still reachable directly
+    let list = get_item(&client, &session_response, list_ids[0]).await;
+    assert_eq!(list.child_ids, [list_ids[1]]);
+    let tasks = utils::get_ids(
+        &client,
+        &session_response,
+        &format!("tasks?list_id={}", list_ids[1]),
+    )
+    .await;
+    assert_eq!(tasks, [nested_id]);
+
+    // Only the root of an archived subtree is listed
+    let archived = utils::get_ids(&client, &session_response, "lists/archived").await;
+    assert_eq!(archived, [list_ids[0]]);
+
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[1],
+        "unarchive",
+        StatusCode::BAD_REQUEST,
+    )
+    .await;
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[0],
+        "unarchive",
+        StatusCode::OK,
+    )
+    .await;
+    let tasks = utils::get_ids(&client, &session_response, "tasks").await;
+    assert_eq!(tasks.len(), 3);
+    assert!(tasks.contains(&task_id) && tasks.contains(&nested_id));
+    assert!(utils::get_ids(&client, &session_response, "lists/archived")
+        .await
+        .is_empty());
+
+    // Archiving can be undone like any other change
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[1],
+        "archive",
+        StatusCode::OK,
+    )
+    .await;
+    let list = get_item(&client, &session_response, list_ids[0]).await;
+    assert!(list.child_ids.is_empty());
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    let list = get_item(&client, &session_response, list_ids[0]).await;
+    assert_eq!(list.child_ids, [list_ids[1]]);
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[1],
+        "unarchive",
+        StatusCode::BAD_REQUEST,
+    )
+    .await;
+}
+
+#[rocket::async_test]
+async fn archived_sublist_stays_archived() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    reparent(&client, &session_response, list_ids[1], list_ids[0]).await;
+    let nested_id = create(&client, &session_response, "Nested", list_ids[1], None).await;
+
+    for list_id in [list_ids[1], list_ids[0]] {
+        utils::archive(
+            &client,
+            &session_response,
+            list_id,
+            "archive",
+            StatusCode::OK,
+        )
+        .await;
+    }
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[0],
+        "unarchive",
+        StatusCode::OK,
+    )
+    .await;
+
+    // The sublist was archived on its own and isn't unarchived with its parent
+    let lists = utils::get_ids(&client, &session_response, "lists").await;
+    assert!(lists.contains(&list_ids[0]));
+    assert!(!lists.contains(&list_ids[1]));
+    let archived = utils::get_ids(&client, &session_response, "lists/archived").await;
+    assert_eq!(archived, [list_ids[1]]);
+    let tasks = utils::get_ids(&client, &session_response, "tasks").await;
+    assert!(!tasks.contains(&nested_id));
+}
+
+#[rocket::async_test]
+async fn search_skips_archived() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "Chore", list_ids[0], None).await;
+
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[0],
+        "archive",
+        StatusCode::OK,
+    )
+    .await;
+    let results = search(&client, &session_response, &[("q", "list")], StatusCode::OK)
+        .await
+        .expect("Expected results");
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|result| result.id != list_ids[0]));
+    let results = search(
+        &client,
+        &session_response,
+        &[("q", "chore")],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected results");
+    assert!(results.is_empty());
+
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[0],
+        "unarchive",
+        StatusCode::OK,
+    )
+    .await;
+    let results = search(
+        &client,
+        &session_response,
+        &[("q", "chore")],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected results");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, task_id);
+}
+
+#[rocket::async_test]
+async fn other_user() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+
+    for action in ["archive", "unarchive"] {
+        utils::archive(
+            &client,
+            &other_session,
+            list_ids[0],
+            action,
+            StatusCode::NOT_FOUND,
+        )
+        .await;
+    }
+    utils::archive(
+        &client,
+        &session_response,
+        list_ids[0],
+        "archive",
+        StatusCode::OK,
+    )
+    .await;
+    assert!(utils::get_ids(&client, &other_session, "lists/archived")
+        .await
+        .is_empty());
+    utils::archive(
+        &client,
+        &other_session,
+        list_ids[0],
+        "unarchive",
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    pub async fn archive(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        list_id: Uuid,
+        action: &str,
+        expected_status: StatusCode,
+    ) {
+        let res = client
+            .post(&format!("lists/{list_id}/{action}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+    }
+
+    pub async fn get_ids(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        path: &str,
+    ) -> Vec<Uuid> {
+        let res = client
+            .get(path)
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: Value = res.json().await.expect("Expected correct json response");
+        body["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_str().unwrap().parse().unwrap())
+            .collect()
+    }
+}
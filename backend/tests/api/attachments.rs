@@ -0,0 +1,523 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use rocket::futures::StreamExt;
+use toast_task::{
+    config::StorageConfig,
+    storage::{S3Storage, Storage},
+};
+use uuid::Uuid;
+
+use crate::{
+    api::auth::email::utils::{
+        email_register_and_login_user, email_register_and_login_user_default,
+    },
+    commons,
+};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+#[rocket::async_test]
+async fn upload_and_download() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let task_id = utils::setup_task(&client, &session_response).await;
+
+    let png_id = utils::upload(
+        &client,
+        &session_response,
+        task_id,
+        "../Receipt Mai ü.png",
+        "text/plain",
+        PNG,
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let html_id = utils::upload(
+        &client,
+        &session_response,
+        task_id,
+        "notes.html",
+        "image/png",
+        b"<script>alert(1)</script>",
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    let attachments = utils::get_attachments(&client, &session_response, task_id).await;
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[0]["id"], png_id.to_string());
+    assert_eq!(attachments[0]["file_name"], "Receipt Mai ü.png");
+    // The type comes from the contents, not from the client
+    assert_eq!(attachments[0]["content_type"], "image/png");
+    assert_eq!(attachments[0]["size"], PNG.len());
+    assert_eq!(attachments[1]["content_type"], "text/plain; charset=utf-8");
+
+    let res = client
+        .get(&format!("tasks/{task_id}/attachments/{png_id}/content"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "image/png");
+    assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+    assert_eq!(
+        res.headers()["content-disposition"],
+        "attachment; filename=\"Receipt Mai _.png\"; filename*=UTF-8''Receipt%20Mai%20%C3%BC.png"
+    );
+    assert_eq!(res.bytes().await.unwrap().as_ref(), PNG);
+
+    let res = client
+        .get(&format!("tasks/{task_id}/attachments/{html_id}/content"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.headers()["content-type"], "text/plain; charset=utf-8");
+
+    let res = client
+        .delete(&format!("tasks/{task_id}/attachments/{png_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client
+        .get(&format!("tasks/{task_id}/attachments/{png_id}/content"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let attachments = utils::get_attachments(&client, &session_response, task_id).await;
+    assert_eq!(attachments.len(), 1);
+}
+
+#[rocket::async_test]
+async fn limits() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let task_id = utils::setup_task(&client, &session_response).await;
+
+    // The test config allows 64 KiB per file and 128 KiB in total
+    utils::upload(
+        &client,
+        &session_response,
+        task_id,
+        "big.bin",
+        "application/octet-stream",
+        &vec![0; 64 * 1024 + 1],
+        StatusCode::PAYLOAD_TOO_LARGE,
+    )
+    .await;
+
+    let mut ids = vec![];
+    for _ in 0..2 {
+        ids.push(
+            utils::upload(
+                &client,
+                &session_response,
+                task_id,
+                "part.bin",
+                "application/octet-stream",
+                &vec![0; 50 * 1024],
+                StatusCode::CREATED,
+            )
+            .await
+            .unwrap(),
+        );
+    }
+    utils::upload(
+        &client,
+        &session_response,
+        task_id,
+        "part.bin",
+        "application/octet-stream",
+        &vec![0; 50 * 1024],
+        StatusCode::PAYLOAD_TOO_LARGE,
+    )
+    .await;
+
+    let res = client
+        .delete(&format!("tasks/{task_id}/attachments/{}", ids[0]))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    utils::upload(
+        &client,
+        &session_response,
+        task_id,
+        "part.bin",
+        "application/octet-stream",
+        &vec![0; 50 * 1024],
+        StatusCode::CREATED,
+    )
+    .await;
+}
+
+#[rocket::async_test]
+async fn other_users() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let task_id = utils::setup_task(&client, &session_response).await;
+    let id = utils::upload(
+        &client,
+        &session_response,
+        task_id,
+        "receipt.png",
+        "image/png",
+        PNG,
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    utils::upload(
+        &client,
+        &other_session,
+        task_id,
+        "receipt.png",
+        "image/png",
+        PNG,
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+    for path in [
+        format!("tasks/{task_id}/attachments"),
+        format!("tasks/{task_id}/attachments/{id}/content"),
+    ] {
+        let res = client
+            .get(&path)
+            .bearer_auth(other_session.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+    let res = client
+        .delete(&format!("tasks/{task_id}/attachments/{id}"))
+        .bearer_auth(other_session.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = client
+        .get(&format!("tasks/{task_id}/attachments/{id}/content"))
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[rocket::async_test]
+async fn purged_task_deletes_files() {
+    let root = std::env::temp_dir().join(format!("toast-task-test-storage-{}", Uuid::new_v4()));
+    let storage_root = root.clone();
+    let client = commons::setup_with_config(|config| {
+        config.trash_retention = time::Duration::ZERO;
+        config.trash_purge_interval = time::Duration::milliseconds(200);
+        config.storage = StorageConfig::Local { root: storage_root };
+    })
+    .await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let task_id = utils::setup_task(&client, &session_response).await;
+
+    utils::upload(
+        &client,
+        &session_response,
+        task_id,
+        "receipt.png",
+        "image/png",
+        PNG,
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    assert_eq!(utils::count_files(&root), 1);
+
+    let res = client
+        .delete(&format!("tasks/{task_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // The worker purges the task, which queues its files for deletion
+    for _ in 0..25 {
+        if utils::count_files(&root) == 0 {
+            break;
+        }
+        rocket::tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    assert_eq!(utils::count_files(&root), 0);
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[rocket::async_test]
+async fn s3_storage() {
+    let (endpoint, objects) = utils::s3_server().await;
+    let storage = S3Storage::new(&endpoint, "attachments", "us-east-1", "key", "secret");
+
+    storage
+        .put("user/file", PNG.to_vec(), "image/png")
+        .await
+        .expect("Expected put to succeed");
+    {
+        let objects = objects.lock().unwrap();
+        let object = &objects["/attachments/user/file"];
+        assert_eq!(object.body, PNG);
+        assert_eq!(object.content_type.as_deref(), Some("image/png"));
+        assert!(object
+            .authorization
+            .starts_with("AWS4-HMAC-SHA256 Credential=key/"));
+        assert!(object
+            .authorization
+            .contains("/us-east-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="));
+    }
+
+    let chunks: Vec<Vec<u8>> = storage
+        .get("user/file")
+        .await
+        .expect("Expected get to succeed")
+        .collect()
+        .await;
+    assert_eq!(chunks.concat(), PNG);
+
+    storage
+        .delete("user/file")
+        .await
+        .expect("Expected delete to succeed");
+    assert!(objects.lock().unwrap().is_empty());
+    assert!(storage.get("user/file").await.is_err());
+    assert!(storage.put("../file", vec![], "text/plain").await.is_err());
+}
+
+pub mod utils {
+    use std::{
+        collections::HashMap,
+        path::Path,
+        sync::{Arc, Mutex},
+    };
+
+    use reqwest::StatusCode;
+    use rocket::tokio::{
+        self,
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    use crate::{
+        api::{
+            auth::email::utils::SessionResponse, lists::utils::setup_lists_default,
+            tasks::lists::utils::create_task,
+        },
+        commons::http_client::HttpClient,
+    };
+
+    #[derive(Deserialize)]
+    struct PostResponse {
+        id: Uuid,
+    }
+
+    /// Counts the files below `dir`, which may not exist.
+    pub fn count_files(dir: &Path) -> usize {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return 0;
+        };
+        entries
+            .flatten()
+            .map(|entry| match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => count_files(&entry.path()),
+                _ => 1,
+            })
+            .sum()
+    }
+
+    pub async fn setup_task(client: &HttpClient, session_response: &SessionResponse) -> Uuid {
+        let (list_ids, _) = setup_lists_default(client, session_response).await;
+        create_task(
+            client,
+            session_response,
+            &json!({
+                "title": "Expenses",
+                "due_at": "2023-10-19T10:23:00.000000000Z",
+                "due_text": "some day"
+            }),
+            list_ids[0],
+        )
+        .await
+    }
+
+    pub async fn upload(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        file_name: &str,
+        content_type: &str,
+        contents: &[u8],
+        expected_status: StatusCode,
+    ) -> Option<Uuid> {
+        let boundary = "attachment-boundary";
+        let mut body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; \
+                filename=\"{}\"\r\nContent-Type: {content_type}\r\n\r\n",
+            file_name
+        )
+        .into_bytes();
+        body.extend_from_slice(contents);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        let res = client
+            .post(&format!("tasks/{task_id}/attachments"))
+            .bearer_auth(session_response.session_token)
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+        if expected_status != StatusCode::CREATED {
+            return None;
+        }
+        let response = res
+            .json::<PostResponse>()
+            .await
+            .expect("Expected correct json response");
+        Some(response.id)
+    }
+
+    pub async fn get_attachments(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+    ) -> Vec<Value> {
+        let res = client
+            .get(&format!("tasks/{task_id}/attachments"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+
+    pub struct S3Object {
+        pub body: Vec<u8>,
+        pub content_type: Option<String>,
+        pub authorization: String,
+    }
+
+    pub type S3Objects = Arc<Mutex<HashMap<String, S3Object>>>;
+
+    /// Starts a minimal S3 stand-in keeping objects by path. Requests
+    /// without a signature or with a wrong payload hash are rejected.
+    pub async fn s3_server() -> (String, S3Objects) {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Expected listener");
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let objects = S3Objects::default();
+        let server_objects = objects.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let objects = server_objects.clone();
+                tokio::spawn(async move {
+                    let (head, body) = read_request(&mut stream).await;
+                    let mut lines = head.lines();
+                    let request_line = lines.next().unwrap_or_default().to_owned();
+                    let headers: HashMap<String, String> = lines
+                        .filter_map(|line| line.split_once(':'))
+                        .map(|(name, value)| (name.to_lowercase(), value.trim().to_owned()))
+                        .collect();
+                    let mut parts = request_line.split(' ');
+                    let method = parts.next().unwrap_or_default();
+                    let path = parts.next().unwrap_or_default().to_owned();
+
+                    let signed = headers
+                        .get("authorization")
+                        .is_some_and(|auth| auth.starts_with("AWS4-HMAC-SHA256 "))
+                        && headers.get("x-amz-content-sha256")
+                            == Some(&hex::encode(Sha256::digest(&body)));
+                    let (status, response_body) = if !signed {
+                        ("403 Forbidden", vec![])
+                    } else {
+                        let mut objects = objects.lock().unwrap();
+                        match method {
+                            "PUT" => {
+                                objects.insert(
+                                    path,
+                                    S3Object {
+                                        body,
+                                        content_type: headers.get("content-type").cloned(),
+                                        authorization: headers["authorization"].clone(),
+                                    },
+                                );
+                                ("200 OK", vec![])
+                            }
+                            "GET" => match objects.get(&path) {
+                                Some(object) => ("200 OK", object.body.clone()),
+                                None => ("404 Not Found", vec![]),
+                            },
+                            "DELETE" => {
+                                objects.remove(&path);
+                                ("204 No Content", vec![])
+                            }
+                            _ => ("405 Method Not Allowed", vec![]),
+                        }
+                    };
+                    let mut response = format!(
+                        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        response_body.len()
+                    )
+                    .into_bytes();
+                    response.extend(response_body);
+                    let _ = stream.write_all(&response).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+        (endpoint, objects)
+    }
+
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> (String, Vec<u8>) {
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        loop {
+            if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                let head = String::from_utf8_lossy(&request[..end]).into_owned();
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    return (head, request[end + 4..end + 4 + length].to_vec());
+                }
+            }
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => return (String::new(), vec![]),
+                Ok(read) => request.extend_from_slice(&buf[..read]),
+            }
+        }
+    }
+}
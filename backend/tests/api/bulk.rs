@@ -0,0 +1,295 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        actions,
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        dependencies::utils::{add_blocker, get_task},
+        lists::utils::setup_lists_default,
+        tasks::ordering::utils::create,
+    },
+    commons::{
+        self,
+        http_client::{APIClient, APIRequestBuilder},
+    },
+};
+
+#[rocket::async_test]
+async fn complete() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let (other_list_ids, _) = setup_lists_default(&client, &other_session).await;
+    let first = create(&client, &session_response, "First", list_ids[0], None).await;
+    let second = create(&client, &session_response, "Second", list_ids[1], None).await;
+    let blocked = create(&client, &session_response, "Blocked", list_ids[0], None).await;
+    let theirs = create(&client, &other_session, "Theirs", other_list_ids[0], None).await;
+    add_blocker(&client, &session_response, blocked, first, StatusCode::OK).await;
+
+    let ids = [first, second, blocked, theirs, Uuid::new_v4()];
+    let results = utils::bulk(
+        &client,
+        &session_response,
+        &ids,
+        json!({ "type": "set_completed", "completed": true }),
+    )
+    .await;
+    // Tasks go in order, so completing the blocker first unblocks the last one
+    let statuses: Vec<_> = results.iter().map(|result| &result["status"]).collect();
+    assert_eq!(statuses, [200, 200, 200, 404, 404]);
+    for id in [first, second, blocked] {
+        assert_eq!(
+            get_task(&client, &session_response, id).await["completed"],
+            true
+        );
+    }
+    assert_eq!(
+        get_task(&client, &other_session, theirs).await["completed"],
+        false
+    );
+
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    for id in [first, second, blocked] {
+        assert_eq!(
+            get_task(&client, &session_response, id).await["completed"],
+            false
+        );
+    }
+
+    // Failures don't roll back the other tasks
+    let results = utils::bulk(
+        &client,
+        &session_response,
+        &[blocked, second],
+        json!({ "type": "set_completed", "completed": true }),
+    )
+    .await;
+    assert_eq!(results[0]["status"], 409);
+    assert!(results[0]["message"].is_string());
+    assert_eq!(results[1]["status"], 200);
+    assert_eq!(
+        get_task(&client, &session_response, second).await["completed"],
+        true
+    );
+}
+
+#[rocket::async_test]
+async fn move_and_due_date() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let root = create(&client, &session_response, "Move", list_ids[0], None).await;
+    let child = create(&client, &session_response, "Pack", list_ids[0], Some(root)).await;
+    let other = create(&client, &session_response, "Clean", list_ids[0], None).await;
+
+    let results = utils::bulk(
+        &client,
+        &session_response,
+        &[root, other],
+        json!({ "type": "move", "list_id": list_ids[1] }),
+    )
+    .await;
+    assert!(results.iter().all(|result| result["status"] == 200));
+    for id in [root, child, other] {
+        let task = get_task(&client, &session_response, id).await;
+        assert_eq!(task["list_id"], list_ids[1].to_string());
+    }
+
+    let results = utils::bulk(
+        &client,
+        &session_response,
+        &[other],
+        json!({ "type": "move", "parent_id": child }),
+    )
+    .await;
+    assert_eq!(results[0]["status"], 200);
+    let task = get_task(&client, &session_response, other).await;
+    assert_eq!(task["parent_id"], child.to_string());
+
+    let results = utils::bulk(
+        &client,
+        &session_response,
+        &[root, child],
+        json!({
+            "type": "set_due_date",
+            "due_at": "2023-12-24T18:00:00.000000000Z",
+            "due_text": "Christmas eve"
+        }),
+    )
+    .await;
+    assert!(results.iter().all(|result| result["status"] == 200));
+    for id in [root, child] {
+        let task = get_task(&client, &session_response, id).await;
+        assert_eq!(task["due_text"], "Christmas eve");
+        assert_eq!(task["due_at"], "2023-12-24T18:00:00.000000000Z");
+    }
+}
+
+#[rocket::async_test]
+async fn labels() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let first = create(&client, &session_response, "First", list_ids[0], None).await;
+    let second = create(&client, &session_response, "Second", list_ids[0], None).await;
+    let labels = client.api().path("labels").auth(&session_response);
+    let label_id = labels
+        .post(json!({ "title": "Home", "color": "#ffa783" }))
+        .await
+        .id;
+    let other_label_id = client
+        .api()
+        .path("labels")
+        .auth(&other_session)
+        .post(json!({ "title": "Theirs", "color": "#ffa783" }))
+        .await
+        .id;
+
+    // Adding twice keeps a single label
+    for _ in 0..2 {
+        let results = utils::bulk(
+            &client,
+            &session_response,
+            &[first, second],
+            json!({ "type": "add_labels", "label_ids": [label_id] }),
+        )
+        .await;
+        assert!(results.iter().all(|result| result["status"] == 200));
+    }
+    for id in [first, second] {
+        let task = get_task(&client, &session_response, id).await;
+        assert_eq!(task["label_ids"], json!([label_id]));
+    }
+
+    let res = utils::send(
+        &client,
+        &session_response,
+        &[first],
+        json!({ "type": "add_labels", "label_ids": [other_label_id] }),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let results = utils::bulk(
+        &client,
+        &session_response,
+        &[first],
+        json!({ "type": "remove_labels", "label_ids": [label_id] }),
+    )
+    .await;
+    assert_eq!(results[0]["status"], 200);
+    let task = get_task(&client, &session_response, first).await;
+    assert_eq!(task["label_ids"], json!([]));
+    let task = get_task(&client, &session_response, second).await;
+    assert_eq!(task["label_ids"], json!([label_id]));
+}
+
+#[rocket::async_test]
+async fn delete() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let first = create(&client, &session_response, "First", list_ids[0], None).await;
+    let second = create(&client, &session_response, "Second", list_ids[0], None).await;
+
+    let results = utils::bulk(
+        &client,
+        &session_response,
+        &[first, second, first],
+        json!({ "type": "delete" }),
+    )
+    .await;
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result["status"] == 200));
+    for id in [first, second] {
+        let res = client
+            .get(&format!("tasks/{id}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    for id in [first, second] {
+        get_task(&client, &session_response, id).await;
+    }
+}
+
+#[rocket::async_test]
+async fn invalid() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "First", list_ids[0], None).await;
+
+    let res = utils::send(&client, &session_response, &[], json!({ "type": "delete" })).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res = utils::send(
+        &client,
+        &session_response,
+        &[task_id],
+        json!({ "type": "explode" }),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let results: Value = client
+        .post("tasks/bulk")
+        .json(&json!({ "ids": [task_id], "operation": { "type": "delete" } }))
+        .send()
+        .await
+        .expect("Expected response")
+        .json()
+        .await
+        .expect("Expected correct json response");
+    assert!(results.get("results").is_none());
+    get_task(&client, &session_response, task_id).await;
+}
+
+pub mod utils {
+    use reqwest::{Response, StatusCode};
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    pub async fn send(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        ids: &[Uuid],
+        operation: Value,
+    ) -> Response {
+        client
+            .post("tasks/bulk")
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "ids": ids, "operation": operation }))
+            .send()
+            .await
+            .expect("Expected response")
+    }
+
+    /// Runs a bulk operation, returning the result of every task.
+    pub async fn bulk(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        ids: &[Uuid],
+        operation: Value,
+    ) -> Vec<Value> {
+        let res = send(client, session_response, ids, operation).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: Value = res.json().await.expect("Expected correct json response");
+        body["results"]
+            .as_array()
+            .expect("Expected results")
+            .clone()
+    }
+}
@@ -0,0 +1,267 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::json;
+
+use crate::{
+    api::{
+        actions,
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        lists::utils::setup_lists_default,
+        tasks::lists::utils::create_task,
+    },
+    commons,
+};
+
+const TASK: &str = r#"{
+    "title": "Long-running task",
+    "due_at": "2023-10-19T10:23:00.000000000Z",
+    "due_text": "some day"
+}"#;
+
+#[rocket::async_test]
+async fn crud() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &serde_json::from_str(TASK).unwrap(),
+        list_ids[0],
+    )
+    .await;
+
+    let first_id = utils::create_comment(
+        &client,
+        &session_response,
+        task_id,
+        "Started on **this**.",
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let second_id = utils::create_comment(
+        &client,
+        &session_response,
+        task_id,
+        "Halfway there.",
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    let comments = utils::get_comments(&client, &session_response, task_id, "").await;
+    assert_eq!(comments.items.len(), 2);
+    assert_eq!(comments.items[0].id, first_id);
+    assert_eq!(comments.items[0].body, "Started on **this**.");
+    assert_eq!(comments.items[0].author, "johnsmith");
+    assert_eq!(comments.items[1].id, second_id);
+
+    let res = client
+        .patch(&format!("tasks/{task_id}/comments/{first_id}"))
+        .bearer_auth(session_response.session_token)
+        .json(&json!({ "body": "Started on this." }))
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    let comments = utils::get_comments(&client, &session_response, task_id, "").await;
+    assert_eq!(comments.items[0].body, "Started on this.");
+    assert!(comments.items[0].updated_at >= comments.items[0].created_at);
+
+    let res = client
+        .delete(&format!("tasks/{task_id}/comments/{second_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    let comments = utils::get_comments(&client, &session_response, task_id, "").await;
+    assert_eq!(comments.items.len(), 1);
+
+    // Deleting a comment can be undone like anything else
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    let comments = utils::get_comments(&client, &session_response, task_id, "").await;
+    assert_eq!(comments.items.len(), 2);
+    assert_eq!(comments.items[1].body, "Halfway there.");
+}
+
+#[rocket::async_test]
+async fn pagination() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &serde_json::from_str(TASK).unwrap(),
+        list_ids[0],
+    )
+    .await;
+
+    let mut ids = vec![];
+    for i in 0..5 {
+        ids.push(
+            utils::create_comment(
+                &client,
+                &session_response,
+                task_id,
+                &format!("Note {i}"),
+                StatusCode::CREATED,
+            )
+            .await
+            .unwrap(),
+        );
+    }
+
+    let page = utils::get_comments(&client, &session_response, task_id, "?limit=2&page=1").await;
+    assert_eq!(page.limit, 2);
+    assert_eq!(page.page, 1);
+    assert_eq!(
+        page.items
+            .iter()
+            .map(|comment| comment.id)
+            .collect::<Vec<_>>(),
+        ids[2..4]
+    );
+    let page = utils::get_comments(&client, &session_response, task_id, "?limit=2&page=2").await;
+    assert_eq!(page.items.len(), 1);
+}
+
+#[rocket::async_test]
+async fn invalid() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &serde_json::from_str(TASK).unwrap(),
+        list_ids[0],
+    )
+    .await;
+
+    utils::create_comment(
+        &client,
+        &session_response,
+        task_id,
+        "",
+        StatusCode::BAD_REQUEST,
+    )
+    .await;
+    let comment_id = utils::create_comment(
+        &client,
+        &session_response,
+        task_id,
+        "Mine",
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    utils::create_comment(
+        &client,
+        &other_session,
+        task_id,
+        "Not yours",
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+    let res = client
+        .get(&format!("tasks/{task_id}/comments"))
+        .bearer_auth(other_session.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let res = client
+        .patch(&format!("tasks/{task_id}/comments/{comment_id}"))
+        .bearer_auth(other_session.session_token)
+        .json(&json!({ "body": "Changed" }))
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let res = client
+        .delete(&format!("tasks/{task_id}/comments/{comment_id}"))
+        .bearer_auth(other_session.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    #[derive(Deserialize)]
+    pub struct CommentResponse {
+        pub id: Uuid,
+        pub author: String,
+        pub body: String,
+        pub created_at: String,
+        pub updated_at: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct CommentsResponse {
+        pub items: Vec<CommentResponse>,
+        pub limit: u32,
+        pub page: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct PostResponse {
+        id: Uuid,
+    }
+
+    pub async fn create_comment(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        body: &str,
+        expected_status: StatusCode,
+    ) -> Option<Uuid> {
+        let res = client
+            .post(&format!("tasks/{task_id}/comments"))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "body": body }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+        if expected_status != StatusCode::CREATED {
+            return None;
+        }
+        let response = res
+            .json::<PostResponse>()
+            .await
+            .expect("Expected correct json response");
+        Some(response.id)
+    }
+
+    pub async fn get_comments(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        query: &str,
+    ) -> CommentsResponse {
+        let res = client
+            .get(&format!("tasks/{task_id}/comments{query}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+}
@@ -0,0 +1,296 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::json;
+
+use crate::{
+    api::{
+        actions,
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        lists::utils::setup_lists_default,
+        tasks::ordering::utils::create,
+    },
+    commons,
+};
+
+#[rocket::async_test]
+async fn blockers() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "Ship", list_ids[0], None).await;
+    let blocker_id = create(&client, &session_response, "Test", list_ids[1], None).await;
+
+    utils::add_blocker(
+        &client,
+        &session_response,
+        task_id,
+        blocker_id,
+        StatusCode::OK,
+    )
+    .await;
+    let task = utils::get_task(&client, &session_response, task_id).await;
+    assert_eq!(task["blocked"], true);
+    let blockers = utils::get_blockers(&client, &session_response, task_id).await;
+    assert_eq!(blockers.len(), 1);
+    assert_eq!(blockers[0]["id"], blocker_id.to_string());
+    assert_eq!(blockers[0]["completed"], false);
+
+    // Blocked tasks can't be completed unless forced
+    utils::complete(
+        &client,
+        &session_response,
+        task_id,
+        false,
+        StatusCode::CONFLICT,
+    )
+    .await;
+    utils::complete(&client, &session_response, task_id, true, StatusCode::OK).await;
+    let res = client
+        .patch(&format!("tasks/{task_id}"))
+        .bearer_auth(session_response.session_token)
+        .json(&json!({ "completed": false }))
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    utils::complete(
+        &client,
+        &session_response,
+        blocker_id,
+        false,
+        StatusCode::OK,
+    )
+    .await;
+    let task = utils::get_task(&client, &session_response, task_id).await;
+    assert_eq!(task["blocked"], false);
+    utils::complete(&client, &session_response, task_id, false, StatusCode::OK).await;
+}
+
+#[rocket::async_test]
+async fn remove_blocker() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "Ship", list_ids[0], None).await;
+    let blocker_id = create(&client, &session_response, "Test", list_ids[0], None).await;
+    utils::add_blocker(
+        &client,
+        &session_response,
+        task_id,
+        blocker_id,
+        StatusCode::OK,
+    )
+    .await;
+
+    let res = client
+        .delete(&format!("tasks/{task_id}/blockers/{blocker_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    let task = utils::get_task(&client, &session_response, task_id).await;
+    assert_eq!(task["blocked"], false);
+
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    let task = utils::get_task(&client, &session_response, task_id).await;
+    assert_eq!(task["blocked"], true);
+
+    // Deleted blockers don't block
+    let res = client
+        .delete(&format!("tasks/{blocker_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    let task = utils::get_task(&client, &session_response, task_id).await;
+    assert_eq!(task["blocked"], false);
+}
+
+#[rocket::async_test]
+async fn cycles() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let a = create(&client, &session_response, "A", list_ids[0], None).await;
+    let b = create(&client, &session_response, "B", list_ids[0], None).await;
+    let c = create(&client, &session_response, "C", list_ids[0], None).await;
+
+    utils::add_blocker(&client, &session_response, a, a, StatusCode::BAD_REQUEST).await;
+    utils::add_blocker(&client, &session_response, a, b, StatusCode::OK).await;
+    utils::add_blocker(&client, &session_response, b, c, StatusCode::OK).await;
+    utils::add_blocker(&client, &session_response, b, a, StatusCode::CONFLICT).await;
+    utils::add_blocker(&client, &session_response, c, a, StatusCode::CONFLICT).await;
+    // Adding an edge twice is fine
+    utils::add_blocker(&client, &session_response, a, c, StatusCode::OK).await;
+    utils::add_blocker(&client, &session_response, a, c, StatusCode::OK).await;
+    assert_eq!(
+        utils::get_blockers(&client, &session_response, a)
+            .await
+            .len(),
+        2
+    );
+}
+
+#[rocket::async_test]
+async fn undo_and_redo_blockers() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let a = create(&client, &session_response, "A", list_ids[0], None).await;
+    let b = create(&client, &session_response, "B", list_ids[0], None).await;
+    utils::add_blocker(&client, &session_response, a, b, StatusCode::OK).await;
+
+    // Remove A blocked by B, then add B blocked by A
+    let res = client
+        .delete(&format!("tasks/{a}/blockers/{b}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    utils::add_blocker(&client, &session_response, b, a, StatusCode::OK).await;
+    let blocked = || async {
+        let a = utils::get_task(&client, &session_response, a).await;
+        let b = utils::get_task(&client, &session_response, b).await;
+        (a["blocked"].clone(), b["blocked"].clone())
+    };
+    assert_eq!(blocked().await, (json!(false), json!(true)));
+
+    // Undoing the removal never leaves A and B blocking each other
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    assert_eq!(blocked().await, (json!(true), json!(false)));
+    assert!(utils::get_blockers(&client, &session_response, b)
+        .await
+        .is_empty());
+
+    actions::utils::redo(&client, &session_response, StatusCode::OK).await;
+    actions::utils::redo(&client, &session_response, StatusCode::OK).await;
+    assert_eq!(blocked().await, (json!(false), json!(true)));
+    assert!(utils::get_blockers(&client, &session_response, a)
+        .await
+        .is_empty());
+}
+
+#[rocket::async_test]
+async fn other_users() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "Mine", list_ids[0], None).await;
+
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (other_list_ids, _) = setup_lists_default(&client, &other_session).await;
+    let other_id = create(&client, &other_session, "Theirs", other_list_ids[0], None).await;
+
+    utils::add_blocker(
+        &client,
+        &session_response,
+        task_id,
+        other_id,
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+    utils::add_blocker(
+        &client,
+        &other_session,
+        task_id,
+        other_id,
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+    utils::add_blocker(
+        &client,
+        &other_session,
+        other_id,
+        task_id,
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+    utils::complete(
+        &client,
+        &other_session,
+        task_id,
+        false,
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    pub async fn add_blocker(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        blocker_id: Uuid,
+        expected_status: StatusCode,
+    ) {
+        let res = client
+            .post(&format!("tasks/{task_id}/blockers"))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "id": blocker_id }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+    }
+
+    pub async fn get_blockers(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+    ) -> Vec<Value> {
+        let res = client
+            .get(&format!("tasks/{task_id}/blockers"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+
+    pub async fn get_task(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+    ) -> Value {
+        let res = client
+            .get(&format!("tasks/{task_id}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+
+    pub async fn complete(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        force: bool,
+        expected_status: StatusCode,
+    ) {
+        let res = client
+            .patch(&format!("tasks/{task_id}"))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "completed": true, "force": force }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+    }
+}
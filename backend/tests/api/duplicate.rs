@@ -0,0 +1,303 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::{
+    api::{
+        actions,
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        dependencies::utils::{complete, get_task},
+        lists::{
+            tree::utils::{get_item, reparent},
+            utils::setup_lists_default,
+        },
+        tasks::ordering::utils::create,
+    },
+    commons::{
+        self,
+        http_client::{APIClient, APIRequestBuilder},
+    },
+};
+
+#[rocket::async_test]
+async fn task() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let root = create(&client, &session_response, "Release", list_ids[0], None).await;
+    let done = create(&client, &session_response, "Tag", list_ids[0], Some(root)).await;
+    let nested = create(&client, &session_response, "Push", list_ids[0], Some(done)).await;
+    let open = create(
+        &client,
+        &session_response,
+        "Announce",
+        list_ids[0],
+        Some(root),
+    )
+    .await;
+    complete(&client, &session_response, done, false, StatusCode::OK).await;
+    let label_id = client
+        .api()
+        .path("labels")
+        .auth(&session_response)
+        .post(json!({ "title": "Work", "color": "#ffa783" }))
+        .await
+        .id;
+    let res = client
+        .post(&format!("tasks/{open}/labels"))
+        .bearer_auth(session_response.session_token)
+        .json(&json!({ "id": label_id }))
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let copy = utils::duplicate(
+        &client,
+        &session_response,
+        &format!("tasks/{root}"),
+        json!({}),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let ids = copy["ids"].as_object().unwrap();
+    assert_eq!(ids.len(), 4);
+    assert_eq!(copy["id"], ids[&root.to_string()]);
+    let task = get_task(&client, &session_response, utils::id(&copy["id"])).await;
+    assert_eq!(task["title"], "Release");
+    assert_eq!(task["list_id"], json!(list_ids[0]));
+    assert_eq!(task["parent_id"], Value::Null);
+    assert_eq!(
+        task["child_ids"],
+        json!([ids[&done.to_string()], ids[&open.to_string()]])
+    );
+    let done_copy = get_task(
+        &client,
+        &session_response,
+        utils::id(&ids[&done.to_string()]),
+    )
+    .await;
+    assert_eq!(done_copy["completed"], true);
+    assert_eq!(done_copy["child_ids"], json!([ids[&nested.to_string()]]));
+    let open_copy = get_task(
+        &client,
+        &session_response,
+        utils::id(&ids[&open.to_string()]),
+    )
+    .await;
+    assert_eq!(open_copy["label_ids"], json!([label_id]));
+    assert_eq!(open_copy["due_at"], "2023-10-19T10:23:00.000000000Z");
+    let original = get_task(&client, &session_response, root).await;
+    assert_eq!(original["child_ids"], json!([done, open]));
+
+    let copy = utils::duplicate(
+        &client,
+        &session_response,
+        &format!("tasks/{root}"),
+        json!({ "include_completed": false, "list_id": list_ids[1] }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let ids = copy["ids"].as_object().unwrap();
+    assert_eq!(ids.len(), 2);
+    let open_copy = get_task(
+        &client,
+        &session_response,
+        utils::id(&ids[&open.to_string()]),
+    )
+    .await;
+    assert_eq!(open_copy["list_id"], json!(list_ids[1]));
+    assert_eq!(open_copy["parent_id"], copy["id"]);
+
+    // Subtasks are copied next to the original, unless told otherwise
+    let copy = utils::duplicate(
+        &client,
+        &session_response,
+        &format!("tasks/{open}"),
+        json!({}),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let task = get_task(&client, &session_response, utils::id(&copy["id"])).await;
+    assert_eq!(task["parent_id"], json!(root));
+    let copy = utils::duplicate(
+        &client,
+        &session_response,
+        &format!("tasks/{open}"),
+        json!({ "parent_id": null }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let copy_id = utils::id(&copy["id"]);
+    let task = get_task(&client, &session_response, copy_id).await;
+    assert_eq!(task["parent_id"], Value::Null);
+    assert_eq!(task["list_id"], json!(list_ids[0]));
+
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    let res = client
+        .get(&format!("tasks/{copy_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[rocket::async_test]
+async fn list() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    reparent(&client, &session_response, list_ids[1], list_ids[0]).await;
+    let open = create(&client, &session_response, "Open", list_ids[0], None).await;
+    let subtask = create(
+        &client,
+        &session_response,
+        "Subtask",
+        list_ids[0],
+        Some(open),
+    )
+    .await;
+    let done = create(&client, &session_response, "Done", list_ids[0], None).await;
+    let nested = create(&client, &session_response, "Nested", list_ids[1], None).await;
+    complete(&client, &session_response, done, false, StatusCode::OK).await;
+
+    let copy = utils::duplicate(
+        &client,
+        &session_response,
+        &format!("lists/{}", list_ids[0]),
+        json!({ "include_completed": false }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let ids = copy["ids"].as_object().unwrap();
+    assert_eq!(ids.len(), 5);
+    assert!(!ids.contains_key(&done.to_string()));
+    let list_id = utils::id(&copy["id"]);
+    let list = get_item(&client, &session_response, list_id).await;
+    assert_eq!(list.parent_id, None);
+    assert_eq!(list.child_ids, [utils::id(&ids[&list_ids[1].to_string()])]);
+
+    let task = get_task(
+        &client,
+        &session_response,
+        utils::id(&ids[&open.to_string()]),
+    )
+    .await;
+    assert_eq!(task["list_id"], json!(list_id));
+    assert_eq!(task["child_ids"], json!([ids[&subtask.to_string()]]));
+    let task = get_task(
+        &client,
+        &session_response,
+        utils::id(&ids[&nested.to_string()]),
+    )
+    .await;
+    assert_eq!(task["list_id"], ids[&list_ids[1].to_string()]);
+
+    // Sublists are copied next to the original, or under another list
+    let copy = utils::duplicate(
+        &client,
+        &session_response,
+        &format!("lists/{}", list_ids[1]),
+        json!({}),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let list = get_item(&client, &session_response, utils::id(&copy["id"])).await;
+    assert_eq!(list.parent_id, Some(list_ids[0]));
+    let copy = utils::duplicate(
+        &client,
+        &session_response,
+        &format!("lists/{}", list_ids[1]),
+        json!({ "parent_id": list_ids[2] }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let list = get_item(&client, &session_response, utils::id(&copy["id"])).await;
+    assert_eq!(list.parent_id, Some(list_ids[2]));
+}
+
+#[rocket::async_test]
+async fn invalid() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let (other_list_ids, _) = setup_lists_default(&client, &other_session).await;
+    let task_id = create(&client, &session_response, "Task", list_ids[0], None).await;
+
+    for path in [format!("tasks/{task_id}"), format!("lists/{}", list_ids[0])] {
+        utils::duplicate(
+            &client,
+            &other_session,
+            &path,
+            json!({}),
+            StatusCode::NOT_FOUND,
+        )
+        .await;
+    }
+    for input in [
+        json!({ "list_id": other_list_ids[0] }),
+        json!({ "list_id": list_ids[1], "parent_id": task_id }),
+    ] {
+        utils::duplicate(
+            &client,
+            &session_response,
+            &format!("tasks/{task_id}"),
+            input,
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+    utils::duplicate(
+        &client,
+        &session_response,
+        &format!("lists/{}", list_ids[0]),
+        json!({ "parent_id": other_list_ids[0] }),
+        StatusCode::BAD_REQUEST,
+    )
+    .await;
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    pub fn id(value: &Value) -> Uuid {
+        value.as_str().unwrap().parse().unwrap()
+    }
+
+    pub async fn duplicate(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        path: &str,
+        input: Value,
+        expected_status: StatusCode,
+    ) -> Option<Value> {
+        let res = client
+            .post(&format!("{path}/duplicate"))
+            .bearer_auth(session_response.session_token)
+            .json(&input)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+        if expected_status != StatusCode::CREATED {
+            return None;
+        }
+        Some(res.json().await.expect("Expected correct json response"))
+    }
+}
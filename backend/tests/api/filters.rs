@@ -0,0 +1,333 @@
+#![cfg(test)]
+
+crate::test_crud! {
+    model_path: "filters",
+    model_plural: filters,
+    get: {
+        response_type: types::GetFilterResponse
+    },
+    post: {
+        valid_item(_client, _session_response) {
+            json!({
+                "title": "Work today",
+                "query": "today & #work",
+            })
+        },
+        test_cases: {
+            valid_0(_client, _session_response, StatusCode::CREATED) {
+                json!({
+                    "title": "Work today",
+                    "query": "(today | overdue) & #work & !completed",
+                })
+            },
+            valid_1(_client, _session_response, StatusCode::CREATED) {
+                json!({
+                    "title": "Errands",
+                    "query": "@errands | search: groceries",
+                })
+            },
+
+            invalid_0(_client, _session_response, StatusCode::UNPROCESSABLE_ENTITY) {
+                json!({
+                    "title": "Work today",
+                    "query": 5,
+                })
+            },
+            invalid_1(_client, _session_response, StatusCode::BAD_REQUEST) {
+                json!({
+                    "title": "Work today",
+                    "query": "(today | overdue",
+                })
+            },
+            invalid_2(_client, _session_response, StatusCode::BAD_REQUEST) {
+                json!({
+                    "title": "Work today",
+                    "query": "someday & #work",
+                })
+            }
+        }
+    },
+    patch: {
+        valid_changes(_client, _session_response) {
+            json!({
+                "title": "Urgent",
+                "query": "p1 & !completed",
+            })
+        },
+        test_cases: {
+            valid_0(_client, _session_response, StatusCode::OK) {
+                json!({
+                    "title": "Urgent",
+                    "query": "p1 & !completed",
+                })
+            },
+            valid_1(_client, _session_response, StatusCode::OK) {
+                json!({
+                    "query": "due before: next week",
+                })
+            },
+
+            invalid_0(_client, _session_response, StatusCode::BAD_REQUEST) {
+                json!({
+                    "query": "today &",
+                })
+            },
+            invalid_1(_client, _session_response, StatusCode::BAD_REQUEST) {
+                json!({
+                    "title": null,
+                })
+            }
+        }
+    },
+    default_items: {
+        json!({
+            "title": "Today",
+            "query": "today | overdue",
+        }),
+        json!({
+            "title": "Work",
+            "query": "#work & !completed",
+        }),
+        json!({
+            "title": "This week",
+            "query": "next 7 days",
+        })
+    }
+}
+
+pub mod evaluate {
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use time::{Date, Duration, OffsetDateTime};
+    use uuid::Uuid;
+
+    use crate::{
+        api::{
+            auth::email::utils::{
+                email_register_and_login_user, email_register_and_login_user_default,
+                SessionResponse,
+            },
+            labels::utils::setup_labels_default,
+            lists::utils::setup_lists_default,
+            tasks::{labels::utils::add_label, lists::utils::create_task},
+        },
+        commons::{self, http_client::HttpClient},
+    };
+
+    async fn create_due_task(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        list_id: Uuid,
+        title: &str,
+        date: Date,
+        completed: bool,
+    ) -> Uuid {
+        create_task(
+            client,
+            session_response,
+            &json!({
+                "title": title,
+                "due_at": format!("{date}T12:00:00.000000000Z"),
+                "due_text": "some day",
+                "completed": completed
+            }),
+            list_id,
+        )
+        .await
+    }
+
+    #[rocket::async_test]
+    pub async fn evaluate_filters() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        // Lists are "Grocery list", "Todo list", ...
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        // Labels are "Important", "Backburner", ...
+        let (label_ids, _) = setup_labels_default(&client, &session_response).await;
+        let today = OffsetDateTime::now_utc().date();
+        let milk_id = create_due_task(
+            &client,
+            &session_response,
+            list_ids[0],
+            "Buy milk",
+            today,
+            false,
+        )
+        .await;
+        let report_id = create_due_task(
+            &client,
+            &session_response,
+            list_ids[1],
+            "Write report",
+            today - Duration::days(3),
+            false,
+        )
+        .await;
+        let bread_id = create_due_task(
+            &client,
+            &session_response,
+            list_ids[0],
+            "Buy bread",
+            today + Duration::days(1),
+            true,
+        )
+        .await;
+        let eggs_id = create_due_task(
+            &client,
+            &session_response,
+            list_ids[0],
+            "Buy eggs",
+            today + Duration::days(20),
+            false,
+        )
+        .await;
+        add_label(&client, &session_response, milk_id, label_ids[0]).await;
+        add_label(&client, &session_response, eggs_id, label_ids[1]).await;
+
+        for (query, expected) in [
+            ("(today | overdue) & !completed", vec![report_id, milk_id]),
+            ("#grocery list & !completed", vec![milk_id, eggs_id]),
+            ("@important | @backburner", vec![milk_id, eggs_id]),
+            ("next 7 days & #Grocery list", vec![milk_id, bread_id]),
+            ("tomorrow | completed", vec![bread_id]),
+            ("search: buy & !(today | tomorrow)", vec![eggs_id]),
+            ("due before: today", vec![report_id, milk_id]),
+            ("due after: in 10 days", vec![eggs_id]),
+            ("#Todo list & @important", vec![]),
+        ] {
+            let filter_id = utils::create_filter(&client, &session_response, query).await;
+            let items = utils::get_tasks(&client, &session_response, filter_id, StatusCode::OK)
+                .await
+                .expect("Expected tasks");
+            assert_eq!(items, expected, "Filter '{query}'");
+        }
+    }
+
+    #[rocket::async_test]
+    pub async fn evaluate_other_users_filter() {
+        let client = commons::setup().await;
+        let (alice_session_response, _) = email_register_and_login_user(&client, "alice").await;
+        let (bob_session_response, _) = email_register_and_login_user(&client, "bob").await;
+
+        let (list_ids, _) = setup_lists_default(&client, &bob_session_response).await;
+        create_due_task(
+            &client,
+            &bob_session_response,
+            list_ids[0],
+            "Buy milk",
+            OffsetDateTime::now_utc().date(),
+            false,
+        )
+        .await;
+
+        let filter_id = utils::create_filter(&client, &alice_session_response, "all").await;
+        let items = utils::get_tasks(&client, &alice_session_response, filter_id, StatusCode::OK)
+            .await
+            .expect("Expected tasks");
+        assert!(items.is_empty());
+
+        utils::get_tasks(
+            &client,
+            &bob_session_response,
+            filter_id,
+            StatusCode::NOT_FOUND,
+        )
+        .await;
+    }
+
+    #[rocket::async_test]
+    pub async fn nested_too_deeply() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let nested = format!("{}today{}", "(".repeat(20), ")".repeat(20));
+        utils::create_filter(&client, &session_response, &nested).await;
+        for query in [
+            format!("{}today{}", "(".repeat(100), ")".repeat(100)),
+            format!("{}today", "!".repeat(100)),
+            "(".repeat(300_000),
+            vec!["today"; 1000].join(" | "),
+        ] {
+            let res = client
+                .post("filters")
+                .bearer_auth(session_response.session_token)
+                .json(&json!({ "title": "Deep", "query": query }))
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    pub mod utils {
+        use reqwest::StatusCode;
+        use serde_json::json;
+        use uuid::Uuid;
+
+        use crate::{
+            api::{auth::email::utils::SessionResponse, tasks::types::GetTaskResponse},
+            commons::{
+                http_client::HttpClient,
+                utils::rest::{GetAllResponse, PostResponse},
+            },
+        };
+
+        pub async fn create_filter(
+            client: &HttpClient,
+            session_response: &SessionResponse,
+            query: &str,
+        ) -> Uuid {
+            let res = client
+                .post("filters")
+                .bearer_auth(session_response.session_token)
+                .json(&json!({ "title": query, "query": query }))
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), StatusCode::CREATED);
+            res.json::<PostResponse>()
+                .await
+                .expect("Expected json response")
+                .id
+        }
+
+        /// Returns the ids of the tasks matching the filter, in order.
+        pub async fn get_tasks(
+            client: &HttpClient,
+            session_response: &SessionResponse,
+            filter_id: Uuid,
+            status: StatusCode,
+        ) -> Option<Vec<Uuid>> {
+            let res = client
+                .get(&format!("filters/{}/tasks", filter_id))
+                .bearer_auth(session_response.session_token)
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), status);
+            if status != StatusCode::OK {
+                return None;
+            }
+            let items = res
+                .json::<GetAllResponse<GetTaskResponse>>()
+                .await
+                .expect("Expected json response")
+                .items;
+            Some(items.into_iter().map(|item| item.id).collect())
+        }
+    }
+}
+
+pub mod types {
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct GetFilterResponse {
+        pub id: Uuid,
+        pub user_id: Uuid,
+        pub title: String,
+        pub query: String,
+    }
+}
@@ -101,6 +101,53 @@ crate::test_crud! {
     }
 }
 
+pub mod ordering {
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::{types::GetLabelResponse, utils::setup_labels_default};
+    use crate::{
+        api::auth::email::utils::email_register_and_login_user_default,
+        commons::{
+            self,
+            http_client::{APIClient, APIRequestBuilder},
+        },
+    };
+
+    #[rocket::async_test]
+    pub async fn move_labels() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (ids, _) = setup_labels_default(&client, &session_response).await;
+        let api = client.api().path("labels").auth(&session_response);
+        let order = || async {
+            let labels = api.get_all::<GetLabelResponse>().await.items;
+            labels
+                .into_iter()
+                .map(|label| label.id)
+                .collect::<Vec<Uuid>>()
+        };
+        assert_eq!(order().await, ids);
+
+        api.patch(ids[0], json!({ "move_after": ids[2] })).await;
+        assert_eq!(order().await, vec![ids[1], ids[2], ids[0], ids[3]]);
+
+        api.patch(ids[3], json!({ "move_before": ids[1] })).await;
+        assert_eq!(order().await, vec![ids[3], ids[1], ids[2], ids[0]]);
+
+        let res = client
+            .patch(&format!("labels/{}", ids[0]))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "move_before": Uuid::new_v4() }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
 pub mod types {
     use serde::{Deserialize, Serialize};
     use uuid::Uuid;
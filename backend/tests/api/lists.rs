@@ -135,6 +135,355 @@ crate::test_tree_crud! {
     rud_setup: utils::rud_setup
 }
 
+pub mod ordering {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::{
+        tree::utils::{get_item, reparent},
+        types::GetListResponse,
+        utils::setup_lists_default,
+    };
+    use crate::{
+        api::auth::email::utils::email_register_and_login_user_default,
+        commons::{
+            self,
+            http_client::{APIClient, APIRequestBuilder},
+        },
+    };
+
+    #[rocket::async_test]
+    pub async fn move_lists() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (ids, _) = setup_lists_default(&client, &session_response).await;
+        let api = client.api().path("lists").auth(&session_response);
+        let order = || async {
+            let lists = api.get_all::<GetListResponse>().await.items;
+            lists.into_iter().map(|list| list.id).collect::<Vec<Uuid>>()
+        };
+        assert_eq!(order().await, ids);
+
+        let last = ids.len() - 1;
+        api.patch(ids[last], json!({ "move_before": ids[0] })).await;
+        let mut expected = vec![ids[last]];
+        expected.extend(&ids[..last]);
+        assert_eq!(order().await, expected);
+
+        // Sublists are ordered under their parent
+        reparent(&client, &session_response, ids[2], ids[0]).await;
+        reparent(&client, &session_response, ids[1], ids[0]).await;
+        let parent = get_item(&client, &session_response, ids[0]).await;
+        assert_eq!(parent.child_ids, vec![ids[2], ids[1]]);
+
+        api.patch(ids[1], json!({ "move_before": ids[2] })).await;
+        let parent = get_item(&client, &session_response, ids[0]).await;
+        assert_eq!(parent.child_ids, vec![ids[1], ids[2]]);
+    }
+}
+
+pub mod hierarchy {
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use super::{tree::utils::reparent, utils::setup_lists_default};
+    use crate::{api::auth::email::utils::email_register_and_login_user_default, commons};
+
+    #[rocket::async_test]
+    pub async fn cycles() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (ids, _) = setup_lists_default(&client, &session_response).await;
+        reparent(&client, &session_response, ids[1], ids[0]).await;
+        reparent(&client, &session_response, ids[2], ids[1]).await;
+
+        for parent_id in [ids[0], ids[1], ids[2]] {
+            let res = client
+                .patch(&format!("lists/{}", ids[0]))
+                .bearer_auth(session_response.session_token)
+                .json(&json!({ "parent_id": parent_id }))
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(
+                res.status(),
+                StatusCode::BAD_REQUEST,
+                "Moving under {parent_id}"
+            );
+        }
+        reparent(&client, &session_response, ids[2], ids[0]).await;
+    }
+}
+
+pub mod nested {
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use super::{tree::utils::reparent, utils::setup_lists_default};
+    use crate::{
+        api::{
+            auth::email::utils::{email_register_and_login_user_default, SessionResponse},
+            tasks::ordering::utils::create,
+        },
+        commons::{self, http_client::HttpClient},
+    };
+
+    #[rocket::async_test]
+    pub async fn list_tree() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (ids, _) = setup_lists_default(&client, &session_response).await;
+        reparent(&client, &session_response, ids[1], ids[0]).await;
+        reparent(&client, &session_response, ids[2], ids[1]).await;
+        create(&client, &session_response, "Open", ids[0], None).await;
+        create(&client, &session_response, "Open", ids[0], None).await;
+        let done = create(&client, &session_response, "Done", ids[0], None).await;
+        create(&client, &session_response, "Nested", ids[2], None).await;
+        let res = client
+            .patch(&format!("tasks/{done}"))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "completed": true }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let tree = get_tree(&client, &session_response, "").await;
+        let roots = tree.as_array().unwrap();
+        assert_eq!(roots.len(), ids.len() - 2);
+        assert_eq!(roots[0]["id"], ids[0].to_string());
+        assert_eq!(roots[0]["task_count"], 2);
+        assert_eq!(roots[0]["completed_task_count"], 1);
+        assert_eq!(roots[1]["id"], ids[3].to_string());
+        assert_eq!(roots[1]["task_count"], 0);
+        let child = &roots[0]["children"][0];
+        assert_eq!(child["id"], ids[1].to_string());
+        assert_eq!(child["children"][0]["id"], ids[2].to_string());
+        assert_eq!(child["children"][0]["task_count"], 1);
+
+        let tree = get_tree(&client, &session_response, "?max_depth=0").await;
+        let roots = tree.as_array().unwrap();
+        assert_eq!(roots[0]["children"], Value::Array(vec![]));
+        assert_eq!(roots[0]["child_ids"][0], ids[1].to_string());
+    }
+
+    async fn get_tree(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        query: &str,
+    ) -> Value {
+        let res = client
+            .get(&format!("lists/tree{query}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+}
+
+pub mod strategies {
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::{
+        tree::utils::{get_item, reparent},
+        utils::setup_lists_default,
+    };
+    use crate::{
+        api::{
+            actions,
+            auth::email::utils::{
+                email_register_and_login_user, email_register_and_login_user_default,
+                SessionResponse,
+            },
+            dependencies::utils::get_task,
+            tasks::ordering::utils::{create, top_level},
+        },
+        commons::{
+            self,
+            http_client::{APIClient, APIRequestBuilder, HttpClient},
+        },
+    };
+
+    #[rocket::async_test]
+    pub async fn cascade() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (ids, _) = setup_lists_default(&client, &session_response).await;
+        reparent(&client, &session_response, ids[1], ids[0]).await;
+        let task = create(&client, &session_response, "Task", ids[0], None).await;
+        let nested = create(&client, &session_response, "Nested", ids[1], None).await;
+
+        let resp = delete(&client, &session_response, ids[0], "", StatusCode::OK)
+            .await
+            .unwrap();
+        assert_eq!(
+            sorted(&resp["deleted_list_ids"]),
+            sorted(&json!([ids[0], ids[1]]))
+        );
+        assert_eq!(
+            sorted(&resp["deleted_task_ids"]),
+            sorted(&json!([task, nested]))
+        );
+        assert_eq!(resp["moved_task_ids"], json!([]));
+        assert_eq!(resp["promoted_list_ids"], json!([]));
+    }
+
+    #[rocket::async_test]
+    pub async fn move_tasks() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (ids, _) = setup_lists_default(&client, &session_response).await;
+        reparent(&client, &session_response, ids[1], ids[0]).await;
+        let mut existing = vec![];
+        for title in ["First", "Second"] {
+            existing.push(create(&client, &session_response, title, ids[2], None).await);
+        }
+        let task = create(&client, &session_response, "Task", ids[0], None).await;
+        let subtask = create(&client, &session_response, "Subtask", ids[0], Some(task)).await;
+        let nested = create(&client, &session_response, "Nested", ids[1], None).await;
+
+        let resp = delete(
+            &client,
+            &session_response,
+            ids[0],
+            &format!("?strategy=move_tasks&target_list_id={}", ids[2]),
+            StatusCode::OK,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            sorted(&resp["deleted_list_ids"]),
+            sorted(&json!([ids[0], ids[1]]))
+        );
+        assert_eq!(resp["deleted_task_ids"], json!([]));
+        assert_eq!(
+            sorted(&resp["moved_task_ids"]),
+            sorted(&json!([task, subtask, nested]))
+        );
+        let moved = get_task(&client, &session_response, subtask).await;
+        assert_eq!(moved["list_id"], json!(ids[2]));
+        assert_eq!(moved["parent_id"], json!(task));
+        // The moved tasks go after the ones already in the target list
+        assert_eq!(
+            top_level(&client, &session_response, ids[2]).await,
+            [existing[0], existing[1], task, nested]
+        );
+
+        // Undoing brings back the lists and their tasks
+        actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+        assert_eq!(
+            get_item(&client, &session_response, ids[0]).await.child_ids,
+            [ids[1]]
+        );
+        let task = get_task(&client, &session_response, nested).await;
+        assert_eq!(task["list_id"], json!(ids[1]));
+    }
+
+    #[rocket::async_test]
+    pub async fn promote_sublists() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (ids, _) = setup_lists_default(&client, &session_response).await;
+        reparent(&client, &session_response, ids[1], ids[0]).await;
+        reparent(&client, &session_response, ids[2], ids[1]).await;
+        reparent(&client, &session_response, ids[3], ids[1]).await;
+        let sibling = client
+            .api()
+            .path("lists")
+            .auth(&session_response)
+            .post(json!({ "title": "Sibling", "color": "#ffa783" }))
+            .await
+            .id;
+        reparent(&client, &session_response, sibling, ids[0]).await;
+        let task = create(&client, &session_response, "Task", ids[1], None).await;
+        let nested = create(&client, &session_response, "Nested", ids[2], None).await;
+
+        let resp = delete(
+            &client,
+            &session_response,
+            ids[1],
+            "?strategy=promote_sublists",
+            StatusCode::OK,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp["deleted_list_ids"], json!([ids[1]]));
+        assert_eq!(resp["deleted_task_ids"], json!([task]));
+        assert_eq!(
+            sorted(&resp["promoted_list_ids"]),
+            sorted(&json!([ids[2], ids[3]]))
+        );
+        // The sublists go after the siblings of the deleted list
+        let list = get_item(&client, &session_response, ids[0]).await;
+        assert_eq!(list.child_ids, [sibling, ids[2], ids[3]]);
+        let task = get_task(&client, &session_response, nested).await;
+        assert_eq!(task["list_id"], json!(ids[2]));
+    }
+
+    #[rocket::async_test]
+    pub async fn invalid() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (other_session, _) = email_register_and_login_user(&client, "other").await;
+        let (ids, _) = setup_lists_default(&client, &session_response).await;
+        let (other_ids, _) = setup_lists_default(&client, &other_session).await;
+        reparent(&client, &session_response, ids[1], ids[0]).await;
+
+        for query in [
+            String::from("?strategy=purge"),
+            String::from("?strategy=move_tasks"),
+            format!("?target_list_id={}", ids[2]),
+            format!("?strategy=move_tasks&target_list_id={}", ids[1]),
+            format!("?strategy=move_tasks&target_list_id={}", other_ids[0]),
+        ] {
+            delete(
+                &client,
+                &session_response,
+                ids[0],
+                &query,
+                StatusCode::BAD_REQUEST,
+            )
+            .await;
+        }
+        delete(&client, &other_session, ids[0], "", StatusCode::NOT_FOUND).await;
+        assert_eq!(
+            get_item(&client, &session_response, ids[0]).await.child_ids,
+            [ids[1]]
+        );
+    }
+
+    fn sorted(ids: &Value) -> Vec<Uuid> {
+        let mut ids: Vec<Uuid> = serde_json::from_value(ids.clone()).unwrap();
+        ids.sort();
+        ids
+    }
+
+    async fn delete(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        list_id: Uuid,
+        query: &str,
+        expected_status: StatusCode,
+    ) -> Option<Value> {
+        let res = client
+            .delete(&format!("lists/{list_id}{query}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+        if expected_status != StatusCode::OK {
+            return None;
+        }
+        Some(res.json().await.expect("Expected correct json response"))
+    }
+}
+
 pub mod types {
     use serde::{Deserialize, Serialize};
     use uuid::Uuid;
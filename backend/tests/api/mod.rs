@@ -1,6 +1,21 @@
+pub mod actions;
+pub mod archive;
+pub mod attachments;
 pub mod auth;
+pub mod bulk;
+pub mod comments;
+pub mod dependencies;
+pub mod duplicate;
+pub mod filters;
 pub mod general;
 pub mod labels;
 pub mod lists;
+pub mod ownership;
+pub mod reminders;
+pub mod search;
 pub mod tasks;
+pub mod templates;
+pub mod time_entries;
+pub mod trash;
 pub mod users;
+pub mod views;
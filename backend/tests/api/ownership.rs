@@ -0,0 +1,166 @@
+#![cfg(test)]
+
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+use crate::{
+    api::{
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default, SessionResponse,
+        },
+        dependencies::utils::get_task,
+        lists::utils::setup_lists_default,
+        tasks::ordering::utils::create,
+    },
+    commons::{
+        self,
+        http_client::{APIClient, APIRequestBuilder, HttpClient},
+    },
+};
+
+#[rocket::async_test]
+async fn tasks() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let (other_list_ids, _) = setup_lists_default(&client, &other_session).await;
+    let task_id = create(&client, &session_response, "Mine", list_ids[0], None).await;
+    let other_task_id = create(&client, &other_session, "Theirs", other_list_ids[0], None).await;
+
+    let status = send(
+        &client,
+        &session_response,
+        Method::POST,
+        "tasks",
+        json!({
+            "title": "Sneaky",
+            "list_id": other_list_ids[0],
+            "due_text": "Next Monday"
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let status = send(
+        &client,
+        &session_response,
+        Method::POST,
+        "tasks",
+        json!({
+            "title": "Sneaky",
+            "list_id": other_list_ids[0],
+            "parent_id": other_task_id,
+            "due_text": "Next Monday"
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let path = format!("tasks/{task_id}");
+    for body in [
+        json!({ "list_id": other_list_ids[0] }),
+        json!({ "parent_id": other_task_id }),
+        json!({ "list_id": other_list_ids[0], "parent_id": other_task_id }),
+    ] {
+        let status = send(
+            &client,
+            &session_response,
+            Method::PATCH,
+            &path,
+            body.clone(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "Patching with {body}");
+    }
+    let task = get_task(&client, &session_response, task_id).await;
+    assert_eq!(task["list_id"], list_ids[0].to_string());
+    assert_eq!(task["parent_id"], Value::Null);
+    let task = get_task(&client, &other_session, other_task_id).await;
+    assert_eq!(task["child_ids"], json!([]));
+}
+
+#[rocket::async_test]
+async fn labels() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "Mine", list_ids[0], None).await;
+    let other_label_id = client
+        .api()
+        .path("labels")
+        .auth(&other_session)
+        .post(json!({ "title": "Theirs", "color": "#ffa783" }))
+        .await
+        .id;
+
+    let status = send(
+        &client,
+        &session_response,
+        Method::POST,
+        &format!("tasks/{task_id}/labels"),
+        json!({ "id": other_label_id }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let task = get_task(&client, &session_response, task_id).await;
+    assert_eq!(task["label_ids"], json!([]));
+}
+
+#[rocket::async_test]
+async fn lists() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let (other_list_ids, _) = setup_lists_default(&client, &other_session).await;
+
+    let status = send(
+        &client,
+        &session_response,
+        Method::POST,
+        "lists",
+        json!({
+            "title": "Sneaky",
+            "color": "#ffa783",
+            "parent_id": other_list_ids[0]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let status = send(
+        &client,
+        &session_response,
+        Method::PATCH,
+        &format!("lists/{}", list_ids[0]),
+        json!({ "parent_id": other_list_ids[0] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let res = client
+        .get(&format!("lists/{}", other_list_ids[0]))
+        .bearer_auth(other_session.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    let list: Value = res.json().await.expect("Expected correct json response");
+    assert_eq!(list["child_ids"], json!([]));
+}
+
+async fn send(
+    client: &HttpClient,
+    session_response: &SessionResponse,
+    method: Method,
+    path: &str,
+    body: Value,
+) -> StatusCode {
+    client
+        .request(method, path)
+        .bearer_auth(session_response.session_token)
+        .json(&body)
+        .send()
+        .await
+        .expect("Expected response")
+        .status()
+}
@@ -0,0 +1,431 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use time::{format_description::well_known::Iso8601, Duration, OffsetDateTime};
+
+use crate::{
+    api::{
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        lists::utils::setup_lists_default,
+        tasks::lists::utils::create_task,
+    },
+    commons,
+};
+
+fn iso(time: OffsetDateTime) -> String {
+    time.format(&Iso8601::DEFAULT).unwrap()
+}
+
+#[rocket::async_test]
+async fn crud() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let due_at = OffsetDateTime::now_utc() + Duration::days(1);
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &json!({ "title": "Task", "due_at": iso(due_at), "due_text": "tomorrow" }),
+        list_ids[0],
+    )
+    .await;
+
+    let remind_at = OffsetDateTime::now_utc() + Duration::days(2);
+    let absolute_id = utils::create_reminder(
+        &client,
+        &session_response,
+        task_id,
+        &json!({ "remind_at": iso(remind_at), "channel": "log" }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let relative_id = utils::create_reminder(
+        &client,
+        &session_response,
+        task_id,
+        &json!({
+            "offset_minutes": -30,
+            "channel": "email",
+            "target": "someone@example.com"
+        }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    let reminders = utils::get_reminders(&client, &session_response, task_id).await;
+    let ids: Vec<&str> = reminders
+        .iter()
+        .map(|reminder| reminder["id"].as_str().unwrap())
+        .collect();
+    // Ordered by when they fire
+    assert_eq!(ids, vec![relative_id.to_string(), absolute_id.to_string()]);
+    assert_eq!(reminders[0]["offset_minutes"], -30);
+    assert_eq!(reminders[0]["target"], "someone@example.com");
+    assert_eq!(reminders[0]["delivered_at"], Value::Null);
+    assert_ne!(reminders[0]["fire_at"], Value::Null);
+    assert_eq!(reminders[1]["remind_at"], reminders[1]["fire_at"]);
+
+    let res = client
+        .delete(&format!("tasks/{task_id}/reminders/{absolute_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    let reminders = utils::get_reminders(&client, &session_response, task_id).await;
+    assert_eq!(reminders.len(), 1);
+
+    let res = client
+        .delete(&format!("tasks/{task_id}/reminders/{absolute_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[rocket::async_test]
+async fn invalid() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &json!({
+            "title": "Task",
+            "due_at": "2023-10-19T10:23:00.000000000Z",
+            "due_text": "some day"
+        }),
+        list_ids[0],
+    )
+    .await;
+    let remind_at = iso(OffsetDateTime::now_utc());
+
+    for input in [
+        json!({ "channel": "log" }),
+        json!({ "remind_at": remind_at, "offset_minutes": 5, "channel": "log" }),
+        json!({ "remind_at": "tomorrow", "channel": "log" }),
+        json!({ "remind_at": remind_at, "channel": "pigeon" }),
+        json!({ "remind_at": remind_at, "channel": "email" }),
+        json!({ "remind_at": remind_at, "channel": "email", "target": "nobody" }),
+        json!({ "remind_at": remind_at, "channel": "webhook", "target": "ftp://example.com" }),
+    ] {
+        let res = client
+            .post(&format!("tasks/{task_id}/reminders"))
+            .bearer_auth(session_response.session_token)
+            .json(&input)
+            .send()
+            .await
+            .expect("Expected response");
+        assert!(
+            res.status().is_client_error(),
+            "Expected {input} to be rejected"
+        );
+    }
+
+    // Webhooks can't reach the server's own network
+    for target in [
+        "http://localhost:8000/hook",
+        "http://127.0.0.2/hook",
+        "http://10.0.0.1/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "http://0.0.0.0/hook",
+        "http://[::1]/hook",
+        "http://[::ffff:192.168.0.1]/hook",
+    ] {
+        utils::create_reminder(
+            &client,
+            &session_response,
+            task_id,
+            &json!({ "remind_at": remind_at, "channel": "webhook", "target": target }),
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    utils::create_reminder(
+        &client,
+        &other_session,
+        task_id,
+        &json!({ "remind_at": remind_at, "channel": "log" }),
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+    let res = client
+        .get(&format!("tasks/{task_id}/reminders"))
+        .bearer_auth(other_session.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[rocket::async_test]
+async fn webhook_delivery() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let due_at = OffsetDateTime::now_utc() + Duration::minutes(30);
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &json!({ "title": "Call back", "due_at": iso(due_at), "due_text": "in 30 minutes" }),
+        list_ids[0],
+    )
+    .await;
+
+    let (url, mut requests) = utils::webhook_server(vec![200]).await;
+    let reminder_id = utils::create_reminder(
+        &client,
+        &session_response,
+        task_id,
+        &json!({ "offset_minutes": -60, "channel": "webhook", "target": url }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    let request = utils::next_request(&mut requests).await;
+    assert!(request.starts_with("POST "));
+    assert!(request
+        .to_lowercase()
+        .contains(&format!("idempotency-key: {reminder_id}:")));
+    assert!(request.contains(&task_id.to_string()));
+    assert!(request.contains("Call back"));
+
+    let reminder = utils::wait_for_delivery(&client, &session_response, task_id).await;
+    assert_eq!(reminder["attempts"], 0);
+    assert_eq!(reminder["last_error"], Value::Null);
+
+    // Delivered once only
+    rocket::tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    assert!(requests.try_recv().is_err());
+}
+
+#[rocket::async_test]
+async fn webhook_retry() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &json!({
+            "title": "Task",
+            "due_at": "2023-10-19T10:23:00.000000000Z",
+            "due_text": "some day"
+        }),
+        list_ids[0],
+    )
+    .await;
+
+    let (url, mut requests) = utils::webhook_server(vec![500, 200]).await;
+    utils::create_reminder(
+        &client,
+        &session_response,
+        task_id,
+        &json!({
+            "remind_at": iso(OffsetDateTime::now_utc() - Duration::minutes(1)),
+            "channel": "webhook",
+            "target": url
+        }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    let first = utils::next_request(&mut requests).await;
+    let second = utils::next_request(&mut requests).await;
+    let key = |request: &str| {
+        request
+            .lines()
+            .find(|line| line.to_lowercase().starts_with("idempotency-key"))
+            .map(String::from)
+    };
+    assert!(key(&first).is_some());
+    assert_eq!(key(&first), key(&second));
+
+    let reminder = utils::wait_for_delivery(&client, &session_response, task_id).await;
+    assert_eq!(reminder["last_error"], Value::Null);
+}
+
+#[rocket::async_test]
+async fn completed_tasks_are_skipped() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &json!({
+            "title": "Task",
+            "due_at": "2023-10-19T10:23:00.000000000Z",
+            "due_text": "some day",
+            "completed": true
+        }),
+        list_ids[0],
+    )
+    .await;
+
+    let (url, mut requests) = utils::webhook_server(vec![200]).await;
+    utils::create_reminder(
+        &client,
+        &session_response,
+        task_id,
+        &json!({
+            "remind_at": iso(OffsetDateTime::now_utc() - Duration::minutes(1)),
+            "channel": "webhook",
+            "target": url
+        }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    rocket::tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert!(requests.try_recv().is_err());
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use rocket::tokio::{
+        self,
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        sync::mpsc,
+    };
+    use serde::Deserialize;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    #[derive(Deserialize)]
+    struct PostResponse {
+        id: Uuid,
+    }
+
+    pub async fn create_reminder(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        input: &Value,
+        expected_status: StatusCode,
+    ) -> Option<Uuid> {
+        let res = client
+            .post(&format!("tasks/{task_id}/reminders"))
+            .bearer_auth(session_response.session_token)
+            .json(input)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+        if expected_status != StatusCode::CREATED {
+            return None;
+        }
+        let response = res
+            .json::<PostResponse>()
+            .await
+            .expect("Expected correct json response");
+        Some(response.id)
+    }
+
+    pub async fn get_reminders(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+    ) -> Vec<Value> {
+        let res = client
+            .get(&format!("tasks/{task_id}/reminders"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+
+    /// Waits until the task's first reminder has been delivered and returns it.
+    pub async fn wait_for_delivery(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+    ) -> Value {
+        for _ in 0..50 {
+            let reminders = get_reminders(client, session_response, task_id).await;
+            if reminders[0]["delivered_at"] != Value::Null {
+                return reminders[0].clone();
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+        panic!("Reminder was not delivered");
+    }
+
+    /// Starts a webhook receiver answering with the given statuses in turn,
+    /// returns its url and the raw requests it receives.
+    pub async fn webhook_server(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Expected listener");
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for status in statuses {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                loop {
+                    let Ok(read) = stream.read(&mut buf).await else {
+                        return;
+                    };
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                    if request_complete(&request) {
+                        break;
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+                let _ = sender.send(String::from_utf8_lossy(&request).into_owned());
+            }
+        });
+        (url, receiver)
+    }
+
+    fn request_complete(request: &[u8]) -> bool {
+        let text = String::from_utf8_lossy(request);
+        let Some((head, body)) = text.split_once("\r\n\r\n") else {
+            return false;
+        };
+        let length = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().ok())?
+            })
+            .unwrap_or(0);
+        body.len() >= length
+    }
+
+    pub async fn next_request(requests: &mut mpsc::UnboundedReceiver<String>) -> String {
+        tokio::time::timeout(std::time::Duration::from_secs(15), requests.recv())
+            .await
+            .expect("Expected a webhook request in time")
+            .expect("Expected a webhook request")
+    }
+}
@@ -0,0 +1,211 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::json;
+
+use crate::{
+    api::{
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        labels::utils::setup_labels_default,
+        lists::utils::setup_lists_default,
+        tasks::lists::utils::create_task,
+    },
+    commons,
+};
+
+#[rocket::async_test]
+async fn search_tasks() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let title_id = create_task(
+        &client,
+        &session_response,
+        &json!({
+            "title": "Buy groceries",
+            "due_at": "2023-10-19T10:23:00.000000000Z",
+            "due_text": "Next Monday"
+        }),
+        list_ids[1],
+    )
+    .await;
+    let description_id = create_task(
+        &client,
+        &session_response,
+        &json!({
+            "title": "Plan the party",
+            "description": "Pick up the groceries on the way",
+            "due_at": "2023-10-19T10:23:00.000000000Z",
+            "due_text": "Next Monday"
+        }),
+        list_ids[1],
+    )
+    .await;
+    let completed_id = create_task(
+        &client,
+        &session_response,
+        &json!({
+            "title": "Return groceries",
+            "completed": true,
+            "due_at": "2023-10-19T10:23:00.000000000Z",
+            "due_text": "Next Monday"
+        }),
+        list_ids[1],
+    )
+    .await;
+
+    // Title matches rank above description matches, and the
+    // "Grocery list" list matches too since words are stemmed.
+    let results = utils::search(
+        &client,
+        &session_response,
+        &[("q", "grocer")],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected results");
+    let tasks: Vec<_> = results
+        .iter()
+        .filter(|result| result.item_type == "task")
+        .map(|result| result.id)
+        .collect();
+    assert_eq!(tasks, vec![title_id, description_id]);
+    assert!(results
+        .iter()
+        .any(|result| result.item_type == "list" && result.id == list_ids[0]));
+
+    let result = results
+        .iter()
+        .find(|result| result.id == description_id)
+        .unwrap();
+    assert!(result.snippet.contains("<mark>groceries</mark>"));
+    assert_eq!(result.list_id, Some(list_ids[1]));
+    assert_eq!(result.completed, Some(false));
+
+    let results = utils::search(
+        &client,
+        &session_response,
+        &[("q", "return groc"), ("include_completed", "true")],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected results");
+    let ids: Vec<_> = results.iter().map(|result| result.id).collect();
+    assert_eq!(ids, vec![completed_id]);
+}
+
+#[rocket::async_test]
+async fn search_labels() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (label_ids, _) = setup_labels_default(&client, &session_response).await;
+    let results = utils::search(
+        &client,
+        &session_response,
+        &[("q", "Import")],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected results");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].item_type, "label");
+    assert_eq!(results[0].id, label_ids[0]);
+    assert_eq!(results[0].snippet, "<mark>Important</mark>");
+}
+
+#[rocket::async_test]
+async fn search_other_users() {
+    let client = commons::setup().await;
+    let (alice_session_response, _) = email_register_and_login_user(&client, "alice").await;
+    let (bob_session_response, _) = email_register_and_login_user(&client, "bob").await;
+
+    setup_lists_default(&client, &bob_session_response).await;
+    setup_labels_default(&client, &bob_session_response).await;
+
+    let results = utils::search(
+        &client,
+        &alice_session_response,
+        &[("q", "list")],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected results");
+    assert!(results.is_empty());
+}
+
+#[rocket::async_test]
+async fn search_invalid() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    utils::search(
+        &client,
+        &session_response,
+        &[("q", " &|! ")],
+        StatusCode::BAD_REQUEST,
+    )
+    .await;
+
+    let res = client
+        .get("search")
+        .query(&[("q", "milk")])
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+pub mod types {
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct SearchResponse {
+        pub id: Uuid,
+        pub item_type: String,
+        pub title: String,
+        pub snippet: String,
+        pub rank: f32,
+        pub completed: Option<bool>,
+        pub list_id: Option<Uuid>,
+    }
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+
+    use super::types::SearchResponse;
+    use crate::{
+        api::auth::email::utils::SessionResponse,
+        commons::{http_client::HttpClient, utils::rest::GetAllResponse},
+    };
+
+    pub async fn search(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        query: &[(&str, &str)],
+        status: StatusCode,
+    ) -> Option<Vec<SearchResponse>> {
+        let res = client
+            .get("search")
+            .query(query)
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), status);
+        if status != StatusCode::OK {
+            return None;
+        }
+        Some(
+            res.json::<GetAllResponse<SearchResponse>>()
+                .await
+                .expect("Expected json response")
+                .items,
+        )
+    }
+}
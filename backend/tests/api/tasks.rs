@@ -48,6 +48,17 @@ crate::test_crud! {
             },
             invalid_1(_client, _session_response, StatusCode::UNPROCESSABLE_ENTITY) {
                 json!({})
+            },
+            invalid_2(client, session_response, StatusCode::BAD_REQUEST) {
+                use crate::api::lists::utils::setup_lists_default;
+                let (list_ids, _) = setup_lists_default(client, session_response).await;
+                json!({
+                    "list_id": list_ids[0],
+                    "title": "Get groceries task",
+                    "due_at": "2023-10-19T10:23:00.000000000Z",
+                    "due_text": "Every sometimes",
+                    "recurrence": "FREQ=SOMETIMES"
+                })
             }
         }
     },
@@ -166,11 +177,20 @@ crate::test_tree_crud! {
     model_path: "tasks",
     response_type: types::GetTaskResponse,
     valid_item(client, session_response) {
-        use crate::api::lists::utils::setup_lists_default;
-        let (list_ids, _) = setup_lists_default(client, session_response).await;
+        use crate::{
+            api::lists::types::GetListResponse,
+            commons::http_client::{APIClient, APIRequestBuilder},
+        };
+        // Subtasks go in the list of their parent, the first one set up
+        let lists = client
+            .api()
+            .path("lists")
+            .auth(session_response)
+            .get_all::<GetListResponse>()
+            .await;
 
         json!({
-            "list_id": list_ids[0],
+            "list_id": lists.items[0].id,
             "title": "Get groceries task",
             "description": "Get groceries task",
             "due_at": "2023-10-19T10:23:00.000000000Z",
@@ -212,6 +232,10 @@ pub mod types {
         pub completed: bool,
         pub title: String,
         pub description: Option<String>,
+        pub recurrence: Option<String>,
+        pub recurrence_from_completion: bool,
+        pub recurrence_keep_history: bool,
+        pub priority: i16,
         pub child_ids: Vec<Uuid>,
         pub label_ids: Vec<Uuid>,
     }
@@ -474,3 +498,1512 @@ pub mod labels {
         }
     }
 }
+
+pub mod recurrence {
+    use serde_json::json;
+    use time::{macros::datetime, Duration, OffsetDateTime};
+
+    use super::{tree::utils::get_item, types::GetTaskResponse};
+    use crate::{
+        api::{
+            actions::utils::undo,
+            auth::email::utils::email_register_and_login_user_default,
+            lists::utils::setup_lists_default,
+            tasks::{lists::utils::create_task, tree::utils::reparent},
+        },
+        commons::{
+            self,
+            http_client::{APIClient, APIRequestBuilder},
+        },
+    };
+
+    #[rocket::async_test]
+    pub async fn complete_advances_due_date() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let task_id = create_task(
+            &client,
+            &session_response,
+            &json!({
+                "due_at": "2023-10-19T10:23:00.000000000Z",
+                "due_text": "Every Monday and Thursday",
+                "title": "Water the plants",
+                "recurrence": "FREQ=WEEKLY;BYDAY=MO,TH"
+            }),
+            list_ids[0],
+        )
+        .await;
+        let child_id = create_task(
+            &client,
+            &session_response,
+            &json!({
+                "due_at": "2023-10-19T10:00:00.000000000Z",
+                "due_text": "Every Monday and Thursday",
+                "completed": true,
+                "title": "Fill the watering can"
+            }),
+            list_ids[0],
+        )
+        .await;
+        reparent(&client, &session_response, child_id, task_id).await;
+
+        let api = client.api().path("tasks").auth(&session_response);
+        api.patch(task_id, json!({ "completed": true })).await;
+
+        // Thursday's occurrence is done, so the next one is on Monday
+        let task = get_item(&client, &session_response, task_id).await;
+        assert!(!task.completed, "Expected task to be reopened");
+        assert_eq!(task.due_at, datetime!(2023-10-23 10:23));
+        let child = get_item(&client, &session_response, child_id).await;
+        assert!(!child.completed, "Expected subtask to be reset");
+        assert_eq!(child.due_at, datetime!(2023-10-23 10:00));
+
+        // Completing and advancing is undone in one step
+        undo(&client, &session_response, reqwest::StatusCode::OK).await;
+        let task = get_item(&client, &session_response, task_id).await;
+        assert!(!task.completed);
+        assert_eq!(task.due_at, datetime!(2023-10-19 10:23));
+        let child = get_item(&client, &session_response, child_id).await;
+        assert!(child.completed);
+    }
+
+    #[rocket::async_test]
+    pub async fn complete_keeps_history() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let task_id = create_task(
+            &client,
+            &session_response,
+            &json!({
+                "due_at": "2023-10-02T09:00:00.000000000Z",
+                "due_text": "First Monday of the month",
+                "title": "Pay rent",
+                "recurrence": "FREQ=MONTHLY;BYDAY=1MO;COUNT=2",
+                "recurrence_keep_history": true
+            }),
+            list_ids[0],
+        )
+        .await;
+
+        let api = client.api().path("tasks").auth(&session_response);
+        api.patch(task_id, json!({ "completed": true })).await;
+
+        let task = get_item(&client, &session_response, task_id).await;
+        assert!(task.completed, "Expected completed task to be kept");
+        assert_eq!(task.recurrence, None);
+
+        let tasks = api.get_all::<GetTaskResponse>().await;
+        let next: Vec<_> = tasks
+            .items
+            .into_iter()
+            .filter(|task| task.title == "Pay rent" && task.id != task_id)
+            .collect();
+        assert_eq!(next.len(), 1, "Expected the next occurrence to be created");
+        let next = &next[0];
+        assert!(!next.completed);
+        assert!(next.recurrence_keep_history);
+        assert_eq!(next.due_at, datetime!(2023-11-06 09:00));
+        assert_eq!(
+            next.recurrence.as_deref(),
+            Some("FREQ=MONTHLY;COUNT=1;BYDAY=1MO")
+        );
+
+        // That was the last occurrence
+        api.patch(next.id, json!({ "completed": true })).await;
+        let tasks = api.get_all::<GetTaskResponse>().await;
+        assert_eq!(tasks.items.len(), 2);
+        assert!(tasks.items.iter().all(|task| task.completed));
+    }
+
+    #[rocket::async_test]
+    pub async fn complete_from_completion_date() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let task_id = create_task(
+            &client,
+            &session_response,
+            &json!({
+                "due_at": "2020-01-01T08:00:00.000000000Z",
+                "due_text": "Every other day",
+                "title": "Go for a run",
+                "recurrence": "FREQ=DAILY;INTERVAL=2",
+                "recurrence_from_completion": true
+            }),
+            list_ids[0],
+        )
+        .await;
+
+        client
+            .api()
+            .path("tasks")
+            .auth(&session_response)
+            .patch(task_id, json!({ "completed": true }))
+            .await;
+
+        let task = get_item(&client, &session_response, task_id).await;
+        let today = OffsetDateTime::now_utc().date();
+        assert!(!task.completed);
+        assert_eq!(
+            task.due_at,
+            (today + Duration::days(2)).with_hms(8, 0, 0).unwrap()
+        );
+    }
+}
+
+pub mod due_dates {
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use time::{Duration, OffsetDateTime, Weekday};
+
+    use super::tree::utils::get_item;
+    use crate::{
+        api::{
+            auth::email::utils::email_register_and_login_user_default,
+            lists::utils::setup_lists_default, tasks::lists::utils::create_task,
+        },
+        commons::{
+            self,
+            http_client::{APIClient, APIRequestBuilder},
+        },
+    };
+
+    #[rocket::async_test]
+    pub async fn post_with_due_text() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        utils::set_timezone(&client, &session_response, "Asia/Kolkata", StatusCode::OK).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let task_id = create_task(
+            &client,
+            &session_response,
+            &json!({
+                "due_text": "tomorrow 5pm",
+                "title": "Pick up the package"
+            }),
+            list_ids[0],
+        )
+        .await;
+
+        // 5pm in Kolkata is 11:30 UTC
+        let local_today = (OffsetDateTime::now_utc() + Duration::minutes(330)).date();
+        let task = get_item(&client, &session_response, task_id).await;
+        assert_eq!(
+            task.due_at,
+            (local_today + Duration::days(1))
+                .with_hms(11, 30, 0)
+                .unwrap()
+        );
+    }
+
+    #[rocket::async_test]
+    pub async fn post_with_repeating_due_text() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let task_id = create_task(
+            &client,
+            &session_response,
+            &json!({
+                "due_text": "every monday and thursday at 9am",
+                "title": "Water the plants"
+            }),
+            list_ids[0],
+        )
+        .await;
+
+        let task = get_item(&client, &session_response, task_id).await;
+        assert_eq!(task.recurrence.as_deref(), Some("FREQ=WEEKLY;BYDAY=MO,TH"));
+        assert!(matches!(
+            task.due_at.weekday(),
+            Weekday::Monday | Weekday::Thursday
+        ));
+        assert_eq!(task.due_at.time(), time::macros::time!(9:00));
+    }
+
+    #[rocket::async_test]
+    pub async fn post_with_invalid_due_text() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let res = client
+            .post("tasks")
+            .bearer_auth(session_response.session_token)
+            .json(&json!({
+                "list_id": list_ids[0],
+                "due_text": "whenever I feel like it",
+                "title": "Clean the garage"
+            }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[rocket::async_test]
+    pub async fn patch_due_text() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let task_id = create_task(
+            &client,
+            &session_response,
+            &super::utils::DEFAULT_TASKS_TEMPLATES[0],
+            list_ids[0],
+        )
+        .await;
+        client
+            .api()
+            .path("tasks")
+            .auth(&session_response)
+            .patch(task_id, json!({ "due_text": "in 3 days" }))
+            .await;
+
+        let task = get_item(&client, &session_response, task_id).await;
+        let expected = OffsetDateTime::now_utc() + Duration::days(3);
+        let expected = time::PrimitiveDateTime::new(expected.date(), expected.time());
+        assert!((task.due_at - expected).abs() < Duration::minutes(1));
+        assert_eq!(task.due_text, "in 3 days");
+    }
+
+    #[rocket::async_test]
+    pub async fn preview_due_text() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let due_date = utils::preview(
+            &client,
+            &session_response,
+            "every other week",
+            StatusCode::OK,
+        )
+        .await
+        .expect("Expected due date");
+        assert_eq!(
+            due_date.recurrence.as_deref(),
+            Some("FREQ=WEEKLY;INTERVAL=2")
+        );
+        assert_eq!(due_date.timezone, "UTC");
+
+        let due_date = utils::preview(&client, &session_response, "oct 19 2030", StatusCode::OK)
+            .await
+            .expect("Expected due date");
+        assert_eq!(due_date.due_at, time::macros::datetime!(2030-10-19 23:59));
+        assert_eq!(due_date.recurrence, None);
+
+        utils::preview(
+            &client,
+            &session_response,
+            "someday",
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+
+    #[rocket::async_test]
+    pub async fn set_invalid_timezone() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        utils::set_timezone(
+            &client,
+            &session_response,
+            "Middle/Earth",
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+
+    pub mod types {
+        use crate::commons::utils::serde::primitive_date_iso_deserialize;
+        use serde::Deserialize;
+        use time::PrimitiveDateTime;
+
+        #[derive(Debug, Deserialize)]
+        pub struct DueDateResponse {
+            #[serde(deserialize_with = "primitive_date_iso_deserialize")]
+            pub due_at: PrimitiveDateTime,
+            pub recurrence: Option<String>,
+            pub timezone: String,
+        }
+    }
+
+    pub mod utils {
+        use reqwest::StatusCode;
+        use serde_json::json;
+
+        use super::types::DueDateResponse;
+        use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+        pub async fn set_timezone(
+            client: &HttpClient,
+            session_response: &SessionResponse,
+            timezone: &str,
+            status: StatusCode,
+        ) {
+            let res = client
+                .patch("users/me")
+                .bearer_auth(session_response.session_token)
+                .json(&json!({ "timezone": timezone }))
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), status);
+        }
+
+        pub async fn preview(
+            client: &HttpClient,
+            session_response: &SessionResponse,
+            text: &str,
+            status: StatusCode,
+        ) -> Option<DueDateResponse> {
+            let res = client
+                .get("tasks/due_date")
+                .query(&[("text", text)])
+                .bearer_auth(session_response.session_token)
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), status);
+            if status != StatusCode::OK {
+                return None;
+            }
+            Some(res.json().await.expect("Expected json response"))
+        }
+    }
+}
+
+pub mod quick_add {
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use time::{Duration, OffsetDateTime};
+    use uuid::Uuid;
+
+    use super::tree::utils::get_item;
+    use crate::{
+        api::{
+            archive::utils::archive, auth::email::utils::email_register_and_login_user_default,
+            labels::utils::setup_labels_default, lists::utils::setup_lists_default,
+        },
+        commons,
+    };
+
+    #[rocket::async_test]
+    pub async fn quick_add_task() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let (label_ids, _) = setup_labels_default(&client, &session_response).await;
+        let res = utils::quick_add(
+            &client,
+            &session_response,
+            json!({ "text": "Buy milk tomorrow #grocery_list @important !p1" }),
+            StatusCode::CREATED,
+        )
+        .await
+        .expect("Expected response");
+
+        let task = get_item(&client, &session_response, res.id).await;
+        assert_eq!(task.title, "Buy milk");
+        assert_eq!(task.list_id, list_ids[0]);
+        assert_eq!(task.priority, 1);
+        assert_eq!(task.label_ids, vec![label_ids[0]]);
+        assert_eq!(task.due_text, "tomorrow");
+        assert_eq!(
+            task.due_at.date(),
+            OffsetDateTime::now_utc().date() + Duration::days(1)
+        );
+
+        let kinds: Vec<&str> = res.tokens.iter().map(|token| token.kind.as_str()).collect();
+        assert_eq!(kinds, ["list", "label", "priority", "due_date", "title"]);
+        assert_eq!(res.tokens[0].text, "#grocery_list");
+        assert_eq!(res.tokens[0].value, json!(list_ids[0]));
+    }
+
+    #[rocket::async_test]
+    pub async fn quick_add_defaults() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let res = utils::quick_add(
+            &client,
+            &session_response,
+            json!({ "text": "Call mom", "list_id": list_ids[1] }),
+            StatusCode::CREATED,
+        )
+        .await
+        .expect("Expected response");
+
+        let task = get_item(&client, &session_response, res.id).await;
+        assert_eq!(task.title, "Call mom");
+        assert_eq!(task.list_id, list_ids[1]);
+        assert_eq!(task.priority, 4);
+        assert_eq!(task.due_text, "today");
+        assert_eq!(task.due_at.date(), OffsetDateTime::now_utc().date());
+
+        // Archived lists take no new tasks, same as with a #list
+        archive(
+            &client,
+            &session_response,
+            list_ids[1],
+            "archive",
+            StatusCode::OK,
+        )
+        .await;
+        utils::quick_add(
+            &client,
+            &session_response,
+            json!({ "text": "Call mom", "list_id": list_ids[1] }),
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+
+    #[rocket::async_test]
+    pub async fn quick_add_creates_labels() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        utils::quick_add(
+            &client,
+            &session_response,
+            json!({ "text": "Water plants @garden", "list_id": list_ids[0] }),
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+
+        let res = utils::quick_add(
+            &client,
+            &session_response,
+            json!({
+                "text": "Water plants @garden",
+                "list_id": list_ids[0],
+                "create_labels": true
+            }),
+            StatusCode::CREATED,
+        )
+        .await
+        .expect("Expected response");
+        let token = res
+            .tokens
+            .iter()
+            .find(|token| token.kind == "label")
+            .expect("Expected label token");
+        assert!(token.created);
+
+        let task = get_item(&client, &session_response, res.id).await;
+        let label_id: Uuid = serde_json::from_value(token.value.clone()).unwrap();
+        assert_eq!(task.label_ids, vec![label_id]);
+    }
+
+    #[rocket::async_test]
+    pub async fn quick_add_invalid() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        setup_lists_default(&client, &session_response).await;
+        let long_text = format!("{} #grocery_list", "Buy milk tomorrow ".repeat(100));
+        for text in [
+            "Buy milk",
+            "Buy milk #nowhere",
+            "tomorrow #grocery_list",
+            &long_text,
+        ] {
+            utils::quick_add(
+                &client,
+                &session_response,
+                json!({ "text": text }),
+                StatusCode::BAD_REQUEST,
+            )
+            .await;
+        }
+    }
+
+    pub mod types {
+        use serde::Deserialize;
+        use serde_json::Value;
+        use uuid::Uuid;
+
+        #[derive(Debug, Deserialize)]
+        pub struct QuickAddResponse {
+            pub id: Uuid,
+            pub tokens: Vec<TokenResponse>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        pub struct TokenResponse {
+            pub text: String,
+            pub kind: String,
+            pub value: Value,
+            pub created: bool,
+        }
+    }
+
+    pub mod utils {
+        use reqwest::StatusCode;
+        use serde_json::Value;
+
+        use super::types::QuickAddResponse;
+        use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+        pub async fn quick_add(
+            client: &HttpClient,
+            session_response: &SessionResponse,
+            input: Value,
+            status: StatusCode,
+        ) -> Option<QuickAddResponse> {
+            let res = client
+                .post("tasks/quick")
+                .bearer_auth(session_response.session_token)
+                .json(&input)
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), status);
+            if status != StatusCode::CREATED {
+                return None;
+            }
+            Some(res.json().await.expect("Expected json response"))
+        }
+    }
+}
+
+pub mod filters {
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::{
+        labels::utils::{add_label, set_eq},
+        utils::setup_tasks_default,
+    };
+    use crate::{
+        api::{
+            auth::email::utils::email_register_and_login_user_default,
+            labels::utils::setup_labels_default,
+        },
+        commons::{
+            self,
+            http_client::{APIClient, APIRequestBuilder},
+        },
+    };
+
+    #[rocket::async_test]
+    pub async fn filter_tasks() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (task_ids, tasks) = setup_tasks_default(&client, &session_response).await;
+        let list_id = tasks[0]["list_id"].as_str().unwrap().to_owned();
+        let in_list = |task: &&Value| task["list_id"] == list_id;
+        let ids_where = |pred: &dyn Fn(&Value) -> bool| -> Vec<Uuid> {
+            task_ids
+                .iter()
+                .zip(&tasks)
+                .filter(|(_, task)| in_list(task) && pred(task))
+                .map(|(id, _)| *id)
+                .collect()
+        };
+
+        let items = utils::get_tasks(&client, &session_response, &[("list_id", &list_id)]).await;
+        assert!(set_eq(&utils::ids(&items), &ids_where(&|_| true)));
+
+        let items = utils::get_tasks(
+            &client,
+            &session_response,
+            &[("list_id", &list_id), ("completed", "true")],
+        )
+        .await;
+        assert!(set_eq(
+            &utils::ids(&items),
+            &ids_where(&|task| task["completed"] == true)
+        ));
+
+        let items = utils::get_tasks(
+            &client,
+            &session_response,
+            &[
+                ("list_id", &list_id),
+                ("due_after", "2023-10-19T10:00:00Z"),
+                ("due_before", "2023-10-29T13:10:00Z"),
+            ],
+        )
+        .await;
+        assert!(set_eq(&utils::ids(&items), &[task_ids[0], task_ids[2]]));
+
+        let (label_ids, _) = setup_labels_default(&client, &session_response).await;
+        add_label(&client, &session_response, task_ids[1], label_ids[0]).await;
+        add_label(&client, &session_response, task_ids[8], label_ids[0]).await;
+        let items = utils::get_tasks(
+            &client,
+            &session_response,
+            &[("label_id", &label_ids[0].to_string())],
+        )
+        .await;
+        assert!(set_eq(&utils::ids(&items), &[task_ids[1], task_ids[8]]));
+    }
+
+    #[rocket::async_test]
+    pub async fn filter_parents() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (task_ids, tasks) = setup_tasks_default(&client, &session_response).await;
+        let list_id = tasks[0]["list_id"].as_str().unwrap().to_owned();
+        client
+            .api()
+            .path("tasks")
+            .auth(&session_response)
+            .patch(task_ids[1], json!({ "parent_id": task_ids[0] }))
+            .await;
+
+        let items = utils::get_tasks(
+            &client,
+            &session_response,
+            &[("parent_id", &task_ids[0].to_string())],
+        )
+        .await;
+        assert_eq!(utils::ids(&items), vec![task_ids[1]]);
+
+        let items = utils::get_tasks(
+            &client,
+            &session_response,
+            &[("list_id", &list_id), ("top_level", "true")],
+        )
+        .await;
+        let ids = utils::ids(&items);
+        assert!(ids.contains(&task_ids[0]));
+        assert!(!ids.contains(&task_ids[1]));
+    }
+
+    #[rocket::async_test]
+    pub async fn sort_tasks() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (task_ids, tasks) = setup_tasks_default(&client, &session_response).await;
+        let list_id = tasks[0]["list_id"].as_str().unwrap().to_owned();
+        let field = |items: &[Value], key: &str| -> Vec<String> {
+            items
+                .iter()
+                .map(|item| item[key].as_str().unwrap().to_owned())
+                .collect()
+        };
+
+        // Manual order by default, which starts out as the order of creation
+        let items = utils::get_tasks(&client, &session_response, &[("list_id", &list_id)]).await;
+        let created: Vec<Uuid> = task_ids
+            .iter()
+            .zip(&tasks)
+            .filter(|(_, task)| task["list_id"] == list_id)
+            .map(|(id, _)| *id)
+            .collect();
+        assert_eq!(utils::ids(&items), created);
+
+        let items = utils::get_tasks(
+            &client,
+            &session_response,
+            &[("list_id", &list_id), ("sort", "due_at")],
+        )
+        .await;
+        let due_dates = field(&items, "due_at");
+        let mut sorted = due_dates.clone();
+        sorted.sort();
+        assert_eq!(due_dates, sorted);
+
+        let items = utils::get_tasks(
+            &client,
+            &session_response,
+            &[("list_id", &list_id), ("sort", "title")],
+        )
+        .await;
+        let titles = field(&items, "title");
+        let mut sorted = titles.clone();
+        sorted.sort();
+        assert_eq!(titles, sorted);
+
+        let items = utils::get_tasks(
+            &client,
+            &session_response,
+            &[("list_id", &list_id), ("sort", "title"), ("order", "desc")],
+        )
+        .await;
+        sorted.reverse();
+        assert_eq!(field(&items, "title"), sorted);
+    }
+
+    #[rocket::async_test]
+    pub async fn select_fields() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        setup_tasks_default(&client, &session_response).await;
+        let items =
+            utils::get_tasks(&client, &session_response, &[("fields", "title,due_at")]).await;
+        for item in items {
+            let mut keys: Vec<_> = item.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            assert_eq!(keys, ["due_at", "id", "title"]);
+        }
+    }
+
+    #[rocket::async_test]
+    pub async fn invalid_filters() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        for query in [("fields", "title,secret"), ("due_before", "next week")] {
+            let res = client
+                .get("tasks")
+                .query(&[query])
+                .bearer_auth(session_response.session_token)
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    pub mod utils {
+        use reqwest::StatusCode;
+        use serde_json::Value;
+        use uuid::Uuid;
+
+        use crate::{
+            api::auth::email::utils::SessionResponse,
+            commons::{http_client::HttpClient, utils::rest::GetAllResponse},
+        };
+
+        pub async fn get_tasks(
+            client: &HttpClient,
+            session_response: &SessionResponse,
+            query: &[(&str, &str)],
+        ) -> Vec<Value> {
+            let res = client
+                .get("tasks")
+                .query(query)
+                .bearer_auth(session_response.session_token)
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), StatusCode::OK);
+            res.json::<GetAllResponse<Value>>()
+                .await
+                .expect("Expected json response")
+                .items
+        }
+
+        pub fn ids(items: &[Value]) -> Vec<Uuid> {
+            items
+                .iter()
+                .map(|item| serde_json::from_value(item["id"].clone()).unwrap())
+                .collect()
+        }
+    }
+}
+
+pub mod ordering {
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::types::GetTaskResponse;
+    use crate::{
+        api::{
+            auth::email::utils::email_register_and_login_user_default,
+            lists::utils::setup_lists_default,
+        },
+        commons::{
+            self,
+            http_client::{APIClient, APIRequestBuilder},
+        },
+    };
+
+    #[rocket::async_test]
+    pub async fn move_tasks() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let mut ids = vec![];
+        for title in ["First", "Second", "Third"] {
+            ids.push(utils::create(&client, &session_response, title, list_ids[0], None).await);
+        }
+        let order = || utils::top_level(&client, &session_response, list_ids[0]);
+        assert_eq!(order().await, ids);
+
+        let api = client.api().path("tasks").auth(&session_response);
+        api.patch(ids[2], json!({ "move_before": ids[0] })).await;
+        assert_eq!(order().await, vec![ids[2], ids[0], ids[1]]);
+
+        api.patch(ids[0], json!({ "move_after": ids[1] })).await;
+        assert_eq!(order().await, vec![ids[2], ids[1], ids[0]]);
+
+        // Moving between two neighbours again and again keeps working
+        for _ in 0..20 {
+            api.patch(ids[0], json!({ "move_after": ids[2] })).await;
+            api.patch(ids[1], json!({ "move_after": ids[2] })).await;
+        }
+        assert_eq!(order().await, vec![ids[2], ids[1], ids[0]]);
+
+        // Another list puts the task last
+        let other_id = utils::create(&client, &session_response, "Other", list_ids[1], None).await;
+        api.patch(other_id, json!({ "list_id": list_ids[0] })).await;
+        assert_eq!(order().await, vec![ids[2], ids[1], ids[0], other_id]);
+    }
+
+    #[rocket::async_test]
+    pub async fn move_subtasks() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let parent_id =
+            utils::create(&client, &session_response, "Parent", list_ids[0], None).await;
+        let mut child_ids = vec![];
+        for title in ["First", "Second", "Third"] {
+            child_ids.push(
+                utils::create(
+                    &client,
+                    &session_response,
+                    title,
+                    list_ids[0],
+                    Some(parent_id),
+                )
+                .await,
+            );
+        }
+        let api = client.api().path("tasks").auth(&session_response);
+        let parent: GetTaskResponse = api.get(parent_id).await;
+        assert_eq!(parent.child_ids, child_ids);
+
+        api.patch(child_ids[0], json!({ "move_after": child_ids[2] }))
+            .await;
+        let parent: GetTaskResponse = api.get(parent_id).await;
+        assert_eq!(
+            parent.child_ids,
+            vec![child_ids[1], child_ids[2], child_ids[0]]
+        );
+    }
+
+    #[rocket::async_test]
+    pub async fn move_invalid() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let first_id = utils::create(&client, &session_response, "First", list_ids[0], None).await;
+        let second_id =
+            utils::create(&client, &session_response, "Second", list_ids[0], None).await;
+        let other_id = utils::create(&client, &session_response, "Other", list_ids[1], None).await;
+        let child_id = utils::create(
+            &client,
+            &session_response,
+            "Child",
+            list_ids[0],
+            Some(first_id),
+        )
+        .await;
+
+        for body in [
+            json!({ "move_before": other_id }),
+            json!({ "move_after": child_id }),
+            json!({ "move_before": Uuid::new_v4() }),
+            json!({ "move_before": first_id, "move_after": first_id }),
+        ] {
+            let res = client
+                .patch(&format!("tasks/{}", second_id))
+                .bearer_auth(session_response.session_token)
+                .json(&body)
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST, "Moving with {body}");
+        }
+    }
+
+    pub mod utils {
+        use serde_json::json;
+        use uuid::Uuid;
+
+        use super::super::{
+            filters::utils::{get_tasks, ids},
+            lists::utils::create_task,
+        };
+        use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+        /// The ids of the top level tasks of a list, in order.
+        pub async fn top_level(
+            client: &HttpClient,
+            session_response: &SessionResponse,
+            list_id: Uuid,
+        ) -> Vec<Uuid> {
+            let list_id = list_id.to_string();
+            let query = [("list_id", list_id.as_str()), ("top_level", "true")];
+            ids(&get_tasks(client, session_response, &query).await)
+        }
+
+        pub async fn create(
+            client: &HttpClient,
+            session_response: &SessionResponse,
+            title: &str,
+            list_id: Uuid,
+            parent_id: Option<Uuid>,
+        ) -> Uuid {
+            create_task(
+                client,
+                session_response,
+                &json!({
+                    "title": title,
+                    "parent_id": parent_id,
+                    "due_at": "2023-10-19T10:23:00.000000000Z",
+                    "due_text": "Next Monday"
+                }),
+                list_id,
+            )
+            .await
+        }
+    }
+}
+
+pub mod priorities {
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    use super::{
+        filters::utils::{get_tasks, ids},
+        lists::utils::create_task,
+        types::GetTaskResponse,
+    };
+    use crate::{
+        api::{
+            auth::email::utils::email_register_and_login_user_default,
+            lists::utils::setup_lists_default,
+        },
+        commons::{
+            self,
+            http_client::{APIClient, APIRequestBuilder},
+        },
+    };
+
+    fn task(title: &str, priority: Option<i16>) -> Value {
+        let mut task = json!({
+            "title": title,
+            "due_at": "2023-10-19T10:23:00.000000000Z",
+            "due_text": "Next Monday"
+        });
+        if let Some(priority) = priority {
+            task["priority"] = json!(priority);
+        }
+        task
+    }
+
+    #[rocket::async_test]
+    pub async fn set_priority() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let api = client.api().path("tasks").auth(&session_response);
+        let id = create_task(
+            &client,
+            &session_response,
+            &task("Default", None),
+            list_ids[0],
+        )
+        .await;
+        let item: GetTaskResponse = api.get(id).await;
+        assert_eq!(item.priority, 4);
+
+        api.patch(id, json!({ "priority": 1 })).await;
+        let item: GetTaskResponse = api.get(id).await;
+        assert_eq!(item.priority, 1);
+
+        let id = create_task(
+            &client,
+            &session_response,
+            &task("Urgent", Some(2)),
+            list_ids[0],
+        )
+        .await;
+        let item: GetTaskResponse = api.get(id).await;
+        assert_eq!(item.priority, 2);
+    }
+
+    #[rocket::async_test]
+    pub async fn sort_and_filter() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let mut task_ids = vec![];
+        for (title, priority) in [("Low", 4), ("High", 1), ("Medium", 2), ("Also high", 1)] {
+            task_ids.push(
+                create_task(
+                    &client,
+                    &session_response,
+                    &task(title, Some(priority)),
+                    list_ids[0],
+                )
+                .await,
+            );
+        }
+        let list_id = list_ids[0].to_string();
+
+        let items = get_tasks(
+            &client,
+            &session_response,
+            &[("list_id", &list_id), ("sort", "priority")],
+        )
+        .await;
+        assert_eq!(
+            ids(&items),
+            vec![task_ids[1], task_ids[3], task_ids[2], task_ids[0]]
+        );
+
+        let items = get_tasks(
+            &client,
+            &session_response,
+            &[
+                ("list_id", &list_id),
+                ("sort", "priority"),
+                ("order", "desc"),
+            ],
+        )
+        .await;
+        assert_eq!(ids(&items)[0], task_ids[0]);
+
+        let items = get_tasks(
+            &client,
+            &session_response,
+            &[("list_id", &list_id), ("priority", "1")],
+        )
+        .await;
+        assert_eq!(ids(&items), vec![task_ids[1], task_ids[3]]);
+    }
+
+    #[rocket::async_test]
+    pub async fn invalid_priority() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        for priority in [0, 5] {
+            let mut req = task("Invalid", Some(priority));
+            req["list_id"] = json!(list_ids[0]);
+            let res = client
+                .post("tasks")
+                .bearer_auth(session_response.session_token)
+                .json(&req)
+                .send()
+                .await
+                .expect("Expected response");
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let id = create_task(
+            &client,
+            &session_response,
+            &task("Valid", None),
+            list_ids[0],
+        )
+        .await;
+        let res = client
+            .patch(&format!("tasks/{}", id))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "priority": 9 }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = client
+            .get("tasks")
+            .query(&[("priority", "7")])
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+pub mod nested {
+    use reqwest::StatusCode;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use super::ordering::utils::create;
+    use crate::{
+        api::{
+            auth::email::utils::{
+                email_register_and_login_user, email_register_and_login_user_default,
+                SessionResponse,
+            },
+            lists::utils::setup_lists_default,
+        },
+        commons::{self, http_client::HttpClient},
+    };
+
+    #[rocket::async_test]
+    async fn subtree() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let root = create(&client, &session_response, "Move", list_ids[0], None).await;
+        let pack = create(&client, &session_response, "Pack", list_ids[0], Some(root)).await;
+        let clean = create(&client, &session_response, "Clean", list_ids[0], Some(root)).await;
+        let books = create(&client, &session_response, "Books", list_ids[0], Some(pack)).await;
+        create(&client, &session_response, "Other", list_ids[0], None).await;
+
+        let tree = get_tree(&client, &session_response, root, "").await;
+        assert_eq!(tree["id"], root.to_string());
+        assert_eq!(tree["title"], "Move");
+        let children = tree["children"].as_array().unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0]["id"], pack.to_string());
+        assert_eq!(children[1]["id"], clean.to_string());
+        assert_eq!(children[0]["children"][0]["id"], books.to_string());
+        assert_eq!(children[0]["children"][0]["children"], Value::Array(vec![]));
+
+        // Deeper subtasks are left out, but still referenced by id
+        let tree = get_tree(&client, &session_response, root, "?max_depth=1").await;
+        let children = tree["children"].as_array().unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0]["children"], Value::Array(vec![]));
+        assert_eq!(children[0]["child_ids"][0], books.to_string());
+        let tree = get_tree(&client, &session_response, root, "?max_depth=0").await;
+        assert_eq!(tree["children"], Value::Array(vec![]));
+
+        let (other_session, _) = email_register_and_login_user(&client, "other").await;
+        let res = client
+            .get(&format!("tasks/{root}/tree"))
+            .bearer_auth(other_session.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    async fn get_tree(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        query: &str,
+    ) -> Value {
+        let res = client
+            .get(&format!("tasks/{task_id}/tree{query}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+}
+
+pub mod hierarchy {
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::ordering::utils::create;
+    use crate::{
+        api::{
+            actions,
+            auth::email::utils::{email_register_and_login_user_default, SessionResponse},
+            dependencies::utils::get_task,
+            lists::utils::setup_lists_default,
+        },
+        commons::{self, http_client::HttpClient},
+    };
+
+    #[rocket::async_test]
+    async fn cycles() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let root = create(&client, &session_response, "Move", list_ids[0], None).await;
+        let child = create(&client, &session_response, "Pack", list_ids[0], Some(root)).await;
+        let grandchild = create(
+            &client,
+            &session_response,
+            "Books",
+            list_ids[0],
+            Some(child),
+        )
+        .await;
+
+        for parent_id in [root, child, grandchild] {
+            let status = patch(
+                &client,
+                &session_response,
+                root,
+                json!({ "parent_id": parent_id }),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "Moving under {parent_id}");
+        }
+        let status = patch(
+            &client,
+            &session_response,
+            grandchild,
+            json!({ "parent_id": root }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[rocket::async_test]
+    async fn cross_list_parents() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let root = create(&client, &session_response, "Move", list_ids[0], None).await;
+        let child = create(&client, &session_response, "Pack", list_ids[0], Some(root)).await;
+
+        let res = client
+            .post("tasks")
+            .bearer_auth(session_response.session_token)
+            .json(&json!({
+                "title": "Books",
+                "parent_id": root,
+                "list_id": list_ids[1],
+                "due_text": "Next Monday"
+            }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        // A subtask only changes lists along with its parent
+        let status = patch(
+            &client,
+            &session_response,
+            child,
+            json!({ "list_id": list_ids[1] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let status = patch(
+            &client,
+            &session_response,
+            child,
+            json!({ "list_id": list_ids[1], "parent_id": root }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let status = patch(
+            &client,
+            &session_response,
+            child,
+            json!({ "list_id": list_ids[1], "parent_id": null }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Moving under a parent moves into its list
+        let status = patch(
+            &client,
+            &session_response,
+            child,
+            json!({ "parent_id": root }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let task = get_task(&client, &session_response, child).await;
+        assert_eq!(task["list_id"], list_ids[0].to_string());
+    }
+
+    #[rocket::async_test]
+    async fn move_subtree() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let root = create(&client, &session_response, "Move", list_ids[0], None).await;
+        let child = create(&client, &session_response, "Pack", list_ids[0], Some(root)).await;
+        let grandchild = create(
+            &client,
+            &session_response,
+            "Books",
+            list_ids[0],
+            Some(child),
+        )
+        .await;
+
+        let status = patch(
+            &client,
+            &session_response,
+            root,
+            json!({ "list_id": list_ids[1] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        for id in [root, child, grandchild] {
+            let task = get_task(&client, &session_response, id).await;
+            assert_eq!(task["list_id"], list_ids[1].to_string());
+        }
+        let task = get_task(&client, &session_response, child).await;
+        assert_eq!(task["parent_id"], root.to_string());
+
+        actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+        for id in [root, child, grandchild] {
+            let task = get_task(&client, &session_response, id).await;
+            assert_eq!(task["list_id"], list_ids[0].to_string());
+        }
+    }
+
+    async fn patch(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        body: Value,
+    ) -> StatusCode {
+        client
+            .patch(&format!("tasks/{task_id}"))
+            .bearer_auth(session_response.session_token)
+            .json(&body)
+            .send()
+            .await
+            .expect("Expected response")
+            .status()
+    }
+}
+
+pub mod completion {
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::ordering::utils::create;
+    use crate::{
+        api::{
+            actions,
+            auth::email::utils::{
+                email_register_and_login_user, email_register_and_login_user_default,
+                SessionResponse,
+            },
+            dependencies::utils::{complete, get_task},
+            lists::utils::setup_lists_default,
+        },
+        commons::{self, http_client::HttpClient},
+    };
+
+    #[rocket::async_test]
+    async fn completed_at() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let task_id = create(&client, &session_response, "Ship", list_ids[0], None).await;
+        let task = get_task(&client, &session_response, task_id).await;
+        assert_eq!(task["completed_at"], Value::Null);
+
+        complete(&client, &session_response, task_id, false, StatusCode::OK).await;
+        let task = get_task(&client, &session_response, task_id).await;
+        let completed_at = task["completed_at"].clone();
+        assert!(completed_at.is_string());
+
+        // Other changes keep the completion time
+        let res = client
+            .patch(&format!("tasks/{task_id}"))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "title": "Shipped" }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        let task = get_task(&client, &session_response, task_id).await;
+        assert_eq!(task["completed_at"], completed_at);
+
+        let res = client
+            .patch(&format!("tasks/{task_id}"))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "completed": false }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        let task = get_task(&client, &session_response, task_id).await;
+        assert_eq!(task["completed_at"], Value::Null);
+
+        // Undoing brings back the original completion time
+        actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+        let task = get_task(&client, &session_response, task_id).await;
+        assert_eq!(task["completed"], true);
+        assert_eq!(task["completed_at"], completed_at);
+    }
+
+    #[rocket::async_test]
+    async fn recently_completed() {
+        let client = commons::setup().await;
+        let (session_response, _) = email_register_and_login_user_default(&client).await;
+        let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+        let mut ids = vec![];
+        for title in ["First", "Second", "Third"] {
+            let id = create(&client, &session_response, title, list_ids[0], None).await;
+            complete(&client, &session_response, id, false, StatusCode::OK).await;
+            ids.push(id);
+        }
+        create(&client, &session_response, "Open", list_ids[0], None).await;
+        let other_list = create(&client, &session_response, "Other", list_ids[1], None).await;
+        complete(
+            &client,
+            &session_response,
+            other_list,
+            false,
+            StatusCode::OK,
+        )
+        .await;
+
+        let page = get_completed(&client, &session_response, list_ids[0], "").await;
+        assert_eq!(page["limit"], 20);
+        assert_eq!(item_ids(&page), [ids[2], ids[1], ids[0]]);
+        let page = get_completed(&client, &session_response, list_ids[0], "&limit=2&page=1").await;
+        assert_eq!(item_ids(&page), [ids[0]]);
+
+        let (other_session, _) = email_register_and_login_user(&client, "other").await;
+        let res = client
+            .get(&format!("tasks/completed?list_id={}", list_ids[0]))
+            .bearer_auth(other_session.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    async fn get_completed(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        list_id: Uuid,
+        query: &str,
+    ) -> Value {
+        let res = client
+            .get(&format!("tasks/completed?list_id={list_id}{query}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+
+    fn item_ids(page: &Value) -> Vec<Uuid> {
+        page["items"]
+            .as_array()
+            .expect("Expected items")
+            .iter()
+            .map(|item| item["id"].as_str().unwrap().parse().unwrap())
+            .collect()
+    }
+}
@@ -0,0 +1,374 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::{
+    api::{
+        actions,
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        dependencies::utils::{complete, get_task},
+        lists::{
+            tree::utils::{get_item, reparent},
+            utils::setup_lists_default,
+        },
+        tasks::{lists::utils::create_task, ordering::utils::create},
+    },
+    commons::{
+        self,
+        http_client::{APIClient, APIRequestBuilder},
+    },
+};
+
+#[rocket::async_test]
+async fn task_template() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let root = create(&client, &session_response, "Release", list_ids[0], None).await;
+    let tag = create(&client, &session_response, "Tag", list_ids[0], Some(root)).await;
+    let announce = create_task(
+        &client,
+        &session_response,
+        &json!({
+            "title": "Announce",
+            "parent_id": root,
+            "due_at": "2023-10-21T08:00:00.000000000Z",
+            "due_text": "Saturday"
+        }),
+        list_ids[0],
+    )
+    .await;
+    complete(&client, &session_response, tag, false, StatusCode::OK).await;
+    let label_id = client
+        .api()
+        .path("labels")
+        .auth(&session_response)
+        .post(json!({ "title": "Work", "color": "#ffa783" }))
+        .await
+        .id;
+    let res = client
+        .post(&format!("tasks/{announce}/labels"))
+        .bearer_auth(session_response.session_token)
+        .json(&json!({ "id": label_id }))
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let template_id = utils::save(
+        &client,
+        &session_response,
+        json!({ "title": "Release checklist", "task_id": root }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let templates = utils::get_templates(&client, &session_response).await;
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0]["id"], json!(template_id));
+    assert_eq!(templates[0]["kind"], "task");
+    assert_eq!(
+        templates[0]["content"]["tasks"].as_array().unwrap().len(),
+        3
+    );
+
+    // The template spans three days, which don't fit after the last date
+    utils::instantiate(
+        &client,
+        &session_response,
+        template_id,
+        json!({ "anchor": "9999-12-31", "list_id": list_ids[1] }),
+        StatusCode::BAD_REQUEST,
+    )
+    .await;
+    let copy = utils::instantiate(
+        &client,
+        &session_response,
+        template_id,
+        json!({ "anchor": "2023-11-01", "list_id": list_ids[1] }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let task = get_task(&client, &session_response, copy).await;
+    assert_eq!(task["title"], "Release");
+    assert_eq!(task["list_id"], json!(list_ids[1]));
+    assert_eq!(task["parent_id"], Value::Null);
+    assert_eq!(task["due_at"], "2023-11-01T10:23:00.000000000Z");
+    let children = task["child_ids"].as_array().unwrap();
+    assert_eq!(children.len(), 2);
+
+    let tag_copy = get_task(&client, &session_response, utils::id(&children[0])).await;
+    assert_eq!(tag_copy["title"], "Tag");
+    assert_eq!(tag_copy["completed"], false);
+    assert_eq!(tag_copy["list_id"], json!(list_ids[1]));
+    let announce_copy = get_task(&client, &session_response, utils::id(&children[1])).await;
+    assert_eq!(announce_copy["title"], "Announce");
+    assert_eq!(announce_copy["due_at"], "2023-11-03T08:00:00.000000000Z");
+    assert_eq!(announce_copy["due_text"], "Saturday");
+    assert_eq!(announce_copy["label_ids"], json!([label_id]));
+
+    // Templates can go under an existing task too
+    let nested = utils::instantiate(
+        &client,
+        &session_response,
+        template_id,
+        json!({ "anchor": "2023-11-01", "parent_id": root }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let task = get_task(&client, &session_response, nested).await;
+    assert_eq!(task["parent_id"], json!(root));
+    assert_eq!(task["list_id"], json!(list_ids[0]));
+
+    // The whole copy is undone at once
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    let res = client
+        .get(&format!("tasks/{nested}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let task = get_task(&client, &session_response, root).await;
+    assert_eq!(task["child_ids"].as_array().unwrap().len(), 2);
+}
+
+#[rocket::async_test]
+async fn list_template() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    reparent(&client, &session_response, list_ids[1], list_ids[0]).await;
+    let clothes = create(&client, &session_response, "Clothes", list_ids[0], None).await;
+    create(
+        &client,
+        &session_response,
+        "Socks",
+        list_ids[0],
+        Some(clothes),
+    )
+    .await;
+    create(&client, &session_response, "Passport", list_ids[1], None).await;
+
+    let template_id = utils::save(
+        &client,
+        &session_response,
+        json!({ "title": "Travel packing", "list_id": list_ids[0] }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let copy = utils::instantiate(
+        &client,
+        &session_response,
+        template_id,
+        json!({ "anchor": "2023-12-01" }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+
+    let original = get_item(&client, &session_response, list_ids[0]).await;
+    let list = get_item(&client, &session_response, copy).await;
+    assert_eq!(list.title, original.title);
+    assert_eq!(list.color, original.color);
+    assert_eq!(list.parent_id, None);
+    assert_eq!(list.child_ids.len(), 1);
+    let sublist = list.child_ids[0];
+    assert_ne!(sublist, list_ids[1]);
+
+    let tasks = utils::get_tasks(&client, &session_response, copy).await;
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0]["title"], "Clothes");
+    assert_eq!(tasks[0]["due_at"], "2023-12-01T10:23:00.000000000Z");
+    assert_eq!(tasks[1]["title"], "Socks");
+    assert_eq!(tasks[1]["parent_id"], tasks[0]["id"]);
+    let tasks = utils::get_tasks(&client, &session_response, sublist).await;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["title"], "Passport");
+
+    let nested = utils::instantiate(
+        &client,
+        &session_response,
+        template_id,
+        json!({ "parent_id": list_ids[2] }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_item(&client, &session_response, nested).await.parent_id,
+        Some(list_ids[2])
+    );
+}
+
+#[rocket::async_test]
+async fn invalid() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "Task", list_ids[0], None).await;
+
+    for input in [
+        json!({ "title": "Both", "task_id": task_id, "list_id": list_ids[0] }),
+        json!({ "title": "Neither" }),
+    ] {
+        utils::save(&client, &session_response, input, StatusCode::BAD_REQUEST).await;
+    }
+    for input in [
+        json!({ "title": "Theirs", "task_id": task_id }),
+        json!({ "title": "Theirs", "list_id": list_ids[0] }),
+    ] {
+        utils::save(&client, &other_session, input, StatusCode::BAD_REQUEST).await;
+    }
+
+    let template_id = utils::save(
+        &client,
+        &session_response,
+        json!({ "title": "Mine", "task_id": task_id }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    for input in [
+        json!({}),
+        json!({ "list_id": list_ids[0], "anchor": "next week" }),
+        json!({ "list_id": list_ids[1], "parent_id": task_id }),
+    ] {
+        utils::instantiate(
+            &client,
+            &session_response,
+            template_id,
+            input,
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+    utils::instantiate(
+        &client,
+        &other_session,
+        template_id,
+        json!({ "list_id": list_ids[0] }),
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+    assert!(utils::get_templates(&client, &other_session)
+        .await
+        .is_empty());
+
+    let res = client
+        .delete(&format!("templates/{template_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    utils::instantiate(
+        &client,
+        &session_response,
+        template_id,
+        json!({ "list_id": list_ids[0] }),
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    #[derive(Deserialize)]
+    struct PostResponse {
+        id: Uuid,
+    }
+
+    pub fn id(value: &Value) -> Uuid {
+        value.as_str().unwrap().parse().unwrap()
+    }
+
+    async fn created(res: reqwest::Response, expected_status: StatusCode) -> Option<Uuid> {
+        assert_eq!(res.status(), expected_status);
+        if expected_status != StatusCode::CREATED {
+            return None;
+        }
+        let response = res
+            .json::<PostResponse>()
+            .await
+            .expect("Expected correct json response");
+        Some(response.id)
+    }
+
+    pub async fn save(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        input: Value,
+        expected_status: StatusCode,
+    ) -> Option<Uuid> {
+        let res = client
+            .post("templates")
+            .bearer_auth(session_response.session_token)
+            .json(&input)
+            .send()
+            .await
+            .expect("Expected response");
+        created(res, expected_status).await
+    }
+
+    pub async fn instantiate(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        template_id: Uuid,
+        input: Value,
+        expected_status: StatusCode,
+    ) -> Option<Uuid> {
+        let res = client
+            .post(&format!("templates/{template_id}/instantiate"))
+            .bearer_auth(session_response.session_token)
+            .json(&input)
+            .send()
+            .await
+            .expect("Expected response");
+        created(res, expected_status).await
+    }
+
+    pub async fn get_templates(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+    ) -> Vec<Value> {
+        let res = client
+            .get("templates")
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: Value = res.json().await.expect("Expected correct json response");
+        body["items"].as_array().unwrap().clone()
+    }
+
+    pub async fn get_tasks(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        list_id: Uuid,
+    ) -> Vec<Value> {
+        let res = client
+            .get(&format!("tasks?list_id={list_id}&sort=title"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: Value = res.json().await.expect("Expected correct json response");
+        body["items"].as_array().unwrap().clone()
+    }
+}
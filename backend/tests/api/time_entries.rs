@@ -0,0 +1,388 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::{
+    api::{
+        actions,
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        lists::utils::setup_lists_default,
+        tasks::ordering::utils::create,
+    },
+    commons::{
+        self,
+        http_client::{APIClient, APIRequestBuilder},
+    },
+};
+
+#[rocket::async_test]
+async fn timer() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let first = create(&client, &session_response, "First", list_ids[0], None).await;
+    let second = create(&client, &session_response, "Second", list_ids[0], None).await;
+
+    utils::running(&client, &session_response, StatusCode::NOT_FOUND).await;
+    let entry_id = utils::start(&client, &session_response, first, StatusCode::CREATED)
+        .await
+        .unwrap();
+    let running = utils::running(&client, &session_response, StatusCode::OK)
+        .await
+        .unwrap();
+    assert_eq!(running["id"], json!(entry_id));
+    assert_eq!(running["task_id"], json!(first));
+    assert_eq!(running["ended_at"], Value::Null);
+
+    // Only one timer runs at a time, whatever the task
+    utils::start(&client, &session_response, first, StatusCode::CONFLICT).await;
+    utils::start(&client, &session_response, second, StatusCode::CONFLICT).await;
+
+    utils::stop(&client, &session_response, StatusCode::OK).await;
+    utils::stop(&client, &session_response, StatusCode::NOT_FOUND).await;
+    utils::running(&client, &session_response, StatusCode::NOT_FOUND).await;
+    let entries = utils::get_entries(&client, &session_response, first).await;
+    assert_eq!(entries.len(), 1);
+    assert_ne!(entries[0]["ended_at"], Value::Null);
+    assert!(entries[0]["ended_at"].as_str() >= entries[0]["started_at"].as_str());
+
+    // Undoing the stop resumes the timer
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    let running = utils::running(&client, &session_response, StatusCode::OK)
+        .await
+        .unwrap();
+    assert_eq!(running["id"], json!(entry_id));
+    utils::stop(&client, &session_response, StatusCode::OK).await;
+
+    utils::start(&client, &session_response, second, StatusCode::CREATED).await;
+    assert_eq!(
+        utils::get_entries(&client, &session_response, second)
+            .await
+            .len(),
+        1
+    );
+}
+
+#[rocket::async_test]
+async fn manual_entries() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "Task", list_ids[0], None).await;
+
+    let entry_id = utils::create_entry(
+        &client,
+        &session_response,
+        task_id,
+        json!({
+            "started_at": "2023-10-02T09:00:00.000000000+02:00",
+            "ended_at": "2023-10-02T10:30:00.000000000+02:00",
+            "note": "Planning"
+        }),
+        StatusCode::CREATED,
+    )
+    .await
+    .unwrap();
+    let entries = utils::get_entries(&client, &session_response, task_id).await;
+    assert_eq!(entries[0]["id"], json!(entry_id));
+    assert_eq!(entries[0]["started_at"], "2023-10-02T07:00:00.000000000Z");
+    assert_eq!(entries[0]["ended_at"], "2023-10-02T08:30:00.000000000Z");
+    assert_eq!(entries[0]["note"], "Planning");
+
+    for input in [
+        json!({
+            "started_at": "2023-10-02T10:00:00.000000000Z",
+            "ended_at": "2023-10-02T09:00:00.000000000Z"
+        }),
+        json!({ "started_at": "yesterday", "ended_at": "2023-10-02T09:00:00.000000000Z" }),
+    ] {
+        utils::create_entry(
+            &client,
+            &session_response,
+            task_id,
+            input,
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+
+    let res = client
+        .delete(&format!("time_entries/{entry_id}"))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(utils::get_entries(&client, &session_response, task_id)
+        .await
+        .is_empty());
+    actions::utils::undo(&client, &session_response, StatusCode::OK).await;
+    assert_eq!(
+        utils::get_entries(&client, &session_response, task_id)
+            .await
+            .len(),
+        1
+    );
+}
+
+#[rocket::async_test]
+async fn totals() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let first = create(&client, &session_response, "First", list_ids[0], None).await;
+    let second = create(&client, &session_response, "Second", list_ids[0], None).await;
+    let third = create(&client, &session_response, "Third", list_ids[1], None).await;
+    let label_id = client
+        .api()
+        .path("labels")
+        .auth(&session_response)
+        .post(json!({ "title": "Work", "color": "#ffa783" }))
+        .await
+        .id;
+    for task_id in [first, third] {
+        let res = client
+            .post(&format!("tasks/{task_id}/labels"))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({ "id": label_id }))
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    for (task_id, started_at, ended_at) in [
+        (first, "2023-10-02T09:00:00Z", "2023-10-02T10:30:00Z"),
+        // Only the half hour before the end of the range counts
+        (first, "2023-10-04T23:30:00Z", "2023-10-05T00:30:00Z"),
+        (second, "2023-10-03T12:00:00Z", "2023-10-03T13:00:00Z"),
+        (third, "2023-10-01T08:00:00Z", "2023-10-01T08:10:00Z"),
+        // Out of range
+        (third, "2023-09-30T08:00:00Z", "2023-09-30T09:00:00Z"),
+    ] {
+        utils::create_entry(
+            &client,
+            &session_response,
+            task_id,
+            json!({ "started_at": started_at, "ended_at": ended_at }),
+            StatusCode::CREATED,
+        )
+        .await;
+    }
+
+    let range = "from=2023-10-01&to=2023-10-04";
+    let totals = utils::totals(&client, &session_response, range).await;
+    assert_eq!(totals["from"], "2023-10-01");
+    assert_eq!(totals["to"], "2023-10-04");
+    assert_eq!(
+        totals["items"],
+        json!([
+            { "id": first, "title": "First", "seconds": 7200 },
+            { "id": second, "title": "Second", "seconds": 3600 },
+            { "id": third, "title": "Third", "seconds": 600 },
+        ])
+    );
+
+    let totals = utils::totals(
+        &client,
+        &session_response,
+        &format!("{range}&group_by=list"),
+    )
+    .await;
+    let items = totals["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["id"], json!(list_ids[0]));
+    assert_eq!(items[0]["seconds"], 10800);
+    assert_eq!(items[1]["id"], json!(list_ids[1]));
+    assert_eq!(items[1]["seconds"], 600);
+
+    let totals = utils::totals(
+        &client,
+        &session_response,
+        &format!("{range}&group_by=label"),
+    )
+    .await;
+    assert_eq!(
+        totals["items"],
+        json!([{ "id": label_id, "title": "Work", "seconds": 7800 }])
+    );
+
+    for query in ["from=2023-10-04&to=2023-10-01", "to=9999-12-31"] {
+        let res = client
+            .get(&format!("time_entries/totals?{query}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[rocket::async_test]
+async fn ownership() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    let (other_session, _) = email_register_and_login_user(&client, "other").await;
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create(&client, &session_response, "Task", list_ids[0], None).await;
+    let entry_id = utils::start(&client, &session_response, task_id, StatusCode::CREATED)
+        .await
+        .unwrap();
+
+    utils::start(&client, &other_session, task_id, StatusCode::NOT_FOUND).await;
+    utils::create_entry(
+        &client,
+        &other_session,
+        task_id,
+        json!({
+            "started_at": "2023-10-02T09:00:00Z",
+            "ended_at": "2023-10-02T10:00:00Z"
+        }),
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+    let res = client
+        .get(&format!("tasks/{task_id}/time_entries"))
+        .bearer_auth(other_session.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let res = client
+        .delete(&format!("time_entries/{entry_id}"))
+        .bearer_auth(other_session.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Timers are per user
+    utils::running(&client, &other_session, StatusCode::NOT_FOUND).await;
+    utils::stop(&client, &other_session, StatusCode::NOT_FOUND).await;
+    utils::running(&client, &session_response, StatusCode::OK).await;
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use crate::{api::auth::email::utils::SessionResponse, commons::http_client::HttpClient};
+
+    #[derive(Deserialize)]
+    struct PostResponse {
+        id: Uuid,
+    }
+
+    async fn created(res: reqwest::Response, expected_status: StatusCode) -> Option<Uuid> {
+        assert_eq!(res.status(), expected_status);
+        if expected_status != StatusCode::CREATED {
+            return None;
+        }
+        let response = res
+            .json::<PostResponse>()
+            .await
+            .expect("Expected correct json response");
+        Some(response.id)
+    }
+
+    pub async fn start(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        expected_status: StatusCode,
+    ) -> Option<Uuid> {
+        let res = client
+            .post(&format!("tasks/{task_id}/timer"))
+            .bearer_auth(session_response.session_token)
+            .json(&json!({}))
+            .send()
+            .await
+            .expect("Expected response");
+        created(res, expected_status).await
+    }
+
+    pub async fn stop(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        expected_status: StatusCode,
+    ) {
+        let res = client
+            .post("time_entries/stop")
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+    }
+
+    pub async fn running(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        expected_status: StatusCode,
+    ) -> Option<Value> {
+        let res = client
+            .get("time_entries/running")
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), expected_status);
+        if expected_status != StatusCode::OK {
+            return None;
+        }
+        Some(res.json().await.expect("Expected correct json response"))
+    }
+
+    pub async fn create_entry(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+        input: Value,
+        expected_status: StatusCode,
+    ) -> Option<Uuid> {
+        let res = client
+            .post(&format!("tasks/{task_id}/time_entries"))
+            .bearer_auth(session_response.session_token)
+            .json(&input)
+            .send()
+            .await
+            .expect("Expected response");
+        created(res, expected_status).await
+    }
+
+    pub async fn get_entries(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        task_id: Uuid,
+    ) -> Vec<Value> {
+        let res = client
+            .get(&format!("tasks/{task_id}/time_entries"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: Value = res.json().await.expect("Expected correct json response");
+        body["items"].as_array().unwrap().clone()
+    }
+
+    pub async fn totals(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        query: &str,
+    ) -> Value {
+        let res = client
+            .get(&format!("time_entries/totals?{query}"))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json().await.expect("Expected correct json response")
+    }
+}
@@ -0,0 +1,327 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use time::Duration;
+
+use crate::{
+    api::{
+        actions,
+        auth::email::utils::{
+            email_register_and_login_user, email_register_and_login_user_default,
+        },
+        lists::utils::setup_lists_default,
+        tasks::{lists::utils::create_task, tree::utils::reparent, utils::DEFAULT_TASKS_TEMPLATES},
+    },
+    commons::{
+        self,
+        http_client::{APIClient, APIRequestBuilder},
+    },
+};
+
+#[rocket::async_test]
+async fn delete_and_restore_task_subtree() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let parent_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[0],
+        list_ids[0],
+    )
+    .await;
+    let child_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[1],
+        list_ids[0],
+    )
+    .await;
+    reparent(&client, &session_response, child_id, parent_id).await;
+
+    client
+        .api()
+        .path("tasks")
+        .auth(&session_response)
+        .delete(parent_id)
+        .await;
+    utils::assert_missing(&client, &session_response, "tasks", parent_id).await;
+    utils::assert_missing(&client, &session_response, "tasks", child_id).await;
+
+    // Only the root of the deleted subtree shows up in the trash
+    let trash = utils::get_trash(&client, &session_response).await;
+    assert_eq!(trash.items.len(), 1);
+    assert_eq!(trash.items[0].id, parent_id);
+    assert_eq!(trash.items[0].item_type, "task");
+
+    utils::restore(&client, &session_response, parent_id, StatusCode::OK).await;
+
+    super::tasks::tree::utils::assert_has_parent(
+        &client,
+        &session_response,
+        child_id,
+        Some(parent_id),
+    )
+    .await;
+    let trash = utils::get_trash(&client, &session_response).await;
+    assert!(trash.items.is_empty(), "Expected trash to be empty");
+}
+
+#[rocket::async_test]
+async fn purged_by_worker() {
+    let client = commons::setup_with_config(|config| {
+        config.trash_retention = Duration::ZERO;
+        config.trash_purge_interval = Duration::milliseconds(200);
+    })
+    .await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[0],
+        list_ids[0],
+    )
+    .await;
+    client
+        .api()
+        .path("tasks")
+        .auth(&session_response)
+        .delete(task_id)
+        .await;
+
+    // Nothing touches the trash, so only the worker can have purged the task
+    rocket::tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    actions::utils::undo(&client, &session_response, StatusCode::CONFLICT).await;
+}
+
+#[rocket::async_test]
+async fn delete_and_restore_list() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let task_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[0],
+        list_ids[0],
+    )
+    .await;
+    super::lists::tree::utils::reparent(&client, &session_response, list_ids[1], list_ids[0]).await;
+
+    client
+        .api()
+        .path("lists")
+        .auth(&session_response)
+        .delete(list_ids[0])
+        .await;
+    utils::assert_missing(&client, &session_response, "lists", list_ids[0]).await;
+    utils::assert_missing(&client, &session_response, "lists", list_ids[1]).await;
+    utils::assert_missing(&client, &session_response, "tasks", task_id).await;
+
+    let trash = utils::get_trash(&client, &session_response).await;
+    assert_eq!(trash.items.len(), 1);
+    assert_eq!(trash.items[0].id, list_ids[0]);
+    assert_eq!(trash.items[0].item_type, "list");
+
+    utils::restore(&client, &session_response, list_ids[0], StatusCode::OK).await;
+
+    super::lists::tree::utils::assert_has_parent(
+        &client,
+        &session_response,
+        list_ids[1],
+        Some(list_ids[0]),
+    )
+    .await;
+    super::tasks::lists::utils::assert_has_list(&client, &session_response, task_id, list_ids[0])
+        .await;
+}
+
+#[rocket::async_test]
+async fn restore_keeps_earlier_deletes() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let parent_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[0],
+        list_ids[0],
+    )
+    .await;
+    let child_id = create_task(
+        &client,
+        &session_response,
+        &DEFAULT_TASKS_TEMPLATES[1],
+        list_ids[0],
+    )
+    .await;
+    reparent(&client, &session_response, child_id, parent_id).await;
+
+    let api = client.api().path("tasks").auth(&session_response);
+    api.delete(child_id).await;
+    api.delete(parent_id).await;
+
+    // The child can't come back before its parent does
+    utils::restore(
+        &client,
+        &session_response,
+        child_id,
+        StatusCode::BAD_REQUEST,
+    )
+    .await;
+
+    // The child was trashed on its own, so it stays in the trash
+    utils::restore(&client, &session_response, parent_id, StatusCode::OK).await;
+    utils::assert_missing(&client, &session_response, "tasks", child_id).await;
+    super::tasks::tree::utils::assert_no_children(&client, &session_response, parent_id).await;
+
+    utils::restore(&client, &session_response, child_id, StatusCode::OK).await;
+    super::tasks::tree::utils::assert_has_parent(
+        &client,
+        &session_response,
+        child_id,
+        Some(parent_id),
+    )
+    .await;
+}
+
+#[rocket::async_test]
+async fn delete_and_restore_label() {
+    use crate::api::labels::utils::setup_labels_default;
+
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (label_ids, _) = setup_labels_default(&client, &session_response).await;
+    client
+        .api()
+        .path("labels")
+        .auth(&session_response)
+        .delete(label_ids[0])
+        .await;
+    utils::assert_missing(&client, &session_response, "labels", label_ids[0]).await;
+
+    let trash = utils::get_trash(&client, &session_response).await;
+    assert_eq!(trash.items.len(), 1);
+    assert_eq!(trash.items[0].item_type, "label");
+
+    utils::restore(&client, &session_response, label_ids[0], StatusCode::OK).await;
+    let res = client
+        .get(&format!("labels/{}", label_ids[0]))
+        .bearer_auth(session_response.session_token)
+        .send()
+        .await
+        .expect("Expected response");
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[rocket::async_test]
+async fn restore_other_unauth() {
+    let client = commons::setup().await;
+    let (alice_session_response, _) = email_register_and_login_user(&client, "alice").await;
+    let (bob_session_response, _) = email_register_and_login_user(&client, "bob").await;
+
+    let (list_ids, _) = setup_lists_default(&client, &bob_session_response).await;
+    client
+        .api()
+        .path("lists")
+        .auth(&bob_session_response)
+        .delete(list_ids[0])
+        .await;
+
+    let trash = utils::get_trash(&client, &alice_session_response).await;
+    assert!(trash.items.is_empty(), "Expected trash to be empty");
+    utils::restore(
+        &client,
+        &alice_session_response,
+        list_ids[0],
+        StatusCode::NOT_FOUND,
+    )
+    .await;
+}
+
+#[rocket::async_test]
+async fn get_trash_unauth() {
+    let client = commons::setup().await;
+    let res = client.get("trash").send().await.expect("Expected response");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+pub mod types {
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct GetTrashResponse {
+        pub id: Uuid,
+        pub item_type: String,
+        pub title: String,
+    }
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use uuid::Uuid;
+
+    use super::types::GetTrashResponse;
+    use crate::{
+        api::auth::email::utils::SessionResponse,
+        commons::{http_client::HttpClient, utils::rest::GetAllResponse},
+    };
+
+    pub async fn get_trash(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+    ) -> GetAllResponse<GetTrashResponse> {
+        let res = client
+            .get("trash")
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), StatusCode::OK);
+        res.json::<GetAllResponse<GetTrashResponse>>()
+            .await
+            .expect("Expected json response")
+    }
+
+    pub async fn restore(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        item_id: Uuid,
+        status: StatusCode,
+    ) {
+        let res = client
+            .post(&format!("trash/{}/restore", item_id))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), status);
+    }
+
+    pub async fn assert_missing(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        model_path: &str,
+        item_id: Uuid,
+    ) {
+        let res = client
+            .get(&format!("{}/{}", model_path, item_id))
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(
+            res.status(),
+            StatusCode::NOT_FOUND,
+            "Expected {} to be missing",
+            item_id
+        );
+    }
+}
@@ -0,0 +1,359 @@
+#![cfg(test)]
+
+use reqwest::StatusCode;
+use serde_json::json;
+use time::{Date, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        auth::email::utils::{email_register_and_login_user_default, SessionResponse},
+        lists::utils::setup_lists_default,
+        tasks::{due_dates::utils::set_timezone, lists::utils::create_task},
+    },
+    commons::{self, http_client::HttpClient},
+};
+
+/// Creates a task due on the given UTC date and time.
+async fn create_due_task(
+    client: &HttpClient,
+    session_response: &SessionResponse,
+    list_id: Uuid,
+    date: Date,
+    hour: u8,
+    completed: bool,
+) -> Uuid {
+    create_task(
+        client,
+        session_response,
+        &json!({
+            "title": format!("Task due {date} {hour}:00"),
+            "due_at": format!("{date}T{hour:02}:00:00.000000000Z"),
+            "due_text": "some day",
+            "completed": completed
+        }),
+        list_id,
+    )
+    .await
+}
+
+#[rocket::async_test]
+async fn today() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let today = OffsetDateTime::now_utc().date();
+    let overdue_id = create_due_task(
+        &client,
+        &session_response,
+        list_ids[0],
+        today - Duration::days(2),
+        12,
+        false,
+    )
+    .await;
+    let today_id = create_due_task(&client, &session_response, list_ids[1], today, 0, false).await;
+    create_due_task(&client, &session_response, list_ids[0], today, 1, true).await;
+    create_due_task(
+        &client,
+        &session_response,
+        list_ids[0],
+        today + Duration::days(1),
+        0,
+        false,
+    )
+    .await;
+
+    let view = utils::get_view(
+        &client,
+        &session_response,
+        "views/today",
+        &[],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected view");
+    assert_eq!(view.timezone, "UTC");
+    assert_eq!(utils::ids(&view.overdue), vec![overdue_id]);
+    assert_eq!(view.days.len(), 1);
+    assert_eq!(view.days[0].date, today.to_string());
+    assert_eq!(utils::ids(&view.days[0].items), vec![today_id]);
+}
+
+#[rocket::async_test]
+async fn upcoming() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let today = OffsetDateTime::now_utc().date();
+    let tomorrow = today + Duration::days(1);
+    let overdue_id = create_due_task(
+        &client,
+        &session_response,
+        list_ids[0],
+        today - Duration::days(1),
+        0,
+        false,
+    )
+    .await;
+    let later_id =
+        create_due_task(&client, &session_response, list_ids[0], tomorrow, 18, false).await;
+    let earlier_id =
+        create_due_task(&client, &session_response, list_ids[1], tomorrow, 9, false).await;
+    let far_id = create_due_task(
+        &client,
+        &session_response,
+        list_ids[0],
+        today + Duration::days(200),
+        9,
+        false,
+    )
+    .await;
+    create_due_task(
+        &client,
+        &session_response,
+        list_ids[0],
+        today + Duration::days(400),
+        9,
+        false,
+    )
+    .await;
+
+    let view = utils::get_view(
+        &client,
+        &session_response,
+        "views/upcoming",
+        &[],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected view");
+    assert_eq!(utils::ids(&view.overdue), vec![overdue_id]);
+    let days: Vec<_> = view.days.iter().map(|day| day.date.clone()).collect();
+    assert_eq!(
+        days,
+        vec![
+            tomorrow.to_string(),
+            (today + Duration::days(200)).to_string()
+        ]
+    );
+    assert_eq!(utils::ids(&view.days[0].items), vec![earlier_id, later_id]);
+    assert_eq!(utils::ids(&view.days[1].items), vec![far_id]);
+
+    // Later ranges don't repeat the overdue tasks
+    let view = utils::get_view(
+        &client,
+        &session_response,
+        "views/upcoming",
+        &[
+            ("from", tomorrow.to_string()),
+            ("to", (tomorrow + Duration::days(7)).to_string()),
+        ],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected view");
+    assert!(view.overdue.is_empty());
+    assert_eq!(view.days.len(), 1);
+    assert_eq!(utils::ids(&view.days[0].items), vec![earlier_id, later_id]);
+}
+
+#[rocket::async_test]
+async fn upcoming_in_timezone() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+    set_timezone(&client, &session_response, "Asia/Kolkata", StatusCode::OK).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    // 20:00 UTC is 01:30 the next day in Kolkata
+    let local_today = (OffsetDateTime::now_utc() + Duration::minutes(330)).date();
+    let task_id = create_due_task(
+        &client,
+        &session_response,
+        list_ids[0],
+        local_today + Duration::days(1),
+        20,
+        false,
+    )
+    .await;
+
+    let view = utils::get_view(
+        &client,
+        &session_response,
+        "views/upcoming",
+        &[],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected view");
+    assert_eq!(view.timezone, "Asia/Kolkata");
+    assert_eq!(view.from, local_today.to_string());
+    assert_eq!(view.days.len(), 1);
+    assert_eq!(
+        view.days[0].date,
+        (local_today + Duration::days(2)).to_string()
+    );
+    assert_eq!(utils::ids(&view.days[0].items), vec![task_id]);
+}
+
+#[rocket::async_test]
+async fn ordered_by_priority() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let (list_ids, _) = setup_lists_default(&client, &session_response).await;
+    let tomorrow = OffsetDateTime::now_utc().date() + Duration::days(1);
+    let early_id =
+        create_due_task(&client, &session_response, list_ids[0], tomorrow, 8, false).await;
+    let mut ids = vec![];
+    for (hour, priority) in [(10, 1), (9, 2), (11, 1)] {
+        let id = create_task(
+            &client,
+            &session_response,
+            &json!({
+                "title": format!("Task due {hour}:00"),
+                "due_at": format!("{tomorrow}T{hour:02}:00:00.000000000Z"),
+                "due_text": "some day",
+                "priority": priority
+            }),
+            list_ids[0],
+        )
+        .await;
+        ids.push(id);
+    }
+
+    // Priority comes first, the time of day only orders the same priority
+    let view = utils::get_view(
+        &client,
+        &session_response,
+        "views/upcoming",
+        &[("to", tomorrow.to_string())],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected view");
+    let day = view
+        .days
+        .iter()
+        .find(|day| day.date == tomorrow.to_string())
+        .expect("Expected tomorrow");
+    assert_eq!(
+        utils::ids(&day.items),
+        vec![ids[0], ids[2], ids[1], early_id]
+    );
+}
+
+#[rocket::async_test]
+async fn upcoming_invalid_range() {
+    let client = commons::setup().await;
+    let (session_response, _) = email_register_and_login_user_default(&client).await;
+
+    let today = OffsetDateTime::now_utc().date();
+    for (from, to) in [
+        (today + Duration::days(5), today + Duration::days(2)),
+        (today, today + Duration::days(400)),
+    ] {
+        utils::get_view(
+            &client,
+            &session_response,
+            "views/upcoming",
+            &[("from", from.to_string()), ("to", to.to_string())],
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+
+    // Without `to` the range runs for the longest allowed span from `from`
+    let from = today + Duration::days(300);
+    let view = utils::get_view(
+        &client,
+        &session_response,
+        "views/upcoming",
+        &[("from", from.to_string())],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected view");
+    assert_eq!(view.from, from.to_string());
+    assert_eq!(view.to, (from + Duration::days(365)).to_string());
+
+    // Near the last representable day the range stops there
+    let view = utils::get_view(
+        &client,
+        &session_response,
+        "views/upcoming",
+        &[("from", String::from("9999-06-01"))],
+        StatusCode::OK,
+    )
+    .await
+    .expect("Expected view");
+    assert_eq!(view.to, "9999-12-31");
+}
+
+#[rocket::async_test]
+async fn views_unauth() {
+    let client = commons::setup().await;
+    for path in ["views/today", "views/upcoming"] {
+        let res = client.get(path).send().await.expect("Expected response");
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+pub mod types {
+    use serde::Deserialize;
+
+    use crate::api::tasks::types::GetTaskResponse;
+
+    #[derive(Debug, Deserialize)]
+    pub struct ViewResponse {
+        pub timezone: String,
+        pub from: String,
+        pub to: String,
+        pub overdue: Vec<GetTaskResponse>,
+        pub days: Vec<DayResponse>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct DayResponse {
+        pub date: String,
+        pub items: Vec<GetTaskResponse>,
+    }
+}
+
+pub mod utils {
+    use reqwest::StatusCode;
+    use uuid::Uuid;
+
+    use super::types::ViewResponse;
+    use crate::{
+        api::{auth::email::utils::SessionResponse, tasks::types::GetTaskResponse},
+        commons::http_client::HttpClient,
+    };
+
+    pub async fn get_view(
+        client: &HttpClient,
+        session_response: &SessionResponse,
+        path: &str,
+        query: &[(&str, String)],
+        status: StatusCode,
+    ) -> Option<ViewResponse> {
+        let res = client
+            .get(path)
+            .query(query)
+            .bearer_auth(session_response.session_token)
+            .send()
+            .await
+            .expect("Expected response");
+        assert_eq!(res.status(), status);
+        if status != StatusCode::OK {
+            return None;
+        }
+        Some(res.json().await.expect("Expected json response"))
+    }
+
+    pub fn ids(items: &[GetTaskResponse]) -> Vec<Uuid> {
+        items.iter().map(|item| item.id).collect()
+    }
+}
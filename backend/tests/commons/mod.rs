@@ -7,7 +7,10 @@ use sqlx::{
     postgres::{PgConnectOptions, PgPoolOptions},
     ConnectOptions,
 };
-use toast_task::{config::get_config, create_rocket};
+use toast_task::{
+    config::{get_config, AppConfig},
+    create_rocket,
+};
 use uuid::Uuid;
 
 use self::http_client::HttpClient;
@@ -20,11 +23,17 @@ pub mod utils;
 static SINGLE_SETUP: Once = Once::new();
 
 pub async fn setup() -> HttpClient {
+    setup_with_config(|_| {}).await
+}
+
+/// Like `setup`, with the test config changed by `configure` first.
+pub async fn setup_with_config(configure: impl FnOnce(&mut AppConfig)) -> HttpClient {
     SINGLE_SETUP.call_once(|| {
         color_eyre::install().expect("Expected color_eyre to install");
     });
 
     let mut app_config = get_config("test").expect("Expected test config to exist");
+    configure(&mut app_config);
     app_config.backend_port = get_next_available_port().await;
 
     let conn_url_no_db = String::from(